@@ -0,0 +1,107 @@
+//! Compares the scalar tracing of camera rays with the packets of 4 and 8 rays.
+//! Run with `cargo bench --bench packet_tracing`
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use gpu_attempt::{
+    hittable::{Hittable, HittableList, Sphere, Triangle},
+    material::Lambertian,
+    packet::{PacketScene, Primitive},
+    Camera, Color3, Point3, Ray, Vec3,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+const WIDTH: u32 = 400;
+const HEIGHT: u32 = 225;
+const RUNS: u32 = 10;
+
+/// the best time of a few runs
+fn measure(name: &str, mut run: impl FnMut() -> usize) -> Duration {
+    let mut best = Duration::MAX;
+    let mut nb_hits = 0;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        nb_hits = black_box(run());
+        best = best.min(start.elapsed());
+    }
+
+    let rays_per_second = (WIDTH * HEIGHT) as f64 / best.as_secs_f64();
+    println!(
+        "{name:>10}: {best:>10.3?} ({:.2} Mrays/s, {nb_hits} hits)",
+        rays_per_second / 1e6
+    );
+
+    best
+}
+
+fn main() {
+    let material = Lambertian::new(&Color3::white());
+    let mut rng = StdRng::seed_from_u64(46);
+
+    // a field of small spheres and triangles on the ground
+    let mut scene = PacketScene::new();
+    let mut spheres = Vec::new();
+    let mut triangles = Vec::new();
+    for _ in 0..100 {
+        let center = Point3::new(rng.gen_range(-10.0..10.0), 0.5, rng.gen_range(-10.0..10.0));
+        scene.add_sphere(&center, 0.5);
+        spheres.push(Sphere::new(&center, 0.5, &material));
+
+        let vertices = [0; 3].map(|_| center + Vec3::new_clamped_random(-1.0, 1.0, &mut rng));
+        scene.add_triangle(&vertices[0], &vertices[1], &vertices[2]);
+        triangles.push(Triangle::new(
+            &vertices[0],
+            &vertices[1],
+            &vertices[2],
+            &material,
+        ));
+    }
+    let mut world = HittableList::new();
+    spheres.iter().for_each(|sphere| world.add(sphere));
+    triangles.iter().for_each(|triangle| world.add(triangle));
+
+    // camera rays line after line: neighbors in a packet are coherent
+    let camera = Camera::new(
+        &Point3::new(0.0, 6.0, 16.0),
+        &Point3::new(0.0, 0.0, 0.0),
+        &Vec3::new(0.0, 1.0, 0.0),
+        40.0,
+        WIDTH as f64 / HEIGHT as f64,
+        0.0,
+        10.0,
+    );
+    let rays: Vec<Ray> = (0..HEIGHT)
+        .flat_map(|line| (0..WIDTH).map(move |column| (column, line)))
+        .map(|(column, line)| {
+            let s = (column as f64 + 0.5) / WIDTH as f64;
+            let t = (line as f64 + 0.5) / HEIGHT as f64;
+            camera.get_ray(s, t, &mut rng)
+        })
+        .collect();
+
+    let scalar = measure("scalar f64", || {
+        rays.iter()
+            .filter(|ray| world.hit(ray, 0.001, f64::INFINITY).is_some())
+            .count()
+    });
+    let count_hits =
+        |hits: Vec<(f32, Option<Primitive>)>| hits.iter().filter(|(_, hit)| hit.is_some()).count();
+    measure("scalar f32", || {
+        count_hits(scene.intersect_all::<1>(&rays, 0.001, f32::INFINITY))
+    });
+    let packet_4 = measure("packet 4", || {
+        count_hits(scene.intersect_all::<4>(&rays, 0.001, f32::INFINITY))
+    });
+    let packet_8 = measure("packet 8", || {
+        count_hits(scene.intersect_all::<8>(&rays, 0.001, f32::INFINITY))
+    });
+
+    println!(
+        "speedup over the scalar path: {:.2}x with 4 lanes, {:.2}x with 8 lanes",
+        scalar.as_secs_f64() / packet_4.as_secs_f64(),
+        scalar.as_secs_f64() / packet_8.as_secs_f64()
+    );
+}
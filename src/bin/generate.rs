@@ -18,6 +18,6 @@ fn main() {
 
             color.as_ppm(100);
         }
-        println!("");
+        println!();
     }
 }
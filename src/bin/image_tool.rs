@@ -0,0 +1,107 @@
+use std::{env, path::Path, process};
+
+use gpu_attempt::{
+    image_diff::{flip, mse, psnr, ssim, DEFAULT_PIXELS_PER_DEGREE},
+    image_io::{load_image, save_image, FloatImage},
+};
+
+const USAGE: &str = "usage:
+    image_tool convert <input> <output>
+    image_tool compare <reference> <test> [--diff <image>] [--ppd <pixels per degree>] [--max-flip <mean error>]
+
+supported formats: ppm, pgm, pbm, pnm, png, pfm and exr (chosen from the file extension)
+
+compare exits with 1 when the mean FLIP error is above --max-flip, and 2 on any other error";
+
+/// a headless tool (no window needed) to convert images and to compare renders, e.g. on CI machines
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+
+    match args.first().map(String::as_str) {
+        Some("convert") if args.len() == 3 => convert(&args[1], &args[2]),
+        Some("compare") if args.len() >= 3 => compare(&args[1], &args[2], &args[3..]),
+        _ => fail(USAGE),
+    }
+}
+
+fn fail(message: &str) -> ! {
+    eprintln!("{message}");
+    process::exit(2);
+}
+
+fn load(path: &str) -> FloatImage {
+    load_image(Path::new(path))
+        .unwrap_or_else(|error| fail(&format!("cannot read {path}: {error}")))
+}
+
+fn save(path: &str, image: &FloatImage) {
+    save_image(Path::new(path), image)
+        .unwrap_or_else(|error| fail(&format!("cannot write {path}: {error}")));
+}
+
+fn convert(input: &str, output: &str) {
+    save(output, &load(input));
+}
+
+fn compare(reference_path: &str, test_path: &str, options: &[String]) {
+    let mut diff_path = None;
+    let mut pixels_per_degree = DEFAULT_PIXELS_PER_DEGREE;
+    let mut max_flip = None;
+
+    // options all take a value
+    for option in options.chunks(2) {
+        let value = option
+            .get(1)
+            .unwrap_or_else(|| fail(&format!("missing value for {}\n\n{USAGE}", option[0])));
+        let number = || {
+            value
+                .parse::<f64>()
+                .unwrap_or_else(|_| fail(&format!("{} expects a number, got {value}", option[0])))
+        };
+
+        match option[0].as_str() {
+            "--diff" => diff_path = Some(value.as_str()),
+            "--ppd" => pixels_per_degree = number(),
+            "--max-flip" => max_flip = Some(number()),
+            unknown => fail(&format!("unknown option {unknown}\n\n{USAGE}")),
+        }
+    }
+
+    let reference = load(reference_path);
+    let test = load(test_path);
+
+    if !reference.same_size(&test) {
+        fail(&format!(
+            "the images do not have the same size: {}x{} and {}x{}",
+            reference.width(),
+            reference.height(),
+            test.width(),
+            test.height()
+        ));
+    }
+
+    let errors = flip(&reference, &test, pixels_per_degree);
+
+    println!("MSE:  {:.6}", mse(&reference, &test));
+    println!("PSNR: {:.2} dB", psnr(&reference, &test));
+    println!("SSIM: {:.4}", ssim(&reference, &test));
+    println!(
+        "FLIP: {:.4} (mean), {:.4} (max)",
+        errors.mean(),
+        errors.max()
+    );
+
+    if let Some(path) = diff_path {
+        save(path, &errors.to_false_color());
+    }
+
+    if let Some(max_flip) = max_flip {
+        if errors.mean() > max_flip {
+            eprintln!(
+                "the images differ: mean FLIP error {:.4} above {max_flip}",
+                errors.mean()
+            );
+            process::exit(1);
+        }
+    }
+}
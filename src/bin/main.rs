@@ -1,134 +1,74 @@
 use std::{
+    env,
     fs::File,
-    io::Write,
-    sync::{Arc, Mutex},
-    thread,
-    time::Instant,
+    io::{self, Write},
+    path::Path,
+    sync::atomic::Ordering,
+    time::{Duration, Instant},
 };
 
 use gpu_attempt::{
-    hittable::{Hittable, HittableList, Quad, Sphere, Tetrahedron, Triangle},
+    hittable::{HittableList, Quad, Sphere, Tetrahedron, Triangle},
     material::{Dielectric, Lambertian, Material, Metal},
-    Camera, Color3, Point3, Ray, Vec3,
+    render::{
+        compute_aovs, load_checkpoint, save_aovs_exr, save_checkpoint, scene_hash,
+        AdaptiveSampling, Denoiser, Integrator, PixelStats, ProgressiveRendering, RenderState,
+    },
+    Camera, Color3, Point3, Vec3,
 };
-use rand::{rngs::ThreadRng, Rng};
-
-fn ray_color(ray: &Ray, world: &dyn Hittable, depth: u32, rng: &mut ThreadRng) -> Color3 {
-    // the ray bounced too many times, we abort the ray and return no light (black)
-    if depth == 0 {
-        return Color3::new(0.0, 0.0, 0.0);
-    }
-
-    // using 0.001 instead of 0.0 to fix shadow acne (ray reflected not exactly at 0)
-    if let Some(hit_record) = world.hit(ray, 0.001, f64::INFINITY) {
-        let (scattered, attenuation, is_reflected) =
-            hit_record.material.scatter(ray, &hit_record, rng);
-
-        if is_reflected {
-            return ray_color(&scattered, world, depth - 1, rng).hadamar(&attenuation);
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// save an image in ppm format
+/// the incoming image is expected to be an vector of rows.
+/// So we iterate this way: image[line][column]
+/// the colors are expected to be already averaged: only the sRGB encoding is applied
+fn save_ppm(image: &[Vec<Color3>], path: &str) -> io::Result<()> {
+    let nb_lines = image.len();
+    let nb_columns = image[0].len();
+
+    let mut file = File::create(path)?;
+    writeln!(file, "P3")?;
+    writeln!(file, "{} {}", nb_columns, nb_lines)?;
+    writeln!(file, "255")?;
+
+    writeln!(file)?;
+    for row in image.iter().rev() {
+        for pixel_color in row {
+            writeln!(file, "{}", pixel_color.as_ppm(1))?;
         }
-
-        return Color3::black();
+        writeln!(file)?;
     }
 
-    let unit_direction = ray.direction().normalize();
-    let t = 0.5 * (unit_direction.y() + 1.0);
-
-    Color3::new(1.0, 1.0, 1.0) * (1.0 - t) + Color3::new(0.5, 0.7, 1.0) * t
+    Ok(())
 }
 
-/// compute a scene:
-/// if dry_mode is true, computation are made but are not streamed to stdout
-fn compute_scene(
-    camera: &Camera,
-    world: &HittableList,
-    image_width: u32,
-    image_height: u32,
-    samples_per_pixel: u32,
-    max_depth: u32,
-) -> Vec<Vec<Color3>> {
-    let scene = Arc::new(Mutex::new(vec![
-        vec![Color3::white(); image_width as usize];
-        image_height as usize
-    ]));
-
-    let nb_thread = 6;
-
-    // taking in account rounding up
-    let nb_line_per_thread = if image_height % nb_thread == 0 {
-        image_height / nb_thread
-    } else {
-        1 + image_height / nb_thread
-    };
-
-    thread::scope(|scope| {
-        let mut handles = Vec::new();
-
-        for num_thread in 0..nb_thread {
-            // cloning the arc to be able to share reference of underlying data
-            let scene = scene.clone();
-
-            let current_line = num_thread * nb_line_per_thread;
-
-            let handle = scope.spawn(move || {
-                let mut rng = rand::thread_rng();
-
-                for i in current_line..(current_line + nb_line_per_thread).min(image_height) {
-                    // eprint!("\r remaining lines {}", image_height - i);
-                    let mut row = Vec::with_capacity(image_width as usize);
-
-                    for j in 0..image_width {
-                        let mut pixel_color = Color3::black();
-
-                        for _ in 0..samples_per_pixel {
-                            let u = (j as f64 + rng.gen::<f64>()) / (image_width - 1) as f64;
-                            let v = (i as f64 + rng.gen::<f64>()) / (image_height - 1) as f64;
-
-                            let ray = camera.get_ray(u, v, &mut rng);
-
-                            pixel_color += ray_color(&ray, world, max_depth, &mut rng);
-                        }
-
-                        row.push(pixel_color);
-                    }
-                    scene.lock().unwrap()[i as usize] = row;
-                }
-            });
-            handles.push(handle);
-        }
-
-        for handle in handles {
-            handle
-                .join()
-                .expect("an error occured while joining threads");
-        }
-    });
-
-    Arc::try_unwrap(scene).unwrap().into_inner().unwrap()
+/// save a scene in ppm format
+/// each pixel is scaled down by its own number of samples
+fn save_scene(scene: &[Vec<PixelStats>], path: &str) -> io::Result<()> {
+    let image = scene
+        .iter()
+        .map(|row| row.iter().map(|pixel| pixel.color()).collect())
+        .collect::<Vec<_>>();
+
+    save_ppm(&image, path)
 }
 
-/// save a scene in ppm format
-/// the incoming schene is expected to be an vector of rows.
-/// So we iterate this way: scene[line][column]
-/// the sample per pixels is necessary to scale colors down and then apply gamma correction
-fn save_scene(scene: &Vec<Vec<Color3>>, samples_per_pixel: u32) {
-    let nb_lines = scene.len();
-    let nb_columns = scene[0].len();
-
-    let mut file = File::create("scene.ppm").expect("could not create the file");
-    write!(file, "P3\n");
-    write!(file, "{} {}\n", nb_columns, nb_lines);
-    write!(file, "255\n");
-
-    write!(file, "\n");
-    for i in (0..nb_lines).rev() {
-        for j in 0..nb_columns {
-            let pixel_color = scene[i][j];
-
-            write!(file, "{}\n", pixel_color.as_ppm(samples_per_pixel));
-        }
-        write!(file, "\n");
-    }
+/// debug output: save the number of samples taken per pixel as a heatmap in ppm format
+/// blue: few samples, red: the whole sample budget was used
+fn save_sample_heatmap(scene: &[Vec<PixelStats>], max_samples: u32, path: &str) -> io::Result<()> {
+    let heatmap = scene
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|pixel| {
+                    let t = pixel.count() as f64 / max_samples as f64;
+                    Color3::new(t, 0.0, 1.0 - t)
+                })
+                .collect()
+        })
+        .collect::<Vec<_>>();
+
+    save_ppm(&heatmap, path)
 }
 
 fn main() {
@@ -136,16 +76,43 @@ fn main() {
 
     let starting_time = Instant::now();
 
+    // resuming a previous render from its checkpoint (e.g. after a crash)
+    let resume = env::args().any(|arg| arg == "--resume");
+    let checkpoint_path = Path::new("scene.checkpoint");
+
     // Rng --------------------------------------
-    let mut rng = rand::thread_rng();
+    // the scene is generated from a fixed seed: the same scene must be built again to resume a render
+    let mut rng = StdRng::seed_from_u64(2022);
 
     // Image ------------------------------------
     let aspect_ratio = 3.0 / 2.0;
     let image_width: u32 = 1200;
     let image_height = (image_width as f64 / aspect_ratio) as u32;
-    let samples_per_pixel = 5; // 500 is great
-                               // max number of ray bounces
+    // adaptive sampling: noisy pixels get more samples than flat ones
+    let min_samples_per_pixel = 5;
+    let max_samples_per_pixel = 50; // 500 is great
+    let max_relative_error = 0.05;
+    let sampling = AdaptiveSampling::new(
+        min_samples_per_pixel,
+        max_samples_per_pixel,
+        max_relative_error,
+    );
+    // debug: also save the number of samples per pixel in samples.ppm
+    let save_heatmap = false;
+    // save the beauty image with its depth, normal, albedo, position and object id passes in aovs.exr
+    let save_aovs = true;
+    let aov_samples_per_pixel = 4;
+    // denoise the render, guided by the output variables, and save it in scene_denoised.ppm
+    let denoise = true;
+    // progressive rendering: the image is refined one sample per pixel per pass.
+    // scene.ppm is rewritten every `passes_per_save` passes (0: only at the end).
+    // The render stops when the sampling is done, after the time limit, or on ctrl-c
+    let passes_per_save = 10;
+    let time_limit: Option<Duration> = None; // e.g. Some(Duration::from_secs(3600))
+                                             // max number of ray bounces
     let max_depth = 50;
+    // spectral rendering shows the dispersion of glass, but is noisier than rgb
+    let integrator = Integrator::Rgb;
 
     // World ------------------------------------
 
@@ -164,7 +131,8 @@ fn main() {
         &(Point3::new(-1.0, 0.0, 1.0) + displacement),
         &(Point3::new(-1.0, 0.0, -1.0) + displacement),
         &material_ground,
-    );
+    )
+    .expect("the ground is a square");
 
     world.add(&quad_ground);
 
@@ -200,7 +168,7 @@ fn main() {
                 let sphere_material: Box<dyn Material> = if random_choose < 0.8 {
                     // lambertian (diffuse)
                     let albedo = Color3::new_clamped_random(0.0, 1.0, &mut rng)
-                        .hadamar(&Color3::new_clamped_random(0.0, 1.0, &mut rng));
+                        * Color3::new_clamped_random(0.0, 1.0, &mut rng);
 
                     Box::new(Lambertian::new(&albedo))
                 } else if random_choose < 0.95 {
@@ -269,19 +237,87 @@ fn main() {
 
     // Render -----------------------------------
 
-    let scene = compute_scene(
+    let progressive = ProgressiveRendering::new(passes_per_save, time_limit);
+
+    // ctrl-c stops the render after the current pass, the current image is then saved as usual
+    let interrupted = progressive.interrupt_flag();
+    ctrlc::set_handler(move || {
+        eprintln!("interrupted, finishing the current pass...");
+        interrupted.store(true, Ordering::SeqCst);
+    })
+    .expect("could not set the ctrl-c handler");
+
+    let hash = scene_hash(
         &camera,
         &world,
         image_width,
         image_height,
-        samples_per_pixel,
         max_depth,
+        integrator,
     );
 
+    let mut state = if resume {
+        let state = load_checkpoint(checkpoint_path, hash)
+            .unwrap_or_else(|error| panic!("cannot resume the render: {error}"));
+        eprintln!("resuming the render after {} passes", state.nb_passes());
+        state
+    } else {
+        RenderState::new(image_width, image_height, rand::random())
+    };
+
+    let stop_reason = progressive.render(
+        &camera,
+        &world,
+        &mut state,
+        &sampling,
+        max_depth,
+        integrator,
+        |state| {
+            eprintln!("pass {} done, saving the current image", state.nb_passes());
+            save_scene(state.scene(), "scene.ppm").expect("could not save the scene");
+            save_checkpoint(checkpoint_path, state, hash).expect("could not save the checkpoint");
+        },
+    );
+    eprintln!("rendering stopped: {:?}", stop_reason);
+
     let duration = starting_time.elapsed();
     eprintln!("the rendering function took {:?} to run", duration);
 
     eprintln!("Saving the values to a file...");
     // comment this for benchmarks
-    save_scene(&scene, samples_per_pixel);
+    save_scene(state.scene(), "scene.ppm").expect("could not save the scene");
+    save_checkpoint(checkpoint_path, &state, hash).expect("could not save the checkpoint");
+
+    if save_aovs || denoise {
+        eprintln!("Computing the output variables...");
+        let aovs = compute_aovs(
+            &camera,
+            &world,
+            image_width,
+            image_height,
+            aov_samples_per_pixel,
+        );
+
+        if save_aovs {
+            save_aovs_exr(Path::new("aovs.exr"), state.scene(), &aovs)
+                .expect("could not save the output variables");
+        }
+
+        if denoise {
+            eprintln!("Denoising...");
+            let image = state
+                .scene()
+                .iter()
+                .map(|row| row.iter().map(|pixel| pixel.color()).collect::<Vec<_>>())
+                .collect::<Vec<_>>();
+
+            let denoised = Denoiser::default().denoise(&image, &aovs);
+            save_ppm(&denoised, "scene_denoised.ppm").expect("could not save the denoised scene");
+        }
+    }
+
+    if save_heatmap {
+        save_sample_heatmap(state.scene(), sampling.max_samples(), "samples.ppm")
+            .expect("could not save the sample heatmap");
+    }
 }
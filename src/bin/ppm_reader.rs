@@ -1,6 +1,6 @@
-use core::panic;
-use std::{env, fs};
+use std::{env, process};
 
+use gpu_attempt::image_io::NetpbmImage;
 use nannou::prelude::*;
 
 fn main() {
@@ -12,10 +12,8 @@ fn main() {
 }
 
 struct Model {
-    magic_number: String,
     width: u32,
     height: u32,
-    max_color: u32,
     values: Vec<(f32, f32, f32)>,
 }
 
@@ -25,62 +23,25 @@ fn model(_app: &App) -> Model {
 
     // the name of the file shoul de the second env parameter
     if args.len() != 2 {
-        panic!("Only the name of the file is expected");
+        eprintln!("usage: ppm_reader <image.ppm>");
+        process::exit(1);
     }
 
-    // extracting the lines from the file
-    let raw_lines = fs::read_to_string(args[1].to_string()).expect("Unable to open the file");
-    let lines: Vec<&str> = raw_lines
-        .split('\n')
-        // and omiting lines starting with #
-        .filter(|line| !line.starts_with('#'))
-        // removing extra spaces
-        .map(|line| line.trim())
-        // removing empty lines
-        .filter(|line| !line.is_empty())
-        .collect();
-
-    let magic_number = lines[0].to_string();
-    if magic_number != "P3" {
-        panic!("only P3 supported");
-    }
+    // any Netpbm image is accepted (P1 to P6)
+    let image = NetpbmImage::open(&args[1]).unwrap_or_else(|error| {
+        eprintln!("cannot display {}: {error}", args[1]);
+        process::exit(1);
+    });
 
-    let mut geometry_line = lines[1].split_ascii_whitespace();
-    let (width, height) = (
-        geometry_line
-            .next()
-            .expect("no width found")
-            .parse::<u32>()
-            .expect("width not a number"),
-        geometry_line
-            .next()
-            .expect("no height found")
-            .parse::<u32>()
-            .expect("height not a number"),
-    );
-
-    let max_color = lines[2].parse::<u32>().expect("max color not a number");
-
-    let values = lines[3..]
+    let values = image
+        .colors()
         .iter()
-        .map(|line| {
-            let rgbs = line
-                .split_ascii_whitespace()
-                .map(|value| value.parse::<f32>().expect("cannot convert one value"))
-                .collect::<Vec<_>>();
-            (
-                rgbs[0] / max_color as f32,
-                rgbs[1] / max_color as f32,
-                rgbs[2] / max_color as f32,
-            )
-        })
+        .map(|color| (color.r() as f32, color.g() as f32, color.b() as f32))
         .collect::<Vec<_>>();
 
     Model {
-        magic_number,
-        width,
-        height,
-        max_color,
+        width: image.width(),
+        height: image.height(),
         values,
     }
 }
@@ -1,7 +1,8 @@
-use rand::rngs::ThreadRng;
+use rand::RngCore;
 
 use crate::{Point3, Ray, Vec3};
 
+#[derive(Debug)]
 pub struct Camera {
     origin: Point3,
     lower_left_corner: Point3,
@@ -9,7 +10,6 @@ pub struct Camera {
     vertical: Vec3,
     u: Vec3,
     v: Vec3,
-    w: Vec3,
     lens_radius: f64,
 }
 
@@ -17,7 +17,6 @@ impl Camera {
     /// vertical_field of view in degree
     /// aspect_ratio: e.g. 16 / 9
     /// vup:  view up vector
-
     pub fn new(
         look_from: &Point3,
         look_at: &Point3,
@@ -51,11 +50,10 @@ impl Camera {
             u,
             v,
             vertical,
-            w,
         }
     }
 
-    pub fn get_ray(&self, s: f64, t: f64, rng: &mut ThreadRng) -> Ray {
+    pub fn get_ray(&self, s: f64, t: f64, rng: &mut dyn RngCore) -> Ray {
         let rd = Vec3::new_random_in_unit_disk(rng) * self.lens_radius;
         let offset = self.u * rd.x() + self.v * rd.y();
 
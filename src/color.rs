@@ -0,0 +1,248 @@
+use std::{iter::Sum, ops};
+
+use rand::Rng;
+
+use crate::Vec3;
+
+/// A linear rgb color. Colors are not vectors: they are multiplied channel by channel,
+/// and can not be mixed up with points or directions
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct Color3 {
+    r: f64,
+    g: f64,
+    b: f64,
+}
+
+impl Color3 {
+    pub fn new(r: f64, g: f64, b: f64) -> Self {
+        Self { r, g, b }
+    }
+
+    /// a black Color3 with all chanels at 0
+    pub fn black() -> Self {
+        Self::new(0.0, 0.0, 0.0)
+    }
+
+    pub fn white() -> Self {
+        Self::new(1.0, 1.0, 1.0)
+    }
+
+    /// returns a new random color with channels in the specified range
+    pub fn new_clamped_random(min: f64, max: f64, rng: &mut (impl Rng + ?Sized)) -> Self {
+        Self {
+            r: rng.gen_range(min..=max),
+            g: rng.gen_range(min..=max),
+            b: rng.gen_range(min..=max),
+        }
+    }
+
+    pub fn r(&self) -> f64 {
+        self.r
+    }
+    pub fn g(&self) -> f64 {
+        self.g
+    }
+    pub fn b(&self) -> f64 {
+        self.b
+    }
+
+    /// the color with `f` applied to each channel
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self::new(f(self.r), f(self.g), f(self.b))
+    }
+
+    pub fn as_ppm(&self, samples_per_pixel: u32) -> String {
+        let scale = 1.0 / samples_per_pixel as f64;
+
+        // scale the color per sample and encode it for display
+        let encoded = (*self * scale).to_srgb();
+        let (r, g, b) = (encoded.r, encoded.g, encoded.b);
+
+        let ir = (256.0 * r.clamp(0.0, 0.999)) as u32;
+        let ig = (256.0 * g.clamp(0.0, 0.999)) as u32;
+        let ib = (256.0 * b.clamp(0.0, 0.999)) as u32;
+
+        format!("{ir} {ig} {ib}")
+    }
+
+    /// relative luminance of a linear rgb color (Rec. 709 weights)
+    pub fn luminance(&self) -> f64 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
+    }
+
+    /// euclidean distance between two colors, in linear rgb
+    pub fn distance(&self, other: &Self) -> f64 {
+        let difference = *self - *other;
+        (difference.r * difference.r + difference.g * difference.g + difference.b * difference.b)
+            .sqrt()
+    }
+
+    /// CIE XYZ coordinates of a linear srgb color (d65 white)
+    pub fn to_xyz(&self) -> Vec3 {
+        Vec3::new(
+            0.412_456_4 * self.r + 0.357_576_1 * self.g + 0.180_437_5 * self.b,
+            0.212_672_9 * self.r + 0.715_152_2 * self.g + 0.072_175_0 * self.b,
+            0.019_333_9 * self.r + 0.119_192_0 * self.g + 0.950_304_1 * self.b,
+        )
+    }
+
+    /// the linear srgb color of CIE XYZ coordinates, inverse of `to_xyz`
+    pub fn from_xyz(xyz: &Vec3) -> Self {
+        Self::new(
+            3.240_454_2 * xyz.x() - 1.537_138_5 * xyz.y() - 0.498_531_4 * xyz.z(),
+            -0.969_266_0 * xyz.x() + 1.876_010_8 * xyz.y() + 0.041_556_0 * xyz.z(),
+            0.055_643_4 * xyz.x() - 0.204_025_9 * xyz.y() + 1.057_225_2 * xyz.z(),
+        )
+    }
+
+    /// the color with every channel clamped in [min, max]
+    pub fn clamp(&self, min: f64, max: f64) -> Self {
+        self.map(|channel| channel.clamp(min, max))
+    }
+
+    /// linear interpolation: self for t = 0, other for t = 1
+    pub fn lerp(&self, other: &Self, t: f64) -> Self {
+        *self * (1.0 - t) + *other * t
+    }
+
+    /// the sRGB encoded color, for 8 bits formats (ppm, png) and displays
+    /// (channels are clamped in [0, 1])
+    pub fn to_srgb(&self) -> Self {
+        self.map(|linear| {
+            let linear = linear.clamp(0.0, 1.0);
+            if linear <= 0.0031308 {
+                12.92 * linear
+            } else {
+                1.055 * linear.powf(1.0 / 2.4) - 0.055
+            }
+        })
+    }
+
+    /// the linear color of sRGB encoded channels, inverse of `to_srgb`
+    pub fn from_srgb(encoded: &Self) -> Self {
+        encoded.map(|encoded| {
+            if encoded <= 0.04045 {
+                encoded / 12.92
+            } else {
+                ((encoded + 0.055) / 1.055).powf(2.4)
+            }
+        })
+    }
+}
+
+// Overloading basic operators for convenience
+
+impl ops::Add for Color3 {
+    type Output = Color3;
+    fn add(self, rhs: Self) -> Self {
+        Self::new(self.r + rhs.r, self.g + rhs.g, self.b + rhs.b)
+    }
+}
+
+impl ops::Sub for Color3 {
+    type Output = Color3;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.r - rhs.r, self.g - rhs.g, self.b - rhs.b)
+    }
+}
+
+/// channels multiplied one by one: the light filtered by a surface
+impl ops::Mul for Color3 {
+    type Output = Color3;
+    fn mul(self, rhs: Self) -> Self {
+        Self::new(self.r * rhs.r, self.g * rhs.g, self.b * rhs.b)
+    }
+}
+
+impl ops::Mul<f64> for Color3 {
+    type Output = Color3;
+    fn mul(self, rhs: f64) -> Self {
+        self.map(|channel| channel * rhs)
+    }
+}
+
+impl ops::Mul<Color3> for f64 {
+    type Output = Color3;
+    fn mul(self, rhs: Color3) -> Color3 {
+        rhs * self
+    }
+}
+
+impl ops::Div<f64> for Color3 {
+    type Output = Color3;
+    fn div(self, rhs: f64) -> Self {
+        self * (1.0 / rhs)
+    }
+}
+
+impl ops::AddAssign for Color3 {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl ops::MulAssign for Color3 {
+    fn mul_assign(&mut self, rhs: Self) {
+        *self = *self * rhs;
+    }
+}
+
+impl ops::MulAssign<f64> for Color3 {
+    fn mul_assign(&mut self, rhs: f64) {
+        *self = *self * rhs;
+    }
+}
+
+impl ops::DivAssign<f64> for Color3 {
+    fn div_assign(&mut self, rhs: f64) {
+        *self = *self / rhs;
+    }
+}
+
+impl Sum for Color3 {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Color3::black(), |sum, color| sum + color)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_should_filter_channel_by_channel() {
+        let light = Color3::new(1.0, 0.5, 2.0);
+        let surface = Color3::new(0.5, 0.5, 0.0);
+
+        assert_eq!(light * surface, Color3::new(0.5, 0.25, 0.0));
+        assert_eq!(2.0 * surface, Color3::new(1.0, 1.0, 0.0));
+        assert_eq!((light + surface) / 2.0, Color3::new(0.75, 0.5, 1.0));
+    }
+
+    #[test]
+    fn it_should_interpolate_and_clamp() {
+        let black = Color3::black();
+        let white = Color3::white();
+
+        assert_eq!(black.lerp(&white, 0.25), Color3::new(0.25, 0.25, 0.25));
+        assert_eq!(
+            Color3::new(-1.0, 0.5, 3.0).clamp(0.0, 1.0),
+            Color3::new(0.0, 0.5, 1.0)
+        );
+        assert!((white.luminance() - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_should_convert_to_srgb_and_back() {
+        // the linear middle grey is encoded around 188 / 255
+        let grey = Color3::new(0.5, 0.5, 0.5).to_srgb();
+        assert!((grey.r() - 0.7354).abs() < 1e-4);
+
+        for color in [Color3::new(0.0, 0.001, 0.002), Color3::new(0.2, 0.5, 1.0)] {
+            let error = Color3::from_srgb(&color.to_srgb()) - color;
+            assert!([error.r(), error.g(), error.b()]
+                .iter()
+                .all(|channel| channel.abs() < 1e-12));
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use crate::{Point3, Ray};
+
+use super::Transform;
+
+/// Where the line of a ray crosses a box: the times it enters and leaves it,
+/// and the axis (0 for x, 1 for y, 2 for z) of the face crossed each time
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct SlabCrossing {
+    pub(crate) t_entry: f64,
+    pub(crate) entry_axis: usize,
+    pub(crate) t_exit: f64,
+    pub(crate) exit_axis: usize,
+}
+
+/// An axis aligned bounding box
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    min: Point3,
+    max: Point3,
+}
+
+impl Aabb {
+    /// the box between two opposite corners, in any order
+    pub fn new(corner_0: &Point3, corner_1: &Point3) -> Self {
+        Self {
+            min: corner_0.min(corner_1),
+            max: corner_0.max(corner_1),
+        }
+    }
+
+    /// the smallest box containing all the points (None without points)
+    pub fn from_points<'p>(points: impl IntoIterator<Item = &'p Point3>) -> Option<Self> {
+        points
+            .into_iter()
+            .map(|point| Self::new(point, point))
+            .reduce(|a, b| a.union(&b))
+    }
+
+    pub fn min(&self) -> Point3 {
+        self.min
+    }
+
+    pub fn max(&self) -> Point3 {
+        self.max
+    }
+
+    pub fn center(&self) -> Point3 {
+        (self.min + self.max) / 2.0
+    }
+
+    /// the smallest box containing both boxes
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            min: self.min.min(&other.min),
+            max: self.max.max(&other.max),
+        }
+    }
+
+    /// the slab test: true if the ray goes through the box between `t_min` and `t_max`
+    pub fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.slabs(ray)
+            .is_some_and(|crossing| crossing.t_entry <= t_max && crossing.t_exit >= t_min)
+    }
+
+    /// the slab method: the line of the ray is clipped by the pair of planes of each axis,
+    /// it crosses the box if the three intervals overlap (the times are not limited to the ray)
+    pub(crate) fn slabs(&self, ray: &Ray) -> Option<SlabCrossing> {
+        let origin = ray.origin();
+        let direction = ray.direction();
+
+        let mut crossing = SlabCrossing {
+            t_entry: f64::NEG_INFINITY,
+            entry_axis: 0,
+            t_exit: f64::INFINITY,
+            exit_axis: 0,
+        };
+
+        for axis in 0..3 {
+            let (origin, direction) = (origin[axis], direction[axis]);
+            let (min, max) = (self.min[axis], self.max[axis]);
+
+            // parallel to the slab: either always in it, or never
+            if direction == 0.0 {
+                if origin < min || origin > max {
+                    return None;
+                }
+                continue;
+            }
+
+            let (t_0, t_1) = ((min - origin) / direction, (max - origin) / direction);
+            let (t_near, t_far) = if t_0 < t_1 { (t_0, t_1) } else { (t_1, t_0) };
+
+            if t_near > crossing.t_entry {
+                crossing.t_entry = t_near;
+                crossing.entry_axis = axis;
+            }
+            if t_far < crossing.t_exit {
+                crossing.t_exit = t_far;
+                crossing.exit_axis = axis;
+            }
+        }
+
+        (crossing.t_entry <= crossing.t_exit).then_some(crossing)
+    }
+
+    /// the box containing this box once transformed
+    pub fn transformed(&self, transform: &Transform) -> Self {
+        let corners: Vec<Point3> = (0..8)
+            .map(|corner| {
+                let pick = |bit: usize, axis: usize| {
+                    if corner & bit == 0 {
+                        self.min[axis]
+                    } else {
+                        self.max[axis]
+                    }
+                };
+
+                transform.apply_point(&Point3::new(pick(1, 0), pick(2, 1), pick(4, 2)))
+            })
+            .collect();
+
+        Self::from_points(&corners).unwrap()
+    }
+
+    pub fn contains(&self, point: &Point3) -> bool {
+        (self.min.x()..=self.max.x()).contains(&point.x())
+            && (self.min.y()..=self.max.y()).contains(&point.y())
+            && (self.min.z()..=self.max.z()).contains(&point.z())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Vec3;
+
+    use super::*;
+
+    #[test]
+    fn it_should_surround_points() {
+        let points = [
+            Point3::new(1.0, -2.0, 0.0),
+            Point3::new(-1.0, 3.0, 0.5),
+            Point3::new(0.0, 0.0, -4.0),
+        ];
+        let aabb = Aabb::from_points(&points).unwrap();
+
+        assert_eq!(aabb.min(), Point3::new(-1.0, -2.0, -4.0));
+        assert_eq!(aabb.max(), Point3::new(1.0, 3.0, 0.5));
+        assert!(points.iter().all(|point| aabb.contains(point)));
+        assert!(Aabb::from_points(&[]).is_none());
+    }
+
+    #[test]
+    fn it_should_be_hit_by_rays_going_through() {
+        let aabb = Aabb::new(&Point3::new(-1.0, -1.0, -1.0), &Point3::new(1.0, 1.0, 1.0));
+
+        let ray = Ray::new(&Point3::new(-5.0, 0.5, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let crossing = aabb.slabs(&ray).unwrap();
+        assert_eq!((crossing.t_entry, crossing.entry_axis), (4.0, 0));
+        assert_eq!((crossing.t_exit, crossing.exit_axis), (6.0, 0));
+        assert!(aabb.hit(&ray, 0.0, f64::INFINITY));
+
+        // the box is behind, or too far
+        assert!(!aabb.hit(&ray, 7.0, f64::INFINITY));
+        assert!(!aabb.hit(&ray, 0.0, 3.0));
+
+        // passing next to the box, parallel to a face
+        let ray = Ray::new(&Point3::new(-5.0, 2.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(!aabb.hit(&ray, 0.0, f64::INFINITY));
+
+        // diagonal ray, entering through the y face
+        let ray = Ray::new(&Point3::new(-1.5, 3.0, 0.0), &Vec3::new(1.0, -1.0, 0.0));
+        let crossing = aabb.slabs(&ray).unwrap();
+        assert_eq!((crossing.t_entry, crossing.entry_axis), (2.0, 1));
+        assert_eq!((crossing.t_exit, crossing.exit_axis), (2.5, 0));
+    }
+}
@@ -0,0 +1,202 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray,
+};
+
+/// A bilinear patch: the surface swept between two opposite edges of four vertices,
+/// p(u, v) = (1 - u)(1 - v) vertex_00 + u (1 - v) vertex_10 + u v vertex_11 + (1 - u) v vertex_01.
+/// Unlike `Quad`, the vertices do not need to be in the same plane: the patch is curved.
+/// The texture coordinates are (u, v), the front face is on the side of dp/du x dp/dv
+#[derive(Debug)]
+pub struct BilinearPatch<'a> {
+    vertex_00: Point3,
+    vertex_10: Point3,
+    vertex_11: Point3,
+    vertex_01: Point3,
+    material: &'a dyn Material,
+}
+
+impl<'a> BilinearPatch<'a> {
+    /// the vertices go around the patch: (u, v) = (0, 0), (1, 0), (1, 1) and (0, 1)
+    pub fn new(
+        vertex_00: &Point3,
+        vertex_10: &Point3,
+        vertex_11: &Point3,
+        vertex_01: &Point3,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self {
+            vertex_00: *vertex_00,
+            vertex_10: *vertex_10,
+            vertex_11: *vertex_11,
+            vertex_01: *vertex_01,
+            material,
+        }
+    }
+}
+
+impl<'a> Hittable for BilinearPatch<'a> {
+    /// exact intersection, from "Cool Patches: A Geometric Approach to Ray/Bilinear Patch Intersections"
+    /// (A. Reshetov, Ray Tracing Gems): the patch is a family of segments along v, u is the root of a quadratic
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let direction = ray.direction();
+        // the vertices relative to the origin of the ray
+        let q_00 = self.vertex_00 - ray.origin();
+        let q_10 = self.vertex_10 - ray.origin();
+        let edge_00 = self.vertex_01 - self.vertex_00;
+        let edge_11 = self.vertex_11 - self.vertex_10;
+        let normal_term =
+            (self.vertex_10 - self.vertex_00).cross(&(self.vertex_01 - self.vertex_11));
+
+        // the segment at u meets the ray for a u * u + b * u + c = 0
+        let c = q_00.cross(&direction).dot(&edge_00);
+        let a = normal_term.dot(&direction);
+        let b = q_10.cross(&direction).dot(&edge_11) - (c + a);
+
+        let roots: Vec<f64> = if a.abs() < 1e-12 {
+            if b.abs() < 1e-12 {
+                return None;
+            }
+            vec![-c / b]
+        } else {
+            let discriminant = b * b - 4.0 * a * c;
+            if discriminant < 0.0 {
+                return None;
+            }
+
+            // avoiding the cancellation of -b + sqrt(discriminant)
+            let q = -0.5 * (b + discriminant.sqrt().copysign(b));
+            if q == 0.0 {
+                vec![0.0]
+            } else {
+                vec![q / a, c / q]
+            }
+        };
+
+        // for each u, the time of the hit and v on the segment at u
+        let (t, u, v) = roots
+            .into_iter()
+            .filter(|u| (0.0..=1.0).contains(u))
+            .filter_map(|u| {
+                let start = q_00 + (q_10 - q_00) * u;
+                let along = edge_00 + (edge_11 - edge_00) * u;
+
+                // closest points of the lines of the ray and of the segment
+                let cross = direction.cross(&along);
+                let determinant = cross.mag_squared();
+                if determinant < 1e-24 {
+                    return None;
+                }
+                let n = cross.cross(&start);
+                let t = n.dot(&along) / determinant;
+                let v = n.dot(&direction) / determinant;
+
+                ((0.0..=1.0).contains(&v) && t >= t_min && t <= t_max).then_some((t, u, v))
+            })
+            .min_by(|(t_0, _, _), (t_1, _, _)| t_0.total_cmp(t_1))?;
+
+        let point = ray.at(t);
+        let du =
+            (self.vertex_10 - self.vertex_00) * (1.0 - v) + (self.vertex_11 - self.vertex_01) * v;
+        let dv = edge_00 * (1.0 - u) + edge_11 * u;
+        let outward_normal = du.cross(&dv).normalize();
+
+        let mut hit_record = HitRecord::new(ray, &point, &outward_normal, self.material, t);
+        hit_record.u = u;
+        hit_record.v = v;
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // the patch is within the convex hull of its vertices
+        Aabb::from_points([
+            &self.vertex_00,
+            &self.vertex_10,
+            &self.vertex_11,
+            &self.vertex_01,
+        ])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{hittable::Quad, material::Lambertian, Color3, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn it_should_hit_flat_patches_like_quads() {
+        let material_black = Lambertian::new(&Color3::black());
+        let corners = [
+            Point3::new(0.0, 0.0, 0.0),
+            Point3::new(2.0, 0.0, 1.0),
+            Point3::new(2.5, 1.0, 1.0),
+            Point3::new(0.5, 1.0, 0.0),
+        ];
+        let patch = BilinearPatch::new(
+            &corners[0],
+            &corners[1],
+            &corners[2],
+            &corners[3],
+            &material_black,
+        );
+        let quad = Quad::new(
+            &corners[0],
+            &corners[1],
+            &corners[2],
+            &corners[3],
+            &material_black,
+        )
+        .unwrap();
+
+        for (x, y) in [(0.5, 0.5), (1.9, 0.1), (0.6, 0.9), (3.0, 0.5), (0.1, 0.9)] {
+            let ray = Ray::new(&Point3::new(x, y, 10.0), &Vec3::new(0.1, 0.0, -1.0));
+            let expected = quad.hit(&ray, 0.0, f64::INFINITY);
+            let hit_record = patch.hit(&ray, 0.0, f64::INFINITY);
+
+            assert_eq!(hit_record.is_some(), expected.is_some());
+            if let (Some(hit_record), Some(expected)) = (hit_record, expected) {
+                assert!((hit_record.t - expected.t).abs() < 1e-9);
+                assert!((hit_record.normal - expected.normal).mag() < 1e-9);
+                assert_eq!(hit_record.front_face, expected.front_face);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_hit_curved_patches() {
+        let material_black = Lambertian::new(&Color3::black());
+
+        // the saddle z = x y over the unit square
+        let patch = BilinearPatch::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(1.0, 0.0, 0.0),
+            &Point3::new(1.0, 1.0, 1.0),
+            &Point3::new(0.0, 1.0, 0.0),
+            &material_black,
+        );
+
+        for (x, y) in [(0.5, 0.5), (0.2, 0.9), (0.75, 0.3)] {
+            let ray = Ray::new(&Point3::new(x, y, 10.0), &Vec3::new(0.0, 0.0, -2.0));
+            let hit_record = patch.hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+            assert!((hit_record.point - Point3::new(x, y, x * y)).mag() < 1e-9);
+            assert!((hit_record.u - x).abs() < 1e-9);
+            assert!((hit_record.v - y).abs() < 1e-9);
+            let expected_normal = Vec3::new(-y, -x, 1.0).normalize();
+            assert!((hit_record.normal - expected_normal).mag() < 1e-9);
+            assert!(hit_record.front_face);
+        }
+
+        // a ray along the surface
+        let ray = Ray::new(&Point3::new(-1.0, 0.5, 0.2), &Vec3::new(1.0, 0.0, 0.0));
+        let hit_record = patch.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!((hit_record.point - Point3::new(0.4, 0.5, 0.2)).mag() < 1e-9);
+
+        // outside of the patch
+        let ray = Ray::new(&Point3::new(1.5, 0.5, 10.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(patch.hit(&ray, 0.0, f64::INFINITY).is_none());
+    }
+}
@@ -0,0 +1,352 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    Ray,
+};
+
+/// maximum number of primitives in a leaf
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug)]
+enum NodeContent {
+    /// the primitives `indices[start..end]`
+    Leaf { start: usize, end: usize },
+    /// indices of the two child nodes
+    Branch { left: usize, right: usize },
+}
+
+#[derive(Debug)]
+struct Node {
+    bounds: Aabb,
+    content: NodeContent,
+}
+
+/// A bounding volume hierarchy over primitives known by their index and bounding box.
+/// The nodes are stored in a flat vector, the root first.
+/// What a primitive is (a triangle of a mesh, a shape, an instance) is left to the structure using the tree
+#[derive(Debug)]
+pub(crate) struct BvhTree {
+    nodes: Vec<Node>,
+    /// the primitives, ordered so each leaf covers a contiguous range
+    indices: Vec<usize>,
+}
+
+impl BvhTree {
+    /// a tree over primitives, `boxes[i]` being the box of the primitive `i`.
+    /// Nodes are split in the middle of their primitives, along the axis their centers spread the most
+    pub(crate) fn new(boxes: &[Aabb]) -> Self {
+        let mut tree = Self {
+            nodes: Vec::with_capacity(2 * boxes.len() / LEAF_SIZE + 1),
+            indices: (0..boxes.len()).collect(),
+        };
+
+        if !boxes.is_empty() {
+            tree.build(boxes, 0, boxes.len());
+        }
+
+        tree
+    }
+
+    /// add the node of the primitives `indices[start..end]` and its children, returns its index
+    fn build(&mut self, boxes: &[Aabb], start: usize, end: usize) -> usize {
+        let primitives = &mut self.indices[start..end];
+        let bounds = primitives
+            .iter()
+            .map(|&index| boxes[index])
+            .reduce(|a, b| a.union(&b))
+            .unwrap();
+
+        let node_index = self.nodes.len();
+        self.nodes.push(Node {
+            bounds,
+            content: NodeContent::Leaf { start, end },
+        });
+
+        if primitives.len() <= LEAF_SIZE {
+            return node_index;
+        }
+
+        let centers = Aabb::from_points(
+            &primitives
+                .iter()
+                .map(|&index| boxes[index].center())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let spread = centers.max() - centers.min();
+        let axis = if spread.x() >= spread.y() && spread.x() >= spread.z() {
+            0
+        } else if spread.y() >= spread.z() {
+            1
+        } else {
+            2
+        };
+
+        let middle = primitives.len() / 2;
+        primitives.select_nth_unstable_by(middle, |&a, &b| {
+            let center = |index: usize| boxes[index].center()[axis];
+            center(a).total_cmp(&center(b))
+        });
+
+        let left = self.build(boxes, start, start + middle);
+        let right = self.build(boxes, start + middle, end);
+        self.nodes[node_index].content = NodeContent::Branch { left, right };
+
+        node_index
+    }
+
+    /// the box of all the primitives (None without primitives)
+    pub(crate) fn bounds(&self) -> Option<Aabb> {
+        self.nodes.first().map(|node| node.bounds)
+    }
+
+    /// the closest hit of the ray: `hit_primitive(index, t_max)` is called for the primitives
+    /// whose boxes are crossed, `t_max` shrinking to the closest hit found so far
+    pub(crate) fn closest_hit<'h>(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut hit_primitive: impl FnMut(usize, f64) -> Option<HitRecord<'h>>,
+    ) -> Option<HitRecord<'h>> {
+        let mut closest_hit_record: Option<HitRecord> = None;
+        let mut closest_t = t_max;
+
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.bounds.hit(ray, t_min, closest_t) {
+                continue;
+            }
+
+            match node.content {
+                NodeContent::Leaf { start, end } => {
+                    for &index in &self.indices[start..end] {
+                        if let Some(hit_record) = hit_primitive(index, closest_t) {
+                            closest_t = hit_record.t;
+                            closest_hit_record = Some(hit_record);
+                        }
+                    }
+                }
+                NodeContent::Branch { left, right } => {
+                    // the closest child is visited first: it can shorten the ray for the other one
+                    let entry = |child: usize| {
+                        self.nodes[child]
+                            .bounds
+                            .slabs(ray)
+                            .map_or(f64::INFINITY, |crossing| crossing.t_entry)
+                    };
+                    if entry(left) <= entry(right) {
+                        stack.extend([right, left]);
+                    } else {
+                        stack.extend([left, right]);
+                    }
+                }
+            }
+        }
+
+        closest_hit_record
+    }
+
+    /// true if `occludes_primitive(index)` is true for a primitive whose box is crossed by the ray.
+    /// The traversal stops at the first one
+    pub(crate) fn any_hit(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        mut occludes_primitive: impl FnMut(usize) -> bool,
+    ) -> bool {
+        let mut stack = Vec::with_capacity(64);
+        if !self.nodes.is_empty() {
+            stack.push(0);
+        }
+
+        while let Some(node_index) = stack.pop() {
+            let node = &self.nodes[node_index];
+            if !node.bounds.hit(ray, t_min, t_max) {
+                continue;
+            }
+
+            match node.content {
+                NodeContent::Leaf { start, end } => {
+                    if self.indices[start..end]
+                        .iter()
+                        .any(|&index| occludes_primitive(index))
+                    {
+                        return true;
+                    }
+                }
+                NodeContent::Branch { left, right } => stack.extend([right, left]),
+            }
+        }
+
+        false
+    }
+}
+
+/// A bounding volume hierarchy over shapes, a faster alternative to `HittableList` for large scenes.
+/// Over instances of meshes (see `Instance`), it is the top level of a two level hierarchy:
+/// each mesh has one bottom level tree, shared by reference by all its instances.
+/// Shapes without bounding box (like planes) are tested for every ray
+#[derive(Debug)]
+pub struct Bvh<'a> {
+    objects: Vec<&'a dyn Hittable>,
+    tree: BvhTree,
+    /// index in `objects` of the bounded objects, in the order of the tree
+    bounded: Vec<usize>,
+    unbounded: Vec<usize>,
+}
+
+impl<'a> Bvh<'a> {
+    pub fn new(objects: Vec<&'a dyn Hittable>) -> Self {
+        let (bounded, unbounded): (Vec<usize>, Vec<usize>) =
+            (0..objects.len()).partition(|&index| objects[index].bounding_box().is_some());
+        let boxes: Vec<Aabb> = bounded
+            .iter()
+            .filter_map(|&index| objects[index].bounding_box())
+            .collect();
+
+        Self {
+            tree: BvhTree::new(&boxes),
+            objects,
+            bounded,
+            unbounded,
+        }
+    }
+}
+
+impl<'a> Hittable for Bvh<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        // the object hit is named by its index, like in a `HittableList`
+        let hit_object = |object_id: usize, t_max: f64| {
+            self.objects[object_id]
+                .hit(ray, t_min, t_max)
+                .map(|mut hit_record| {
+                    hit_record.object_id = object_id as u32;
+                    hit_record
+                })
+        };
+
+        let mut closest_hit_record = None;
+        let mut closest_t = t_max;
+        for &object_id in &self.unbounded {
+            if let Some(hit_record) = hit_object(object_id, closest_t) {
+                closest_t = hit_record.t;
+                closest_hit_record = Some(hit_record);
+            }
+        }
+
+        self.tree
+            .closest_hit(ray, t_min, closest_t, |index, t_max| {
+                hit_object(self.bounded[index], t_max)
+            })
+            .or(closest_hit_record)
+    }
+
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.unbounded
+            .iter()
+            .any(|&object_id| self.objects[object_id].occluded(ray, t_min, t_max))
+            || self.tree.any_hit(ray, t_min, t_max, |index| {
+                self.objects[self.bounded[index]].occluded(ray, t_min, t_max)
+            })
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        if self.unbounded.is_empty() {
+            self.tree.bounds()
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        hittable::{HittableList, Plane, Sphere},
+        material::Lambertian,
+        Color3, Point3, Vec3,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_should_hit_like_a_list() {
+        let material = Lambertian::new(&Color3::white());
+        let mut rng = StdRng::seed_from_u64(11);
+
+        let spheres: Vec<Sphere> = (0..300)
+            .map(|_| {
+                let center = Vec3::new_clamped_random(-10.0, 10.0, &mut rng);
+                Sphere::new(&center, rng.gen_range(0.1..1.0), &material)
+            })
+            .collect();
+        let ground = Plane::new(
+            &Point3::new(0.0, -10.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &material,
+        );
+
+        let mut list = HittableList::new();
+        let mut objects: Vec<&dyn Hittable> = Vec::new();
+        for sphere in &spheres {
+            list.add(sphere);
+            objects.push(sphere);
+        }
+        list.add(&ground);
+        objects.push(&ground);
+        let bvh = Bvh::new(objects);
+
+        let mut nb_hits = 0;
+        for _ in 0..500 {
+            let origin = Vec3::new_clamped_random(-15.0, 15.0, &mut rng);
+            let ray = Ray::new(&origin, &Vec3::new_randow_unit_vector(&mut rng));
+
+            let expected = list.hit(&ray, 0.001, 30.0);
+            let hit_record = bvh.hit(&ray, 0.001, 30.0);
+
+            assert_eq!(hit_record.is_some(), expected.is_some());
+            assert_eq!(bvh.occluded(&ray, 0.001, 30.0), expected.is_some());
+            assert_eq!(list.occluded(&ray, 0.001, 30.0), expected.is_some());
+            if let (Some(hit_record), Some(expected)) = (hit_record, expected) {
+                assert_eq!(hit_record.t, expected.t);
+                assert_eq!(hit_record.object_id, expected.object_id);
+                nb_hits += 1;
+            }
+        }
+        assert!(nb_hits > 100);
+
+        // the ground is not bounded
+        assert!(bvh.bounding_box().is_none());
+    }
+
+    #[test]
+    fn it_should_bound_all_the_objects() {
+        let material = Lambertian::new(&Color3::white());
+        let spheres: Vec<Sphere> = (0..10)
+            .map(|i| Sphere::new(&Point3::new(i as f64, 0.0, 0.0), 0.5, &material))
+            .collect();
+        let bvh = Bvh::new(
+            spheres
+                .iter()
+                .map(|sphere| sphere as &dyn Hittable)
+                .collect(),
+        );
+
+        let aabb = bvh.bounding_box().unwrap();
+        assert_eq!(aabb.min(), Point3::new(-0.5, -0.5, -0.5));
+        assert_eq!(aabb.max(), Point3::new(9.5, 0.5, 0.5));
+
+        // an empty tree is never hit
+        let empty = Bvh::new(Vec::new());
+        let ray = Ray::new(&Point3::new(0.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(empty.hit(&ray, 0.0, f64::INFINITY).is_none());
+    }
+}
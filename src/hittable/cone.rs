@@ -0,0 +1,197 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray, Vec3,
+};
+
+use super::{frame::Frame, roots::solve_quadratic};
+
+/// A cone from a disk base to its apex, closed by its base or open.
+/// Texture coordinates: u is the angle around the axis (0 to 1), v the height (0 at the base, 1 at the apex)
+#[derive(Debug)]
+pub struct Cone<'a> {
+    base: Point3,
+    height: f64,
+    radius: f64,
+    capped: bool,
+    /// the z axis goes from the base to the apex
+    frame: Frame,
+    material: &'a dyn Material,
+}
+
+impl<'a> Cone<'a> {
+    /// a closed cone
+    pub fn new(base: &Point3, apex: &Point3, radius: f64, material: &'a dyn Material) -> Self {
+        Self {
+            base: *base,
+            height: (*apex - *base).mag(),
+            radius,
+            capped: true,
+            frame: Frame::from_z(&(*apex - *base)),
+            material,
+        }
+    }
+
+    /// a cone without its base
+    pub fn open(base: &Point3, apex: &Point3, radius: f64, material: &'a dyn Material) -> Self {
+        Self {
+            capped: false,
+            ..Self::new(base, apex, radius, material)
+        }
+    }
+}
+
+impl<'a> Hittable for Cone<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        // in the local space, the axis is z, the base is at the origin and the apex at z = height
+        let origin = self.frame.to_local(&(ray.origin() - self.base));
+        let direction = self.frame.to_local(&ray.direction());
+
+        // candidate hits: time and outward local normal
+        let mut closest: Option<(f64, Vec3)> = None;
+        let mut consider = |t: f64, normal: Vec3| {
+            if t >= t_min && t <= t_max && closest.is_none_or(|(closest_t, _)| t < closest_t) {
+                closest = Some((t, normal));
+            }
+        };
+
+        // side: x² + y² = k² (height - z)², with k the slope of the side
+        let k = self.radius / self.height;
+        let k2 = k * k;
+        let h = self.height - origin.z();
+        let a = direction.x() * direction.x() + direction.y() * direction.y()
+            - k2 * direction.z() * direction.z();
+        let b = 2.0
+            * (origin.x() * direction.x() + origin.y() * direction.y() + k2 * h * direction.z());
+        let c = origin.x() * origin.x() + origin.y() * origin.y() - k2 * h * h;
+        for t in solve_quadratic(a, b, c) {
+            let point = origin + direction * t;
+            // the equation also describes the mirrored cone above the apex
+            if (0.0..=self.height).contains(&point.z()) {
+                let normal = Vec3::new(point.x(), point.y(), k2 * (self.height - point.z()));
+                let normal = if normal.is_near_zero() {
+                    // the apex
+                    Vec3::new(0.0, 0.0, 1.0)
+                } else {
+                    normal.normalize()
+                };
+                consider(t, normal);
+            }
+        }
+
+        // base: z = 0, within the radius
+        if self.capped && direction.z().abs() > 1e-12 {
+            let t = -origin.z() / direction.z();
+            let point = origin + direction * t;
+            if point.x() * point.x() + point.y() * point.y() <= self.radius * self.radius {
+                consider(t, Vec3::new(0.0, 0.0, -1.0));
+            }
+        }
+
+        let (t, local_normal) = closest?;
+        let local_point = origin + direction * t;
+        let point = ray.at(t);
+        let outward_normal = self.frame.to_world(&local_normal);
+
+        let mut hit_record = HitRecord::new(ray, &point, &outward_normal, self.material, t);
+        hit_record.u = (local_point.y().atan2(local_point.x()) + PI) / (2.0 * PI);
+        hit_record.v = (local_point.z() / self.height).clamp(0.0, 1.0);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = self.frame.circle_extent(self.radius);
+        let apex = self.base + self.frame.z() * self.height;
+
+        let base_box = Aabb::new(&(self.base - extent), &(self.base + extent));
+
+        Some(base_box.union(&Aabb::new(&apex, &apex)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{material::Lambertian, Color3, Point3, Ray, Vec3};
+
+    use super::*;
+
+    /// a cone of radius 1 on the ground, its apex at y = 1
+    fn cone(material: &dyn Material) -> Cone<'_> {
+        Cone::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(0.0, 1.0, 0.0),
+            1.0,
+            material,
+        )
+    }
+
+    #[test]
+    fn it_should_detect_intersection() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cone = cone(&material_black);
+
+        // ray comming from the left, at mid height: the radius is 0.5
+        let ray = Ray::new(&Point3::new(-100.0, 0.5, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let hit_record = cone.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point - Point3::new(-0.5, 0.5, 0.0)).mag() < 1e-9);
+        // the side has a slope of 45°
+        let expected_normal = Vec3::new(-1.0, 1.0, 0.0).normalize();
+        assert!((hit_record.normal - expected_normal).mag() < 1e-9);
+        assert!(hit_record.front_face);
+        assert!((hit_record.v - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_detect_intersection_with_the_base() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cone = cone(&material_black);
+
+        let ray = Ray::new(&Point3::new(0.2, -10.0, 0.3), &Vec3::new(0.0, 1.0, 0.0));
+        let hit_record = cone.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point - Point3::new(0.2, 0.0, 0.3)).mag() < 1e-9);
+        assert!((hit_record.normal - Vec3::new(0.0, -1.0, 0.0)).mag() < 1e-9);
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn it_should_ignore_the_mirrored_cone() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cone = cone(&material_black);
+
+        // above the apex, where the mirrored cone of the equation would be
+        let ray = Ray::new(&Point3::new(-100.0, 1.5, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(cone.hit(&ray, 0.000, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn it_should_see_the_inside_of_an_open_cone() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cone = Cone::open(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(0.0, 1.0, 0.0),
+            1.0,
+            &material_black,
+        );
+
+        let ray = Ray::new(&Point3::new(0.0, -10.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let hit_record = cone.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point - Point3::new(0.0, 1.0, 0.0)).mag() < 1e-9);
+        assert!(!hit_record.front_face);
+    }
+
+    #[test]
+    fn it_should_be_bounded() {
+        let material_black = Lambertian::new(&Color3::black());
+        let aabb = cone(&material_black).bounding_box().unwrap();
+
+        assert!((aabb.min() - Point3::new(-1.0, 0.0, -1.0)).mag() < 1e-9);
+        assert!((aabb.max() - Point3::new(1.0, 1.0, 1.0)).mag() < 1e-9);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray,
+};
+
+use super::{hittable::MultiFaceHittable, Triangle};
+
+/// the vertices of a triangle, reordered if needed so its normal points away from `interior`
+/// (a point inside the closed convex solid the triangle is a face of)
+pub(crate) fn orient_outward(face: [Point3; 3], interior: &Point3) -> [Point3; 3] {
+    let [vertex_0, vertex_1, vertex_2] = face;
+    let normal = (vertex_1 - vertex_0).cross(&(vertex_2 - vertex_0));
+
+    if normal.dot(&(vertex_0 - *interior)) < 0.0 {
+        [vertex_0, vertex_2, vertex_1]
+    } else {
+        face
+    }
+}
+
+/// The indices of the triangles of the convex hull of points, counter clockwise seen from outside
+/// (incremental algorithm: each point outside the current hull replaces the faces it can see).
+/// None if the points are all in a plane
+pub(crate) fn convex_hull(points: &[Point3]) -> Option<Vec<[usize; 3]>> {
+    let bounds = Aabb::from_points(points)?;
+    let epsilon = 1e-9 * (bounds.max() - bounds.min()).mag();
+
+    // a first tetrahedron, as large as possible
+    let farthest = |distance: &dyn Fn(&Point3) -> f64| {
+        (0..points.len())
+            .max_by(|&i, &j| distance(&points[i]).total_cmp(&distance(&points[j])))
+            .filter(|&i| distance(&points[i]) > epsilon)
+    };
+    let i_0 = 0;
+    let i_1 = farthest(&|point| (*point - points[i_0]).mag())?;
+    let axis = (points[i_1] - points[i_0]).normalize();
+    let i_2 = farthest(&|point| (*point - points[i_0]).cross(&axis).mag())?;
+    let normal = (points[i_1] - points[i_0])
+        .cross(&(points[i_2] - points[i_0]))
+        .normalize();
+    let i_3 = farthest(&|point| (*point - points[i_0]).dot(&normal).abs())?;
+
+    // the center of the first tetrahedron stays inside the hull
+    let interior = (points[i_0] + points[i_1] + points[i_2] + points[i_3]) / 4.0;
+    let outward = |[a, b, c]: [usize; 3]| {
+        let normal = (points[b] - points[a]).cross(&(points[c] - points[a]));
+
+        if normal.dot(&(points[a] - interior)) < 0.0 {
+            [a, c, b]
+        } else {
+            [a, b, c]
+        }
+    };
+    let mut faces: Vec<[usize; 3]> = [
+        [i_0, i_1, i_2],
+        [i_0, i_1, i_3],
+        [i_0, i_2, i_3],
+        [i_1, i_2, i_3],
+    ]
+    .into_iter()
+    .map(outward)
+    .collect();
+
+    for (index, point) in points.iter().enumerate() {
+        // the faces seen from the point
+        let is_visible = |[a, b, c]: &[usize; 3]| {
+            let normal = (points[*b] - points[*a])
+                .cross(&(points[*c] - points[*a]))
+                .normalize();
+
+            (*point - points[*a]).dot(&normal) > epsilon
+        };
+        let (visible, hidden): (Vec<[usize; 3]>, Vec<[usize; 3]>) =
+            faces.into_iter().partition(is_visible);
+
+        // inside the hull
+        if visible.is_empty() {
+            faces = hidden;
+            continue;
+        }
+
+        // the horizon: edges between a visible face and a hidden one.
+        // Its edges keep their direction, so the new faces are wound like the ones they replace
+        let edges = |[a, b, c]: [usize; 3]| [(a, b), (b, c), (c, a)];
+        let horizon = visible
+            .iter()
+            .flat_map(|face| edges(*face))
+            .filter(|&(a, b)| !visible.iter().any(|face| edges(*face).contains(&(b, a))));
+
+        faces = hidden
+            .iter()
+            .copied()
+            .chain(horizon.map(|(a, b)| [a, b, index]))
+            .collect();
+    }
+
+    Some(faces)
+}
+
+/// A closed convex solid with flat triangular faces, like the convex hull of a set of points.
+/// All the faces point outward, so transparent materials know when a ray goes in or out
+#[derive(Debug)]
+pub struct ConvexPolyhedron<'a> {
+    faces: Vec<Triangle<'a>>,
+}
+
+impl<'a> ConvexPolyhedron<'a> {
+    /// the smallest convex solid containing all the points (points inside it are ignored).
+    /// None if there are less than 4 points, or if they are all in a plane
+    pub fn from_points(points: &[Point3], material: &'a dyn Material) -> Option<Self> {
+        let faces = convex_hull(points)?
+            .into_iter()
+            .map(|[a, b, c]| Triangle::new(&points[a], &points[b], &points[c], material))
+            .collect();
+
+        Some(Self { faces })
+    }
+}
+
+impl<'a> Hittable for ConvexPolyhedron<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.get_closest_hit(ray, t_min, t_max)
+    }
+
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.get_any_hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.get_faces_bounding_box()
+    }
+}
+
+impl<'a> MultiFaceHittable for ConvexPolyhedron<'a> {
+    fn get_faces(&self) -> &Vec<Triangle<'_>> {
+        &self.faces
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{material::Lambertian, Color3, Vec3};
+
+    use super::*;
+
+    /// the outward normal of a face of the hull
+    fn face_normal(points: &[Point3], [a, b, c]: [usize; 3]) -> Vec3 {
+        (points[b] - points[a])
+            .cross(&(points[c] - points[a]))
+            .normalize()
+    }
+
+    #[test]
+    fn it_should_wrap_the_points() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let points: Vec<Point3> = (0..200)
+            .map(|_| Vec3::new_randow_in_unit_sphere(&mut rng))
+            .collect();
+        let faces = convex_hull(&points).unwrap();
+
+        // every point is behind every face
+        for face in &faces {
+            let normal = face_normal(&points, *face);
+            assert!(points
+                .iter()
+                .all(|point| (*point - points[face[0]]).dot(&normal) < 1e-9));
+        }
+
+        // a closed surface: every edge is shared by two faces, in opposite directions
+        for [a, b, c] in &faces {
+            for (from, to) in [(a, b), (b, c), (c, a)] {
+                let nb_opposite = faces
+                    .iter()
+                    .filter(|[d, e, f]| [(d, e), (e, f), (f, d)].contains(&(to, from)))
+                    .count();
+                assert_eq!(nb_opposite, 1);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_ignore_the_points_inside() {
+        let mut points: Vec<Point3> = (0..8)
+            .map(|corner| {
+                let coordinate = |bit: usize| if corner & bit == 0 { -1.0 } else { 1.0 };
+                Point3::new(coordinate(1), coordinate(2), coordinate(4))
+            })
+            .collect();
+        points.push(Point3::new(0.0, 0.0, 0.0));
+        points.push(Point3::new(0.5, -0.2, 0.9));
+
+        // a cube: two triangles per side
+        let faces = convex_hull(&points).unwrap();
+        assert_eq!(faces.len(), 12);
+        assert!(faces.iter().flatten().all(|&index| index < 8));
+
+        // flat sets of points have no volume
+        let square = &points[0..4];
+        assert!(convex_hull(square).is_none());
+    }
+
+    #[test]
+    fn it_should_be_entered_through_the_front_faces() {
+        let material = Lambertian::new(&Color3::white());
+        let mut rng = StdRng::seed_from_u64(3);
+        let points: Vec<Point3> = (0..30)
+            .map(|_| Vec3::new_randow_in_unit_sphere(&mut rng))
+            .collect();
+        let polyhedron = ConvexPolyhedron::from_points(&points, &material).unwrap();
+        let center = Aabb::from_points(&points).unwrap().center();
+
+        for _ in 0..50 {
+            let direction = Vec3::new_randow_unit_vector(&mut rng);
+
+            // from outside, the ray enters
+            let ray = Ray::new(&(center + direction * 10.0), &-direction);
+            assert!(polyhedron.hit(&ray, 0.0, f64::INFINITY).unwrap().front_face);
+
+            // from inside, it leaves
+            let ray = Ray::new(&center, &direction);
+            assert!(!polyhedron.hit(&ray, 0.0, f64::INFINITY).unwrap().front_face);
+        }
+    }
+}
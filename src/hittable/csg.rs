@@ -0,0 +1,235 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    Ray,
+};
+
+/// How the two shapes of a CSG node are combined
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsgOperation {
+    /// inside either shape
+    Union,
+    /// inside both shapes
+    Intersection,
+    /// inside the first shape, but not in the second one
+    Difference,
+}
+
+impl CsgOperation {
+    fn is_inside(&self, inside_left: bool, inside_right: bool) -> bool {
+        match self {
+            CsgOperation::Union => inside_left || inside_right,
+            CsgOperation::Intersection => inside_left && inside_right,
+            CsgOperation::Difference => inside_left && !inside_right,
+        }
+    }
+}
+
+/// Constructive solid geometry: a boolean operation between two closed shapes.
+/// The surface of the result is made of the parts of the surfaces of the shapes where the ray
+/// goes in or out of the combined solid. Nodes can be combined, to build complex parts
+#[derive(Debug)]
+pub struct Csg<'a> {
+    left: &'a dyn Hittable,
+    right: &'a dyn Hittable,
+    operation: CsgOperation,
+}
+
+impl<'a> Csg<'a> {
+    pub fn new(left: &'a dyn Hittable, right: &'a dyn Hittable, operation: CsgOperation) -> Self {
+        Self {
+            left,
+            right,
+            operation,
+        }
+    }
+
+    pub fn union(left: &'a dyn Hittable, right: &'a dyn Hittable) -> Self {
+        Self::new(left, right, CsgOperation::Union)
+    }
+
+    pub fn intersection(left: &'a dyn Hittable, right: &'a dyn Hittable) -> Self {
+        Self::new(left, right, CsgOperation::Intersection)
+    }
+
+    /// `left` with the volume of `right` removed
+    pub fn difference(left: &'a dyn Hittable, right: &'a dyn Hittable) -> Self {
+        Self::new(left, right, CsgOperation::Difference)
+    }
+}
+
+impl<'a> Hittable for Csg<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        self.all_hits(ray, t_min, t_max).into_iter().next()
+    }
+
+    fn all_hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        // the hits after t_max are needed to know if the ray starts inside the shapes
+        let left_hits = self.left.all_hits(ray, t_min, f64::INFINITY);
+        let right_hits = self.right.all_hits(ray, t_min, f64::INFINITY);
+
+        // a ray leaving a closed shape first started inside it
+        let starts_inside = |hits: &[HitRecord]| hits.first().is_some_and(|hit| !hit.front_face);
+        let mut inside_left = starts_inside(&left_hits);
+        let mut inside_right = starts_inside(&right_hits);
+        let mut inside = self.operation.is_inside(inside_left, inside_right);
+
+        // walking along the ray through the hits of both shapes, in order
+        let mut left_hits = left_hits.into_iter().peekable();
+        let mut right_hits = right_hits.into_iter().peekable();
+        let mut hits = Vec::new();
+
+        loop {
+            let is_left = match (left_hits.peek(), right_hits.peek()) {
+                (Some(left), Some(right)) => left.t <= right.t,
+                (Some(_), None) => true,
+                (None, Some(_)) => false,
+                (None, None) => break,
+            };
+            let mut hit_record = if is_left {
+                left_hits.next()
+            } else {
+                right_hits.next()
+            }
+            .unwrap();
+
+            if hit_record.t > t_max {
+                break;
+            }
+
+            if is_left {
+                inside_left = hit_record.front_face;
+            } else {
+                inside_right = hit_record.front_face;
+            }
+
+            // only the hits changing the state of the combined solid are on its surface
+            let was_inside = inside;
+            inside = self.operation.is_inside(inside_left, inside_right);
+            if inside != was_inside {
+                // the normal already faces the ray, only the side of the solid can change
+                // (entering the solid by leaving the removed volume of a difference)
+                hit_record.front_face = inside;
+                hits.push(hit_record);
+            }
+        }
+
+        hits
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        match self.operation {
+            CsgOperation::Union => {
+                Some(self.left.bounding_box()?.union(&self.right.bounding_box()?))
+            }
+            // the result is within the first shape
+            CsgOperation::Intersection | CsgOperation::Difference => self.left.bounding_box(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hittable::{Cuboid, Sphere},
+        material::Lambertian,
+        Color3, Point3, Ray, Vec3,
+    };
+
+    use super::*;
+
+    /// the times of the hits of a ray along the x axis, and whether they enter the solid
+    fn hits_along_x(shape: &dyn Hittable, origin_x: f64) -> Vec<(f64, bool)> {
+        let ray = Ray::new(&Point3::new(origin_x, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+
+        shape
+            .all_hits(&ray, 0.0, f64::INFINITY)
+            .iter()
+            .map(|hit_record| (hit_record.t + origin_x, hit_record.front_face))
+            .collect()
+    }
+
+    #[test]
+    fn it_should_combine_two_spheres() {
+        let material_black = Lambertian::new(&Color3::black());
+
+        // two spheres of radius 1 overlapping between x = 0 and x = 1
+        let left = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material_black);
+        let right = Sphere::new(&Point3::new(1.0, 0.0, 0.0), 1.0, &material_black);
+
+        let union = Csg::union(&left, &right);
+        assert_eq!(hits_along_x(&union, -10.0), [(-1.0, true), (2.0, false)]);
+
+        let intersection = Csg::intersection(&left, &right);
+        assert_eq!(
+            hits_along_x(&intersection, -10.0),
+            [(0.0, true), (1.0, false)]
+        );
+
+        let difference = Csg::difference(&left, &right);
+        assert_eq!(
+            hits_along_x(&difference, -10.0),
+            [(-1.0, true), (0.0, false)]
+        );
+        let difference = Csg::difference(&right, &left);
+        assert_eq!(
+            hits_along_x(&difference, -10.0),
+            [(1.0, true), (2.0, false)]
+        );
+    }
+
+    #[test]
+    fn it_should_face_the_ray_on_the_removed_volume() {
+        let material_black = Lambertian::new(&Color3::black());
+        let left = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material_black);
+        let right = Sphere::new(&Point3::new(1.0, 0.0, 0.0), 1.0, &material_black);
+
+        // coming from the right, the ray enters the difference on the surface of the removed sphere
+        let difference = Csg::difference(&left, &right);
+        let ray = Ray::new(&Point3::new(10.0, 0.0, 0.0), &Vec3::new(-1.0, 0.0, 0.0));
+        let hit_record = difference.hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+        assert_eq!(hit_record.point, Point3::new(0.0, 0.0, 0.0));
+        assert_eq!(hit_record.normal, Vec3::new(1.0, 0.0, 0.0));
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn it_should_start_inside_the_solid() {
+        let material_black = Lambertian::new(&Color3::black());
+        let left = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material_black);
+        let right = Sphere::new(&Point3::new(1.0, 0.0, 0.0), 1.0, &material_black);
+
+        // from the middle of the overlap, only the exit of the union is hit
+        let union = Csg::union(&left, &right);
+        assert_eq!(hits_along_x(&union, 0.5), [(2.0, false)]);
+
+        // limited to t_max, the exit is not reached
+        let ray = Ray::new(&Point3::new(0.5, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(union.hit(&ray, 0.0, 1.0).is_none());
+    }
+
+    #[test]
+    fn it_should_drill_a_plate() {
+        let material_black = Lambertian::new(&Color3::black());
+
+        // a plate with a hole of radius 0.5, drilled by a sphere, along x
+        let plate = Cuboid::new(
+            &Point3::new(-0.1, -2.0, -2.0),
+            &Point3::new(0.1, 2.0, 2.0),
+            &material_black,
+        );
+        let drill = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 0.5, &material_black);
+        let drilled = Csg::difference(&plate, &drill);
+
+        // through the hole
+        assert!(hits_along_x(&drilled, -10.0).is_empty());
+
+        // the plate is still there next to the hole
+        let ray = Ray::new(&Point3::new(-10.0, 1.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(drilled.all_hits(&ray, 0.0, f64::INFINITY).len(), 2);
+
+        // nested nodes: filling the hole again
+        let filled = Csg::union(&drilled, &drill);
+        assert_eq!(hits_along_x(&filled, -10.0), [(-0.5, true), (0.5, false)]);
+    }
+}
@@ -0,0 +1,218 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray, Vec3,
+};
+
+use super::Transform;
+
+/// A rectangular box, intersected with the slab method.
+/// The box is axis aligned in its local space, and placed in the world by a transform.
+/// The texture coordinates cover each face from 0 to 1: (z, y) on the x faces, (x, z) on the y faces, (x, y) on the z faces
+#[derive(Debug)]
+pub struct Cuboid<'a> {
+    /// the box in its local space
+    local_box: Aabb,
+    transform: Transform,
+    material: &'a dyn Material,
+}
+
+impl<'a> Cuboid<'a> {
+    /// an axis aligned box between two opposite corners
+    pub fn new(corner_0: &Point3, corner_1: &Point3, material: &'a dyn Material) -> Self {
+        Self {
+            local_box: Aabb::new(corner_0, corner_1),
+            transform: Transform::identity(),
+            material,
+        }
+    }
+
+    /// the same box, moved by a transform (applied after the corners are placed)
+    pub fn with_transform(self, transform: &Transform) -> Self {
+        Self {
+            transform: self.transform.then(transform),
+            ..self
+        }
+    }
+
+    /// the hit of a face, crossed along `axis` at time `t`.
+    /// The face faces the ray (`sign` = -1) when entering, and faces away (`sign` = 1) when leaving
+    fn hit_record(
+        &self,
+        ray: &Ray,
+        local_ray: &Ray,
+        t: f64,
+        axis: usize,
+        sign: f64,
+    ) -> HitRecord<'_> {
+        let direction = local_ray.direction()[axis];
+        let mut local_normal = [0.0; 3];
+        local_normal[axis] = sign * direction.signum();
+        let local_normal = Vec3::new(local_normal[0], local_normal[1], local_normal[2]);
+
+        let point = ray.at(t);
+        let outward_normal = self.transform.apply_normal(&local_normal).normalize();
+        let mut hit_record = HitRecord::new(ray, &point, &outward_normal, self.material, t);
+
+        // position of the hit in the box, from 0 to 1 along each axis
+        let local_point = local_ray.at(t);
+        let relative = |axis: usize| {
+            let min = self.local_box.min()[axis];
+            let max = self.local_box.max()[axis];
+
+            ((local_point[axis] - min) / (max - min)).clamp(0.0, 1.0)
+        };
+        (hit_record.u, hit_record.v) = match axis {
+            0 => (relative(2), relative(1)),
+            1 => (relative(0), relative(2)),
+            _ => (relative(0), relative(1)),
+        };
+
+        hit_record
+    }
+}
+
+impl<'a> Hittable for Cuboid<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        // the times are the same in the local space
+        let local_ray = self.transform.inverse_ray(ray);
+        let crossing = self.local_box.slabs(&local_ray)?;
+
+        // from the outside, the ray hits the face it enters by, from the inside the one it leaves by
+        if crossing.t_entry >= t_min && crossing.t_entry <= t_max {
+            Some(self.hit_record(ray, &local_ray, crossing.t_entry, crossing.entry_axis, -1.0))
+        } else if crossing.t_exit >= t_min && crossing.t_exit <= t_max {
+            Some(self.hit_record(ray, &local_ray, crossing.t_exit, crossing.exit_axis, 1.0))
+        } else {
+            None
+        }
+    }
+
+    fn all_hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        let local_ray = self.transform.inverse_ray(ray);
+        let Some(crossing) = self.local_box.slabs(&local_ray) else {
+            return Vec::new();
+        };
+
+        [
+            (crossing.t_entry, crossing.entry_axis, -1.0),
+            (crossing.t_exit, crossing.exit_axis, 1.0),
+        ]
+        .into_iter()
+        .filter(|(t, _, _)| (t_min..=t_max).contains(t))
+        .map(|(t, axis, sign)| self.hit_record(ray, &local_ray, t, axis, sign))
+        .collect()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.local_box.transformed(&self.transform))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use crate::{material::Lambertian, Color3, Point3, Ray, Vec3};
+
+    use super::*;
+
+    /// a box from (-1, -1, -1) to (1, 2, 3)
+    fn cuboid(material: &dyn Material) -> Cuboid<'_> {
+        Cuboid::new(
+            &Point3::new(-1.0, -1.0, -1.0),
+            &Point3::new(1.0, 2.0, 3.0),
+            material,
+        )
+    }
+
+    #[test]
+    fn it_should_detect_intersection() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cuboid = cuboid(&material_black);
+
+        // ray comming from the left
+        let ray = Ray::new(&Point3::new(-100.0, 0.5, 1.0), &Vec3::new(1.0, 0.0, 0.0));
+        let hit_record = cuboid.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert_eq!(hit_record.point, Point3::new(-1.0, 0.5, 1.0));
+        assert_eq!(hit_record.normal, Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(hit_record.t, 99.0);
+        assert!(hit_record.front_face);
+        assert_eq!((hit_record.u, hit_record.v), (0.5, 0.5));
+    }
+
+    #[test]
+    fn it_should_give_the_normal_of_each_face() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cuboid = cuboid(&material_black);
+        let center = Point3::new(0.0, 0.5, 1.0);
+
+        for normal in [
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(-1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, -1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+            Vec3::new(0.0, 0.0, -1.0),
+        ] {
+            let ray = Ray::new(&(center + normal * 100.0), &-normal);
+            let hit_record = cuboid.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+            assert_eq!(hit_record.normal, normal);
+            assert!(hit_record.front_face);
+        }
+    }
+
+    #[test]
+    fn it_should_detect_intersection_from_within() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cuboid = cuboid(&material_black);
+
+        let ray = Ray::new(&Point3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 0.0, 1.0));
+        let hit_record = cuboid.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert_eq!(hit_record.point, Point3::new(0.0, 0.0, 3.0));
+        // the normal faces the ray, inside the box
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 0.0, -1.0));
+        assert!(!hit_record.front_face);
+    }
+
+    #[test]
+    fn it_should_ignore_outer_rays() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cuboid = cuboid(&material_black);
+
+        let ray = Ray::new(&Point3::new(-100.0, 2.5, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(cuboid.hit(&ray, 0.000, f64::INFINITY).is_none());
+
+        // going away from the box
+        let ray = Ray::new(&Point3::new(-100.0, 0.0, 0.0), &Vec3::new(-1.0, 0.0, 0.0));
+        assert!(cuboid.hit(&ray, 0.000, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn it_should_be_oriented_by_a_transform() {
+        let material_black = Lambertian::new(&Color3::black());
+
+        // a unit cube turned by 45° around y
+        let cuboid = Cuboid::new(
+            &Point3::new(-0.5, -0.5, -0.5),
+            &Point3::new(0.5, 0.5, 0.5),
+            &material_black,
+        )
+        .with_transform(&Transform::rotation(&Vec3::new(0.0, 1.0, 0.0), PI / 4.0));
+
+        // the ray meets an edge of the cube
+        let ray = Ray::new(&Point3::new(-100.0, 0.0, 0.1), &Vec3::new(1.0, 0.0, 0.0));
+        let hit_record = cuboid.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        let half_diagonal = 0.5 * 2.0_f64.sqrt();
+        assert!((hit_record.point.x() - (-half_diagonal + 0.1)).abs() < 1e-9);
+        let expected_normal = Vec3::new(-1.0, 0.0, 1.0).normalize();
+        assert!((hit_record.normal - expected_normal).mag() < 1e-9);
+
+        let aabb = cuboid.bounding_box().unwrap();
+        assert!((aabb.max() - Point3::new(half_diagonal, 0.5, half_diagonal)).mag() < 1e-9);
+    }
+}
@@ -0,0 +1,190 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray, Vec3,
+};
+
+use super::{frame::Frame, roots::solve_quadratic};
+
+/// A cylinder between the centers of its two ends, capped by two disks or open.
+/// Texture coordinates: u is the angle around the axis (0 to 1), v the height (0 at the base, 1 at the top)
+#[derive(Debug)]
+pub struct Cylinder<'a> {
+    base: Point3,
+    height: f64,
+    radius: f64,
+    capped: bool,
+    /// the z axis goes from the base to the top
+    frame: Frame,
+    material: &'a dyn Material,
+}
+
+impl<'a> Cylinder<'a> {
+    /// a closed cylinder
+    pub fn new(base: &Point3, top: &Point3, radius: f64, material: &'a dyn Material) -> Self {
+        Self {
+            base: *base,
+            height: (*top - *base).mag(),
+            radius,
+            capped: true,
+            frame: Frame::from_z(&(*top - *base)),
+            material,
+        }
+    }
+
+    /// a tube, without the disks at its ends
+    pub fn open(base: &Point3, top: &Point3, radius: f64, material: &'a dyn Material) -> Self {
+        Self {
+            capped: false,
+            ..Self::new(base, top, radius, material)
+        }
+    }
+}
+
+impl<'a> Hittable for Cylinder<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        // in the local space, the axis is z and the base is at the origin
+        let origin = self.frame.to_local(&(ray.origin() - self.base));
+        let direction = self.frame.to_local(&ray.direction());
+
+        // candidate hits: time and outward local normal
+        let mut closest: Option<(f64, Vec3)> = None;
+        let mut consider = |t: f64, normal: Vec3| {
+            if t >= t_min && t <= t_max && closest.is_none_or(|(closest_t, _)| t < closest_t) {
+                closest = Some((t, normal));
+            }
+        };
+
+        // side: x² + y² = r²
+        let a = direction.x() * direction.x() + direction.y() * direction.y();
+        let b = 2.0 * (origin.x() * direction.x() + origin.y() * direction.y());
+        let c = origin.x() * origin.x() + origin.y() * origin.y() - self.radius * self.radius;
+        for t in solve_quadratic(a, b, c) {
+            let point = origin + direction * t;
+            if (0.0..=self.height).contains(&point.z()) {
+                consider(t, Vec3::new(point.x(), point.y(), 0.0) / self.radius);
+            }
+        }
+
+        // caps: z = 0 and z = height, within the radius
+        if self.capped && direction.z().abs() > 1e-12 {
+            for (z, normal) in [(0.0, -1.0), (self.height, 1.0)] {
+                let t = (z - origin.z()) / direction.z();
+                let point = origin + direction * t;
+                if point.x() * point.x() + point.y() * point.y() <= self.radius * self.radius {
+                    consider(t, Vec3::new(0.0, 0.0, normal));
+                }
+            }
+        }
+
+        let (t, local_normal) = closest?;
+        let local_point = origin + direction * t;
+        let point = ray.at(t);
+        let outward_normal = self.frame.to_world(&local_normal);
+
+        let mut hit_record = HitRecord::new(ray, &point, &outward_normal, self.material, t);
+        hit_record.u = (local_point.y().atan2(local_point.x()) + PI) / (2.0 * PI);
+        hit_record.v = (local_point.z() / self.height).clamp(0.0, 1.0);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = self.frame.circle_extent(self.radius);
+        let top = self.base + self.frame.z() * self.height;
+
+        let base_box = Aabb::new(&(self.base - extent), &(self.base + extent));
+        let top_box = Aabb::new(&(top - extent), &(top + extent));
+
+        Some(base_box.union(&top_box))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{material::Lambertian, Color3, Point3, Ray, Vec3};
+
+    use super::*;
+
+    /// a cylinder of radius 1 along y, from 0 to 2
+    fn cylinder(material: &dyn Material, capped: bool) -> Cylinder<'_> {
+        let (base, top) = (Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 2.0, 0.0));
+
+        if capped {
+            Cylinder::new(&base, &top, 1.0, material)
+        } else {
+            Cylinder::open(&base, &top, 1.0, material)
+        }
+    }
+
+    #[test]
+    fn it_should_detect_intersection() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cylinder = cylinder(&material_black, true);
+
+        // ray comming from the left
+        let ray = Ray::new(&Point3::new(-100.0, 1.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let hit_record = cylinder.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert_eq!(hit_record.point, Point3::new(-1.0, 1.0, 0.0));
+        assert!((hit_record.normal - Vec3::new(-1.0, 0.0, 0.0)).mag() < 1e-9);
+        assert_eq!(hit_record.t, 99.0);
+        assert!(hit_record.front_face);
+        assert!((hit_record.v - 0.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_detect_intersection_with_the_caps() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cylinder = cylinder(&material_black, true);
+
+        // ray comming from above
+        let ray = Ray::new(&Point3::new(0.5, 10.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let hit_record = cylinder.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point - Point3::new(0.5, 2.0, 0.0)).mag() < 1e-9);
+        assert!((hit_record.normal - Vec3::new(0.0, 1.0, 0.0)).mag() < 1e-9);
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn it_should_see_the_inside_of_an_open_cylinder() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cylinder = cylinder(&material_black, false);
+
+        // ray comming from above, going through the opening
+        let ray = Ray::new(&Point3::new(0.0, 3.0, 0.0), &Vec3::new(0.5, -1.0, 0.0));
+        let hit_record = cylinder.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        // the inside of the tube: the normal faces the ray
+        assert!(!hit_record.front_face);
+        assert!((hit_record.point - Point3::new(1.0, 1.0, 0.0)).mag() < 1e-9);
+        assert!((hit_record.normal - Vec3::new(-1.0, 0.0, 0.0)).mag() < 1e-9);
+
+        // a ray along the axis goes through
+        let ray = Ray::new(&Point3::new(0.0, 10.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        assert!(cylinder.hit(&ray, 0.000, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn it_should_ignore_outer_rays() {
+        let material_black = Lambertian::new(&Color3::black());
+        let cylinder = cylinder(&material_black, true);
+
+        // passing above the cylinder
+        let ray = Ray::new(&Point3::new(-100.0, 2.5, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(cylinder.hit(&ray, 0.000, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn it_should_be_bounded() {
+        let material_black = Lambertian::new(&Color3::black());
+        let aabb = cylinder(&material_black, true).bounding_box().unwrap();
+
+        assert!((aabb.min() - Point3::new(-1.0, 0.0, -1.0)).mag() < 1e-9);
+        assert!((aabb.max() - Point3::new(1.0, 2.0, 1.0)).mag() < 1e-9);
+    }
+}
@@ -0,0 +1,129 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray, Vec3,
+};
+
+use super::frame::Frame;
+
+/// A flat disk. Its front face is on the side of the normal.
+/// Texture coordinates are polar: u is the angle (0 to 1 around the center), v the distance to the center (0 to 1)
+#[derive(Debug)]
+pub struct Disk<'a> {
+    center: Point3,
+    radius: f64,
+    frame: Frame,
+    material: &'a dyn Material,
+}
+
+impl<'a> Disk<'a> {
+    pub fn new(center: &Point3, normal: &Vec3, radius: f64, material: &'a dyn Material) -> Self {
+        Self {
+            center: *center,
+            radius,
+            frame: Frame::from_z(normal),
+            material,
+        }
+    }
+}
+
+impl<'a> Hittable for Disk<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let normal = self.frame.z();
+        let denominator = ray.direction().dot(&normal);
+
+        // the ray is parallel to the disk
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = (self.center - ray.origin()).dot(&normal) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let local = self.frame.to_local(&(point - self.center));
+        let distance = (local.x() * local.x() + local.y() * local.y()).sqrt();
+        if distance > self.radius {
+            return None;
+        }
+
+        let mut hit_record = HitRecord::new(ray, &point, &normal, self.material, t);
+        hit_record.u = (local.y().atan2(local.x()) + PI) / (2.0 * PI);
+        hit_record.v = distance / self.radius;
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = self.frame.circle_extent(self.radius);
+
+        Some(Aabb::new(&(self.center - extent), &(self.center + extent)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{material::Lambertian, Color3, Point3, Ray, Vec3};
+
+    use super::*;
+
+    fn disk(material: &dyn Material) -> Disk<'_> {
+        // a disk of radius 2 facing +z
+        Disk::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 0.0, 1.0),
+            2.0,
+            material,
+        )
+    }
+
+    #[test]
+    fn it_should_detect_intersection() {
+        let material_black = Lambertian::new(&Color3::black());
+        let disk = disk(&material_black);
+
+        let ray = Ray::new(&Point3::new(1.0, 1.0, 10.0), &Vec3::new(0.0, 0.0, -1.0));
+        let hit_record = disk.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert_eq!(hit_record.point, Point3::new(1.0, 1.0, 0.0));
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(hit_record.t, 10.0);
+        assert!(hit_record.front_face);
+        assert!((hit_record.v - 2.0_f64.sqrt() / 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_ignore_rays_outside_of_the_radius() {
+        let material_black = Lambertian::new(&Color3::black());
+        let disk = disk(&material_black);
+
+        let ray = Ray::new(&Point3::new(1.5, 1.5, 10.0), &Vec3::new(0.0, 0.0, -1.0));
+
+        assert!(disk.hit(&ray, 0.000, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn it_should_be_bounded() {
+        let material_black = Lambertian::new(&Color3::black());
+        let disk = disk(&material_black);
+
+        let aabb = disk.bounding_box().unwrap();
+        assert_eq!(aabb.min(), Point3::new(-2.0, -2.0, 0.0));
+        assert_eq!(aabb.max(), Point3::new(2.0, 2.0, 0.0));
+
+        // a tilted disk
+        let tilted = Disk::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(1.0, 0.0, 1.0),
+            1.0,
+            &material_black,
+        );
+        let aabb = tilted.bounding_box().unwrap();
+        assert!((aabb.max().x() - 0.5_f64.sqrt()).abs() < 1e-9);
+        assert!((aabb.max().y() - 1.0).abs() < 1e-9);
+    }
+}
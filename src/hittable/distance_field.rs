@@ -0,0 +1,258 @@
+use std::fmt::Debug;
+
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray, Vec3,
+};
+
+use super::sdf::Sdf;
+
+/// maximum number of steps along a ray, before giving up
+const MAX_STEPS: u32 = 512;
+/// distance to the surface under which it is hit
+const EPSILON: f64 = 1e-6;
+
+/// A shape given by a signed distance function (see the `sdf` module), rendered by sphere tracing:
+/// the ray moves forward by the distance to the surface, which can not be crossed in one step.
+/// The function is only evaluated within its bounding box.
+/// Functions that are not exact distances must give their Lipschitz bound:
+/// the largest ratio between a change of the value and the move of the point causing it
+pub struct DistanceField<'a> {
+    /// describes the function and its parameters (e.g. "twisted plate 2x2x0.1, 4 rad/unit"):
+    /// closures can not be printed, so the name stands for the shape in the debug output,
+    /// and in the scene hash of checkpoints
+    name: String,
+    sdf: Box<dyn Sdf>,
+    lipschitz_bound: f64,
+    bounds: Aabb,
+    material: &'a dyn Material,
+}
+
+impl<'a> DistanceField<'a> {
+    /// a shape whose signed distance function is exact (a Lipschitz bound of 1).
+    /// Two different functions must have different names
+    pub fn new(
+        name: &str,
+        sdf: impl Sdf + 'static,
+        bounds: &Aabb,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            sdf: Box::new(sdf),
+            lipschitz_bound: 1.0,
+            bounds: *bounds,
+            material,
+        }
+    }
+
+    /// the same shape, for a function that can change faster than the distance
+    /// # panics
+    /// if the bound is lower than 1
+    pub fn with_lipschitz_bound(self, lipschitz_bound: f64) -> Self {
+        assert!(lipschitz_bound >= 1.0, "a Lipschitz bound is at least 1");
+
+        Self {
+            lipschitz_bound,
+            ..self
+        }
+    }
+
+    /// the gradient of the distance, by central differences
+    fn normal(&self, point: &Point3) -> Vec3 {
+        let axis_difference =
+            |offset: Vec3| (self.sdf)(&(*point + offset)) - (self.sdf)(&(*point - offset));
+
+        Vec3::new(
+            axis_difference(Vec3::new(EPSILON, 0.0, 0.0)),
+            axis_difference(Vec3::new(0.0, EPSILON, 0.0)),
+            axis_difference(Vec3::new(0.0, 0.0, EPSILON)),
+        )
+        .normalize()
+    }
+}
+
+impl Debug for DistanceField<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DistanceField")
+            .field("name", &self.name)
+            .field("lipschitz_bound", &self.lipschitz_bound)
+            .field("bounds", &self.bounds)
+            .field("material", &self.material)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<'a> Hittable for DistanceField<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        // the shape is only searched where the ray crosses the bounding box
+        let crossing = self.bounds.slabs(ray)?;
+        let t_end = crossing.t_exit.min(t_max);
+        let mut t = crossing.t_entry.max(t_min);
+
+        // the steps are in world units, the times are in lengths of the direction
+        let step_scale = 1.0 / (self.lipschitz_bound * ray.direction().mag());
+        // from inside the shape, the ray searches the surface on its way out
+        let start_sign = (self.sdf)(&ray.at(t)).signum();
+
+        for _ in 0..MAX_STEPS {
+            if t > t_end {
+                return None;
+            }
+
+            let point = ray.at(t);
+            let distance = (self.sdf)(&point) * start_sign;
+
+            if distance < EPSILON {
+                let outward_normal = self.normal(&point);
+                return Some(HitRecord::new(
+                    ray,
+                    &point,
+                    &outward_normal,
+                    self.material,
+                    t,
+                ));
+            }
+
+            t += distance * step_scale;
+        }
+
+        None
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hittable::{sdf, Sphere},
+        material::Lambertian,
+        Color3, Point3, Ray, Vec3,
+    };
+
+    use super::*;
+
+    fn unit_bounds() -> Aabb {
+        Aabb::new(&Point3::new(-1.0, -1.0, -1.0), &Point3::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn it_should_detect_intersection() {
+        let material_black = Lambertian::new(&Color3::black());
+        let field = DistanceField::new(
+            "sphere 1",
+            sdf::sphere(1.0),
+            &unit_bounds(),
+            &material_black,
+        );
+        let sphere = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material_black);
+
+        // the same hits as the analytic sphere
+        for origin in [
+            Point3::new(-100.0, 0.0, 0.0),
+            Point3::new(-100.0, 0.5, 0.3),
+            Point3::new(3.0, 4.0, -5.0),
+        ] {
+            let ray = Ray::new(&origin, &((Point3::new(0.1, 0.2, 0.0) - origin) * 0.5));
+            let expected = sphere.hit(&ray, 0.0, f64::INFINITY).unwrap();
+            let hit_record = field.hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+            assert!((hit_record.t - expected.t).abs() < 1e-5);
+            assert!((hit_record.point - expected.point).mag() < 1e-5);
+            assert!((hit_record.normal - expected.normal).mag() < 1e-5);
+            assert!(hit_record.front_face);
+        }
+    }
+
+    #[test]
+    fn it_should_detect_intersection_from_within() {
+        let material_black = Lambertian::new(&Color3::black());
+        let field = DistanceField::new(
+            "sphere 1",
+            sdf::sphere(1.0),
+            &unit_bounds(),
+            &material_black,
+        );
+
+        let ray = Ray::new(&Point3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let hit_record = field.hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point - Point3::new(0.0, 1.0, 0.0)).mag() < 1e-5);
+        assert!(!hit_record.front_face);
+        assert!((hit_record.normal - Vec3::new(0.0, -1.0, 0.0)).mag() < 1e-5);
+    }
+
+    #[test]
+    fn it_should_ignore_outer_rays() {
+        let material_black = Lambertian::new(&Color3::black());
+        let field = DistanceField::new(
+            "sphere 1",
+            sdf::sphere(1.0),
+            &unit_bounds(),
+            &material_black,
+        );
+
+        // passing next to the sphere, but through its bounding box
+        let ray = Ray::new(&Point3::new(-100.0, 0.9, 0.9), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(field.hit(&ray, 0.0, f64::INFINITY).is_none());
+
+        // the sphere is too far
+        let ray = Ray::new(&Point3::new(-100.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(field.hit(&ray, 0.0, 50.0).is_none());
+    }
+
+    #[test]
+    fn it_should_not_step_through_twisted_shapes() {
+        let material_black = Lambertian::new(&Color3::black());
+
+        // a thin plate twisted a lot: without the Lipschitz bound, the steps are too long
+        let twisted = || sdf::twist(sdf::cuboid(Vec3::new(1.0, 1.0, 0.05)), 4.0);
+        let bounds = Aabb::new(&Point3::new(-2.0, -1.0, -2.0), &Point3::new(2.0, 1.0, 2.0));
+        let field = DistanceField::new(
+            "twisted plate 2x2x0.1, 4",
+            twisted(),
+            &bounds,
+            &material_black,
+        )
+        .with_lipschitz_bound((1.0_f64 + 4.0 * 4.0 * 2.0).sqrt());
+        let sdf = twisted();
+
+        for y in [-0.7, -0.2, 0.3, 0.5] {
+            let ray = Ray::new(&Point3::new(-3.0, y, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+            let hit_record = field.hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+            // the first hit is on the surface, nothing of the shape before it
+            assert!(sdf(&hit_record.point).abs() < 1e-5);
+            let steps = 1000;
+            assert!((0..steps).all(|step| {
+                let t = hit_record.t * step as f64 / steps as f64;
+                sdf(&ray.at(t)) > 0.0
+            }));
+        }
+    }
+
+    #[test]
+    fn it_should_tell_functions_apart_by_their_name() {
+        let material_black = Lambertian::new(&Color3::black());
+        let sphere = DistanceField::new(
+            "sphere 1",
+            sdf::sphere(1.0),
+            &unit_bounds(),
+            &material_black,
+        );
+        let smaller_sphere = DistanceField::new(
+            "sphere 0.5",
+            sdf::sphere(0.5),
+            &unit_bounds(),
+            &material_black,
+        );
+
+        // the debug output identifies the scene to resume from a checkpoint
+        assert!(format!("{sphere:?}").contains("\"sphere 1\""));
+        assert_ne!(format!("{sphere:?}"), format!("{smaller_sphere:?}"));
+    }
+}
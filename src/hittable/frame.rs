@@ -0,0 +1,53 @@
+use crate::Vec3;
+
+/// An orthonormal basis, used to express rays in the local space of a shape.
+/// Shapes with an axis (disk, cylinder, cone, torus) are intersected along the local z axis
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Frame {
+    x: Vec3,
+    y: Vec3,
+    z: Vec3,
+}
+
+impl Frame {
+    /// a basis whose z axis is `axis` (any length), the other axes are chosen arbitrarily
+    pub(crate) fn from_z(axis: &Vec3) -> Self {
+        let z = axis.normalize();
+
+        // any vector not parallel to z gives the x axis
+        let helper = if z.x().abs() > 0.9 {
+            Vec3::new(0.0, 1.0, 0.0)
+        } else {
+            Vec3::new(1.0, 0.0, 0.0)
+        };
+        let y = z.cross(&helper).normalize();
+        let x = y.cross(&z);
+
+        Self { x, y, z }
+    }
+
+    pub(crate) fn z(&self) -> Vec3 {
+        self.z
+    }
+
+    /// the coordinates of a world vector in this basis
+    pub(crate) fn to_local(self, vector: &Vec3) -> Vec3 {
+        Vec3::new(
+            vector.dot(&self.x),
+            vector.dot(&self.y),
+            vector.dot(&self.z),
+        )
+    }
+
+    /// the world vector of local coordinates
+    pub(crate) fn to_world(self, vector: &Vec3) -> Vec3 {
+        self.x * vector.x() + self.y * vector.y() + self.z * vector.z()
+    }
+
+    /// the extent along each world axis of a circle of this basis' xy plane
+    pub(crate) fn circle_extent(&self, radius: f64) -> Vec3 {
+        let extent = |z: f64| radius * (1.0 - z * z).max(0.0).sqrt();
+
+        Vec3::new(extent(self.z.x()), extent(self.z.y()), extent(self.z.z()))
+    }
+}
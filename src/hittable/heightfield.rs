@@ -0,0 +1,404 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    image_io::FloatImage,
+    material::Material,
+    Point3, Ray, Vec3,
+};
+
+use super::triangle;
+
+/// A terrain given by a grid of heights, along the x (columns) and z (rows) axes.
+/// Each cell of the grid is made of two triangles, intersected only when the ray goes over the cell:
+/// the cells are visited along the ray with a 2D DDA (digital differential analyzer).
+/// The normals are interpolated from normals at the samples, computed from the slopes of the grid
+/// (only the heights are stored: large grids use 8 bytes per sample).
+/// Texture coordinates go from 0 to 1 across the grid
+#[derive(Debug)]
+pub struct Heightfield<'a> {
+    /// heights of the samples in world units, row after row
+    heights: Vec<f64>,
+    columns: usize,
+    rows: usize,
+    /// the sample of the first row and first column, at height 0
+    corner: Point3,
+    cell_width: f64,
+    cell_depth: f64,
+    bounds: Aabb,
+    material: &'a dyn Material,
+}
+
+impl<'a> Heightfield<'a> {
+    /// a grid of `columns` samples per row, covering `extent.x()` along x and `extent.z()` along z
+    /// from `corner`. The heights are scaled by `extent.y()`
+    /// # panics
+    /// if the grid has less than 2 rows and 2 columns, or if its last row is incomplete
+    pub fn new(
+        heights: Vec<f64>,
+        columns: usize,
+        corner: &Point3,
+        extent: &Vec3,
+        material: &'a dyn Material,
+    ) -> Self {
+        assert!(
+            columns >= 2 && heights.len().is_multiple_of(columns) && heights.len() / columns >= 2,
+            "a heightfield needs complete rows of at least 2 by 2 samples"
+        );
+
+        let rows = heights.len() / columns;
+        let heights: Vec<f64> = heights
+            .into_iter()
+            .map(|height| corner.y() + height * extent.y())
+            .collect();
+        let cell_width = extent.x() / (columns - 1) as f64;
+        let cell_depth = extent.z() / (rows - 1) as f64;
+
+        let (min, max) = heights
+            .iter()
+            .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &height| {
+                (min.min(height), max.max(height))
+            });
+        let bounds = Aabb::new(
+            &Point3::new(corner.x(), min, corner.z()),
+            &Point3::new(corner.x() + extent.x(), max, corner.z() + extent.z()),
+        );
+
+        Self {
+            heights,
+            columns,
+            rows,
+            corner: *corner,
+            cell_width,
+            cell_depth,
+            bounds,
+            material,
+        }
+    }
+
+    /// a grid with a sample per pixel: the luminance gives the height.
+    /// The first line of the image is at the corner
+    pub fn from_image(
+        image: &FloatImage,
+        corner: &Point3,
+        extent: &Vec3,
+        material: &'a dyn Material,
+    ) -> Self {
+        let heights = image
+            .pixels()
+            .iter()
+            .map(|pixel| pixel.luminance())
+            .collect();
+
+        Self::new(heights, image.width() as usize, corner, extent, material)
+    }
+
+    fn vertex(&self, column: usize, row: usize) -> Point3 {
+        Point3::new(
+            self.corner.x() + column as f64 * self.cell_width,
+            self.heights[row * self.columns + column],
+            self.corner.z() + row as f64 * self.cell_depth,
+        )
+    }
+
+    /// the normal of a sample, given by the slopes to its neighbors (central differences)
+    fn sample_normal(&self, column: usize, row: usize) -> Vec3 {
+        let height = |column: usize, row: usize| self.heights[row * self.columns + column];
+        let (left, right) = (column.saturating_sub(1), (column + 1).min(self.columns - 1));
+        let (back, front) = (row.saturating_sub(1), (row + 1).min(self.rows - 1));
+
+        let slope_x =
+            (height(right, row) - height(left, row)) / ((right - left) as f64 * self.cell_width);
+        let slope_z = (height(column, front) - height(column, back))
+            / ((front - back) as f64 * self.cell_depth);
+
+        Vec3::new(-slope_x, 1.0, -slope_z).normalize()
+    }
+
+    /// intersect the two triangles of a cell, facing +y
+    fn hit_cell(
+        &self,
+        column: usize,
+        row: usize,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> Option<HitRecord<'_>> {
+        let corners = [
+            (column, row),
+            (column, row + 1),
+            (column + 1, row + 1),
+            (column + 1, row),
+        ];
+        let triangles = [
+            [corners[0], corners[1], corners[2]],
+            [corners[0], corners[2], corners[3]],
+        ];
+
+        // the closest hit: time, barycentric coordinates and index of the triangle
+        let mut closest: Option<(f64, f64, f64, usize)> = None;
+        for (index, triangle) in triangles.iter().enumerate() {
+            let [vertex_0, vertex_1, vertex_2] =
+                triangle.map(|(column, row)| self.vertex(column, row));
+            let t_max = closest.map_or(t_max, |(t, _, _, _)| t);
+
+            if let Some((t, u, v)) =
+                triangle::intersect(ray, &vertex_0, &vertex_1, &vertex_2, t_min, t_max)
+            {
+                closest = Some((t, u, v, index));
+            }
+        }
+
+        let (t, u, v, index) = closest?;
+        let triangle = triangles[index];
+        let [vertex_0, vertex_1, vertex_2] = triangle.map(|(column, row)| self.vertex(column, row));
+        let [normal_0, normal_1, normal_2] =
+            triangle.map(|(column, row)| self.sample_normal(column, row));
+
+        // the geometric normal decides which face is hit
+        let face_normal = (vertex_1 - vertex_0)
+            .cross(&(vertex_2 - vertex_0))
+            .normalize();
+        let point = ray.at(t);
+        let mut hit_record = HitRecord::new(ray, &point, &face_normal, self.material, t);
+
+        let normal = (normal_0 * (1.0 - u - v) + normal_1 * u + normal_2 * v).normalize();
+        hit_record.normal = if hit_record.front_face {
+            normal
+        } else {
+            -normal
+        };
+
+        hit_record.u = ((point.x() - self.corner.x()) / (self.bounds.max().x() - self.corner.x()))
+            .clamp(0.0, 1.0);
+        hit_record.v = ((point.z() - self.corner.z()) / (self.bounds.max().z() - self.corner.z()))
+            .clamp(0.0, 1.0);
+
+        Some(hit_record)
+    }
+}
+
+impl<'a> Hittable for Heightfield<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let crossing = self.bounds.slabs(ray)?;
+        let t_start = crossing.t_entry.max(t_min);
+        let t_end = crossing.t_exit.min(t_max);
+        if t_start > t_end {
+            return None;
+        }
+
+        let (nb_cell_columns, nb_cell_rows) = (self.columns - 1, self.rows - 1);
+        let start = ray.at(t_start);
+        let cell_of = |position: f64, size: f64, nb_cells: usize| {
+            ((position / size).floor().max(0.0) as usize).min(nb_cells - 1)
+        };
+        let mut column = cell_of(
+            start.x() - self.corner.x(),
+            self.cell_width,
+            nb_cell_columns,
+        );
+        let mut row = cell_of(start.z() - self.corner.z(), self.cell_depth, nb_cell_rows);
+
+        // for each axis: the direction of the steps, the time of the next cell border,
+        // and the time needed to cross a cell
+        let axis_steps = |direction: f64, origin: f64, cell: usize, size: f64| {
+            if direction > 0.0 {
+                (
+                    1,
+                    ((cell + 1) as f64 * size - origin) / direction,
+                    size / direction,
+                )
+            } else if direction < 0.0 {
+                (
+                    -1,
+                    (cell as f64 * size - origin) / direction,
+                    -size / direction,
+                )
+            } else {
+                (0, f64::INFINITY, f64::INFINITY)
+            }
+        };
+        let (step_x, mut t_next_x, t_delta_x) = axis_steps(
+            ray.direction().x(),
+            ray.origin().x() - self.corner.x(),
+            column,
+            self.cell_width,
+        );
+        let (step_z, mut t_next_z, t_delta_z) = axis_steps(
+            ray.direction().z(),
+            ray.origin().z() - self.corner.z(),
+            row,
+            self.cell_depth,
+        );
+
+        // the cells are visited in the order of the ray: the first hit is the closest
+        loop {
+            if let Some(hit_record) = self.hit_cell(column, row, ray, t_min, t_max) {
+                return Some(hit_record);
+            }
+
+            if t_next_x < t_next_z {
+                if t_next_x > t_end {
+                    return None;
+                }
+                column = column
+                    .checked_add_signed(step_x)
+                    .filter(|&column| column < nb_cell_columns)?;
+                t_next_x += t_delta_x;
+            } else {
+                if t_next_z > t_end {
+                    return None;
+                }
+                row = row
+                    .checked_add_signed(step_z)
+                    .filter(|&row| row < nb_cell_rows)?;
+                t_next_z += t_delta_z;
+            }
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.bounds)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        hittable::{Mesh, TriangleMesh},
+        material::Lambertian,
+        Color3,
+    };
+
+    use super::*;
+
+    /// a bumpy terrain of 20 by 15 samples, over 10 by 5 units
+    fn terrain(material: &dyn Material) -> Heightfield<'_> {
+        let (columns, rows) = (20, 15);
+        let heights = (0..rows)
+            .flat_map(|row| {
+                (0..columns).map(move |column| {
+                    ((column as f64 * 0.7).sin() + (row as f64 * 1.3).cos()) * 0.5
+                })
+            })
+            .collect();
+
+        Heightfield::new(
+            heights,
+            columns,
+            &Point3::new(-5.0, 1.0, -2.0),
+            &Vec3::new(10.0, 2.0, 5.0),
+            material,
+        )
+    }
+
+    /// the same terrain, as a mesh of all its triangles
+    fn terrain_mesh(heightfield: &Heightfield) -> TriangleMesh {
+        let positions = (0..heightfield.rows)
+            .flat_map(|row| (0..heightfield.columns).map(move |column| (column, row)))
+            .map(|(column, row)| heightfield.vertex(column, row))
+            .collect();
+        let index = |column: usize, row: usize| row * heightfield.columns + column;
+        let triangles = (0..heightfield.rows - 1)
+            .flat_map(|row| (0..heightfield.columns - 1).map(move |column| (column, row)))
+            .flat_map(|(column, row)| {
+                [
+                    [
+                        index(column, row),
+                        index(column, row + 1),
+                        index(column + 1, row + 1),
+                    ],
+                    [
+                        index(column, row),
+                        index(column + 1, row + 1),
+                        index(column + 1, row),
+                    ],
+                ]
+            })
+            .collect();
+
+        TriangleMesh::new(positions, triangles)
+    }
+
+    #[test]
+    fn it_should_hit_the_same_triangles_as_a_mesh() {
+        let material = Lambertian::new(&Color3::white());
+        let heightfield = terrain(&material);
+        let geometry = terrain_mesh(&heightfield);
+        let mesh = Mesh::new(&geometry, &material);
+
+        let mut nb_hits = 0;
+        for i in 0..200 {
+            let angle = i as f64 * 0.37;
+            let origin = Point3::new(angle.cos() * 8.0, 4.0 + (i % 7) as f64, angle.sin() * 8.0);
+            let target = Point3::new((i % 11) as f64 - 5.0, 1.0, (i % 5) as f64 - 2.0);
+            let ray = Ray::new(&origin, &(target - origin));
+
+            let expected = mesh.hit(&ray, 0.0, f64::INFINITY);
+            let hit_record = heightfield.hit(&ray, 0.0, f64::INFINITY);
+
+            assert_eq!(hit_record.is_some(), expected.is_some());
+            if let (Some(hit_record), Some(expected)) = (hit_record, expected) {
+                assert!((hit_record.t - expected.t).abs() < 1e-9);
+                assert_eq!(hit_record.front_face, expected.front_face);
+                nb_hits += 1;
+            }
+        }
+
+        // the rays are not all missing the terrain
+        assert!(nb_hits > 100);
+    }
+
+    #[test]
+    fn it_should_interpolate_the_normals() {
+        let material = Lambertian::new(&Color3::white());
+
+        // a slope rising along x, at 45°
+        let heights = vec![0.0, 1.0, 2.0, 0.0, 1.0, 2.0, 0.0, 1.0, 2.0];
+        let heightfield = Heightfield::new(
+            heights,
+            3,
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(2.0, 1.0, 2.0),
+            &material,
+        );
+
+        let ray = Ray::new(&Point3::new(0.5, 10.0, 1.5), &Vec3::new(0.0, -1.0, 0.0));
+        let hit_record = heightfield.hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point - Point3::new(0.5, 0.5, 1.5)).mag() < 1e-9);
+        assert!((hit_record.normal - Vec3::new(-1.0, 1.0, 0.0).normalize()).mag() < 1e-9);
+        assert!(hit_record.front_face);
+        assert!((hit_record.u - 0.25).abs() < 1e-9);
+        assert!((hit_record.v - 0.75).abs() < 1e-9);
+
+        // from below
+        let ray = Ray::new(&Point3::new(0.5, -10.0, 1.5), &Vec3::new(0.0, 1.0, 0.0));
+        let hit_record = heightfield.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!(!hit_record.front_face);
+        assert!((hit_record.normal - Vec3::new(1.0, -1.0, 0.0).normalize()).mag() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_read_heights_from_an_image() {
+        let material = Lambertian::new(&Color3::white());
+        let image = FloatImage::new(
+            2,
+            2,
+            vec![
+                Color3::black(),
+                Color3::white(),
+                Color3::black(),
+                Color3::white(),
+            ],
+        );
+        let heightfield = Heightfield::from_image(
+            &image,
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(1.0, 3.0, 1.0),
+            &material,
+        );
+
+        let aabb = heightfield.bounding_box().unwrap();
+        assert!((aabb.max() - Point3::new(1.0, 3.0, 1.0)).mag() < 1e-9);
+        assert_eq!(aabb.min(), Point3::new(0.0, 0.0, 0.0));
+    }
+}
@@ -1,6 +1,8 @@
-use crate::{material::Material, Point3, Ray, Vec3};
+use std::fmt::Debug;
 
-use super::Triangle;
+use crate::{material::Material, Color3, Point3, Ray, Vec3};
+
+use super::{Aabb, Triangle};
 
 #[derive(Debug)]
 pub struct HitRecord<'a> {
@@ -10,10 +12,17 @@ pub struct HitRecord<'a> {
     pub material: &'a dyn Material,
     /// normal of the hit
     pub normal: Vec3,
+    /// identifier of the object hit: its index in the world `HittableList` (0 for a lone shape)
+    pub object_id: u32,
     /// point hit by the ray
     pub point: Point3,
     /// time of the hit
     pub t: f64,
+    /// texture coordinates of the hit point (0 for shapes without a mapping)
+    pub u: f64,
+    pub v: f64,
+    /// interpolated color of the vertices, for meshes with vertex colors
+    pub vertex_color: Option<Color3>,
 }
 
 impl<'h> HitRecord<'h> {
@@ -33,28 +42,59 @@ impl<'h> HitRecord<'h> {
         let normal = if front_face {
             *outward_normal
         } else {
-            *outward_normal * (-1.0)
+            -*outward_normal
         };
 
         Self {
             front_face,
             material,
             normal,
+            object_id: 0,
             point: *point,
             t,
+            u: 0.0,
+            v: 0.0,
+            vertex_color: None,
         }
     }
 }
 
-pub trait Hittable: Send + Sync {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord>;
+pub trait Hittable: Debug + Send + Sync {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>>;
+
+    /// every hit along the ray between `t_min` and `t_max`, in increasing time.
+    /// For a closed shape, they alternate between entries (front face) and exits (back face).
+    /// By default, the shape is hit again and again just after the previous hit:
+    /// shapes knowing all their hits at once should give them directly
+    fn all_hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        let mut hits = Vec::new();
+        let mut t_start = t_min;
+
+        while let Some(hit_record) = self.hit(ray, t_start, t_max) {
+            // stepping over the hit found, not to find it again
+            t_start = hit_record.t + 1e-9 * hit_record.t.abs().max(1.0);
+            hits.push(hit_record);
+        }
+
+        hits
+    }
+
+    /// true if the ray hits the shape between `t_min` and `t_max`, for shadow rays:
+    /// any hit will do, so implementations can stop at the first one they find
+    /// and skip building a hit record
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.hit(ray, t_min, t_max).is_some()
+    }
+
+    /// the box containing the whole shape (None for unbounded shapes, like planes)
+    fn bounding_box(&self) -> Option<Aabb>;
 }
 
 /// This trait should be implemented for shapes that are compound shapes
 /// (a square = 2 triangles)
 /// (a tetrahedron = 4 triangles)
 pub trait MultiFaceHittable: Send + Sync + Hittable {
-    fn get_closest_hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn get_closest_hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let objects = self.get_faces();
 
         let mut closest_hit_record: Option<HitRecord> = None;
@@ -77,5 +117,20 @@ pub trait MultiFaceHittable: Send + Sync + Hittable {
         closest_hit_record
     }
 
-    fn get_faces(&self) -> &Vec<Triangle>;
+    /// true if any face is hit, stopping at the first one
+    fn get_any_hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.get_faces()
+            .iter()
+            .any(|face| face.occluded(ray, t_min, t_max))
+    }
+
+    /// the box containing all the faces
+    fn get_faces_bounding_box(&self) -> Option<Aabb> {
+        self.get_faces()
+            .iter()
+            .filter_map(|face| face.bounding_box())
+            .reduce(|a, b| a.union(&b))
+    }
+
+    fn get_faces(&self) -> &Vec<Triangle<'_>>;
 }
@@ -1,9 +1,16 @@
-use crate::hittable::{HitRecord, Hittable};
+use crate::hittable::{Aabb, HitRecord, Hittable};
 
+#[derive(Debug)]
 pub struct HittableList<'a> {
     objects: Vec<&'a dyn Hittable>,
 }
 
+impl Default for HittableList<'_> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl<'a> HittableList<'a> {
     pub fn new() -> HittableList<'a> {
         HittableList {
@@ -24,8 +31,11 @@ impl Hittable for HittableList<'_> {
     fn hit(&self, ray: &crate::Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         let mut closest_hit_record: Option<HitRecord> = None;
 
-        for object in &self.objects {
-            if let Some(hit_record) = object.hit(ray, t_min, t_max) {
+        for (object_id, object) in self.objects.iter().enumerate() {
+            if let Some(mut hit_record) = object.hit(ray, t_min, t_max) {
+                // the list owning the object names it (a nested list is seen as one object)
+                hit_record.object_id = object_id as u32;
+
                 match closest_hit_record {
                     None => {
                         closest_hit_record = Some(hit_record);
@@ -41,10 +51,23 @@ impl Hittable for HittableList<'_> {
 
         closest_hit_record
     }
+
+    fn occluded(&self, ray: &crate::Ray, t_min: f64, t_max: f64) -> bool {
+        self.objects
+            .iter()
+            .any(|object| object.occluded(ray, t_min, t_max))
+    }
+
+    /// None if the list is empty or holds an unbounded object
+    fn bounding_box(&self) -> Option<Aabb> {
+        let mut boxes = self.objects.iter().map(|object| object.bounding_box());
+        let first = boxes.next()??;
+
+        boxes.try_fold(first, |union, aabb| Some(union.union(&aabb?)))
+    }
 }
 
 #[cfg(test)]
-
 mod test {
 
     use super::{HitRecord, HittableList};
@@ -80,6 +103,10 @@ mod test {
             t: 99.0,
             front_face: true,
             material: &material_black,
+            object_id: 0,
+            u: 0.0,
+            v: 0.0,
+            vertex_color: None,
         };
 
         assert_eq!(hit_record.point, expected_record.point);
@@ -89,7 +116,6 @@ mod test {
     }
 
     #[test]
-
     fn it_should_detect_intersection_with_one_sphere() {
         let material_black = Lambertian::new(&Color3::black());
 
@@ -115,6 +141,10 @@ mod test {
             t: 0.5,
             front_face: true,
             material: &material_black,
+            object_id: 1,
+            u: 0.0,
+            v: 0.0,
+            vertex_color: None,
         };
 
         assert_eq!(hit_record.point, expected_record.point);
@@ -122,4 +152,45 @@ mod test {
         assert_eq!(hit_record.t, expected_record.t);
         assert_eq!(hit_record.front_face, expected_record.front_face);
     }
+
+    #[test]
+    fn it_should_identify_the_object_hit() {
+        let material_black = Lambertian::new(&Color3::black());
+
+        let sphere1 = Sphere::new(&Vec3::new(0.0, 0.0, 0.0), 1.0, &material_black);
+        let sphere2 = Sphere::new(&Vec3::new(3.0, 0.0, 0.0), 1.0, &material_black);
+
+        let mut world = HittableList::new();
+        world.add(&sphere1);
+        world.add(&sphere2);
+
+        // ray comming from the right: the second sphere is hit first
+        let ray = Ray::new(&Vec3::new(100.0, 0.0, 0.0), &Vec3::new(-1.0, 0.0, 0.0));
+        assert_eq!(world.hit(&ray, 0.0, f64::INFINITY).unwrap().object_id, 1);
+
+        let ray = Ray::new(&Vec3::new(-100.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert_eq!(world.hit(&ray, 0.0, f64::INFINITY).unwrap().object_id, 0);
+    }
+
+    #[test]
+    fn it_should_be_occluded_by_any_object() {
+        let material_black = Lambertian::new(&Color3::black());
+
+        let sphere1 = Sphere::new(&Vec3::new(0.0, 0.0, 0.0), 1.0, &material_black);
+        let sphere2 = Sphere::new(&Vec3::new(3.0, 0.0, 0.0), 1.0, &material_black);
+
+        let mut world = HittableList::new();
+        world.add(&sphere1);
+        world.add(&sphere2);
+
+        let ray = Ray::new(&Vec3::new(-100.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(world.occluded(&ray, 0.0, f64::INFINITY));
+        // between the spheres
+        assert!(!world.occluded(&ray, 101.5, 101.9));
+        assert!(world.occluded(&ray, 101.5, 102.1));
+
+        // above the spheres
+        let ray = Ray::new(&Vec3::new(-100.0, 2.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(!world.occluded(&ray, 0.0, f64::INFINITY));
+    }
 }
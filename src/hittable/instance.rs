@@ -0,0 +1,222 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable, Transform, TriangleMesh},
+    material::Material,
+    Ray,
+};
+
+use super::bvh::BvhTree;
+
+/// The bottom level of a two level hierarchy: a bounding volume hierarchy over the triangles of a mesh.
+/// It is built once per mesh, and shared by all the instances of the mesh
+#[derive(Debug)]
+pub struct MeshBvh<'g> {
+    geometry: &'g TriangleMesh,
+    tree: BvhTree,
+}
+
+impl<'g> MeshBvh<'g> {
+    pub fn new(geometry: &'g TriangleMesh) -> Self {
+        let boxes: Vec<Aabb> = geometry
+            .triangles()
+            .iter()
+            .map(|triangle| {
+                Aabb::from_points(triangle.iter().map(|&index| &geometry.positions()[index]))
+                    .unwrap()
+            })
+            .collect();
+
+        Self {
+            geometry,
+            tree: BvhTree::new(&boxes),
+        }
+    }
+
+    pub fn geometry(&self) -> &TriangleMesh {
+        self.geometry
+    }
+
+    /// the closest hit of the ray, in the space of the mesh
+    fn hit<'m>(
+        &self,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        material: &'m dyn Material,
+    ) -> Option<HitRecord<'m>> {
+        self.tree.closest_hit(ray, t_min, t_max, |index, t_max| {
+            self.geometry
+                .hit_triangle(index, ray, t_min, t_max, material)
+        })
+    }
+
+    /// true if any triangle is hit, in the space of the mesh
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.tree.any_hit(ray, t_min, t_max, |index| {
+            self.geometry.occludes_triangle(index, ray, t_min, t_max)
+        })
+    }
+}
+
+/// A mesh placed in the world by a transform, with a material.
+/// Instances only hold a reference to the tree of their mesh: their memory does not depend on
+/// the size of the mesh. A `Bvh` over instances is the top level of the hierarchy
+#[derive(Debug)]
+pub struct Instance<'a> {
+    mesh: &'a MeshBvh<'a>,
+    transform: Transform,
+    material: &'a dyn Material,
+}
+
+impl<'a> Instance<'a> {
+    pub fn new(mesh: &'a MeshBvh<'a>, transform: &Transform, material: &'a dyn Material) -> Self {
+        Self {
+            mesh,
+            transform: *transform,
+            material,
+        }
+    }
+}
+
+impl<'a> Hittable for Instance<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        // the times are the same in the space of the mesh
+        let local_ray = self.transform.inverse_ray(ray);
+        let mut hit_record = self.mesh.hit(&local_ray, t_min, t_max, self.material)?;
+
+        // the transform keeps the normal on the side of the ray
+        hit_record.point = ray.at(hit_record.t);
+        hit_record.normal = self.transform.apply_normal(&hit_record.normal).normalize();
+
+        Some(hit_record)
+    }
+
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.mesh
+            .occluded(&self.transform.inverse_ray(ray), t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Some(self.mesh.tree.bounds()?.transformed(&self.transform))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        hittable::{Bvh, Mesh},
+        material::Lambertian,
+        Color3, Point3, Vec3,
+    };
+
+    use super::*;
+
+    /// a grid of 10 by 10 squares in the xy plane, a bit wavy
+    fn grid() -> TriangleMesh {
+        let size = 11;
+        let positions = (0..size * size)
+            .map(|index| {
+                let (x, y) = ((index % size) as f64, (index / size) as f64);
+                Point3::new(x, y, (x * 0.5).sin() * (y * 0.3).cos())
+            })
+            .collect();
+        let triangles = (0..size - 1)
+            .flat_map(|y| (0..size - 1).map(move |x| y * size + x))
+            .flat_map(|corner| {
+                [
+                    [corner, corner + 1, corner + size + 1],
+                    [corner, corner + size + 1, corner + size],
+                ]
+            })
+            .collect();
+
+        TriangleMesh::new(positions, triangles).with_smooth_normals()
+    }
+
+    #[test]
+    fn it_should_hit_like_the_transformed_mesh() {
+        let material = Lambertian::new(&Color3::white());
+        let geometry = grid();
+        let mesh_bvh = MeshBvh::new(&geometry);
+
+        let transform = Transform::scaling(&Vec3::new(0.5, 0.5, 2.0))
+            .then(&Transform::rotation(&Vec3::new(1.0, 1.0, 0.0), PI / 3.0))
+            .then(&Transform::translation(&Vec3::new(3.0, -1.0, 2.0)));
+        let instance = Instance::new(&mesh_bvh, &transform, &material);
+
+        // the same mesh, transformed vertex by vertex
+        let moved = TriangleMesh::new(
+            geometry
+                .positions()
+                .iter()
+                .map(|position| transform.apply_point(position))
+                .collect(),
+            geometry.triangles().to_vec(),
+        )
+        .with_smooth_normals();
+        let mesh = Mesh::new(&moved, &material);
+
+        let center = instance.bounding_box().unwrap().center();
+        let mut rng = StdRng::seed_from_u64(4);
+        let mut nb_hits = 0;
+        for _ in 0..300 {
+            let origin = center + Vec3::new_randow_unit_vector(&mut rng) * 10.0;
+            let target = center + Vec3::new_clamped_random(-2.0, 2.0, &mut rng);
+            let ray = Ray::new(&origin, &(target - origin));
+
+            let expected = mesh.hit(&ray, 0.0, f64::INFINITY);
+            let hit_record = instance.hit(&ray, 0.0, f64::INFINITY);
+
+            assert_eq!(hit_record.is_some(), expected.is_some());
+            assert_eq!(
+                instance.occluded(&ray, 0.0, f64::INFINITY),
+                expected.is_some()
+            );
+            assert_eq!(mesh.occluded(&ray, 0.0, f64::INFINITY), expected.is_some());
+            if let (Some(hit_record), Some(expected)) = (hit_record, expected) {
+                assert!((hit_record.t - expected.t).abs() < 1e-9);
+                assert!((hit_record.point - expected.point).mag() < 1e-9);
+                assert_eq!(hit_record.front_face, expected.front_face);
+                // the normals of the moved mesh are computed after the scaling: they are close, not equal
+                assert!(hit_record.normal.dot(&expected.normal) > 0.9);
+                nb_hits += 1;
+            }
+        }
+        assert!(nb_hits > 50);
+    }
+
+    #[test]
+    fn it_should_share_the_mesh_between_instances() {
+        let material = Lambertian::new(&Color3::white());
+        let geometry = grid();
+        let mesh_bvh = MeshBvh::new(&geometry);
+
+        // a row of copies along z
+        let instances: Vec<Instance> = (0..100)
+            .map(|i| {
+                let transform = Transform::translation(&Vec3::new(0.0, 0.0, -3.0 * i as f64));
+                Instance::new(&mesh_bvh, &transform, &material)
+            })
+            .collect();
+        let world = Bvh::new(
+            instances
+                .iter()
+                .map(|instance| instance as &dyn Hittable)
+                .collect(),
+        );
+
+        // from the front, the first copy hides the others
+        let ray = Ray::new(&Point3::new(5.0, 5.0, 10.0), &Vec3::new(0.0, 0.0, -1.0));
+        let hit_record = world.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert_eq!(hit_record.object_id, 0);
+
+        // from far away on the other side, the last copy is hit
+        let ray = Ray::new(&Point3::new(5.0, 5.0, -1000.0), &Vec3::new(0.0, 0.0, 1.0));
+        let hit_record = world.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert_eq!(hit_record.object_id, 99);
+        assert!(!hit_record.front_face);
+    }
+}
@@ -0,0 +1,380 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Color3, Point3, Ray, Vec3,
+};
+
+use super::triangle;
+
+/// The geometry of an indexed triangle mesh: vertices are shared between the triangles using them.
+/// Vertices can carry a normal (for smooth shading), texture coordinates and a color.
+/// Triangles are counter clockwise seen from their front face
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriangleMesh {
+    positions: Vec<Point3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<(f64, f64)>>,
+    colors: Option<Vec<Color3>>,
+    /// indices of the three vertices of each triangle
+    triangles: Vec<[usize; 3]>,
+}
+
+impl TriangleMesh {
+    /// a mesh without vertex normals (flat shading) nor texture coordinates
+    /// # panics
+    /// if a triangle uses a vertex that does not exist
+    pub fn new(positions: Vec<Point3>, triangles: Vec<[usize; 3]>) -> Self {
+        assert!(
+            triangles
+                .iter()
+                .flatten()
+                .all(|&index| index < positions.len()),
+            "a triangle uses a vertex out of the mesh"
+        );
+
+        Self {
+            positions,
+            normals: None,
+            uvs: None,
+            colors: None,
+            triangles,
+        }
+    }
+
+    /// the same mesh, with a normal per vertex
+    /// # panics
+    /// if there is not exactly one normal per vertex
+    pub fn with_normals(mut self, normals: Vec<Vec3>) -> Self {
+        assert_eq!(
+            normals.len(),
+            self.positions.len(),
+            "one normal per vertex expected"
+        );
+
+        self.normals = Some(normals.iter().map(|normal| normal.normalize()).collect());
+        self
+    }
+
+    /// the same mesh, with texture coordinates per vertex
+    /// # panics
+    /// if there is not exactly one pair of texture coordinates per vertex
+    pub fn with_uvs(mut self, uvs: Vec<(f64, f64)>) -> Self {
+        assert_eq!(
+            uvs.len(),
+            self.positions.len(),
+            "one uv per vertex expected"
+        );
+
+        self.uvs = Some(uvs);
+        self
+    }
+
+    /// the same mesh, with a linear color per vertex (see `VertexColors` to use them as a texture)
+    /// # panics
+    /// if there is not exactly one color per vertex
+    pub fn with_colors(mut self, colors: Vec<Color3>) -> Self {
+        assert_eq!(
+            colors.len(),
+            self.positions.len(),
+            "one color per vertex expected"
+        );
+
+        self.colors = Some(colors);
+        self
+    }
+
+    /// the same mesh, with normals computed from the geometry (for files without normals).
+    /// The normal of a vertex is the average of the normals of the triangles around it, weighted by their area
+    pub fn with_smooth_normals(self) -> Self {
+        let mut normals = vec![Vec3::new(0.0, 0.0, 0.0); self.positions.len()];
+
+        for triangle in &self.triangles {
+            // the length of the cross product is twice the area of the triangle
+            let [vertex_0, vertex_1, vertex_2] = triangle.map(|index| self.positions[index]);
+            let face_normal = (vertex_1 - vertex_0).cross(&(vertex_2 - vertex_0));
+
+            for &index in triangle {
+                normals[index] += face_normal;
+            }
+        }
+
+        // vertices used by no triangle (or only by degenerate ones) keep a null normal
+        let normals = normals
+            .into_iter()
+            .map(|normal| {
+                if normal.is_near_zero() {
+                    normal
+                } else {
+                    normal.normalize()
+                }
+            })
+            .collect();
+
+        Self {
+            normals: Some(normals),
+            ..self
+        }
+    }
+
+    pub fn positions(&self) -> &[Point3] {
+        &self.positions
+    }
+
+    pub fn normals(&self) -> Option<&[Vec3]> {
+        self.normals.as_deref()
+    }
+
+    pub fn uvs(&self) -> Option<&[(f64, f64)]> {
+        self.uvs.as_deref()
+    }
+
+    pub fn colors(&self) -> Option<&[Color3]> {
+        self.colors.as_deref()
+    }
+
+    pub fn triangles(&self) -> &[[usize; 3]] {
+        &self.triangles
+    }
+
+    /// true if one triangle of the mesh is hit (without computing the hit record)
+    pub(crate) fn occludes_triangle(
+        &self,
+        index: usize,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+    ) -> bool {
+        let [i_0, i_1, i_2] = self.triangles[index];
+
+        triangle::intersect(
+            ray,
+            &self.positions[i_0],
+            &self.positions[i_1],
+            &self.positions[i_2],
+            t_min,
+            t_max,
+        )
+        .is_some()
+    }
+
+    /// intersect one triangle of the mesh.
+    /// The hit carries the interpolated normal, texture coordinates and color
+    pub(crate) fn hit_triangle<'m>(
+        &self,
+        index: usize,
+        ray: &Ray,
+        t_min: f64,
+        t_max: f64,
+        material: &'m dyn Material,
+    ) -> Option<HitRecord<'m>> {
+        let [i_0, i_1, i_2] = self.triangles[index];
+        let (vertex_0, vertex_1, vertex_2) = (
+            self.positions[i_0],
+            self.positions[i_1],
+            self.positions[i_2],
+        );
+
+        let (t, u, v) = triangle::intersect(ray, &vertex_0, &vertex_1, &vertex_2, t_min, t_max)?;
+        let w = 1.0 - u - v;
+
+        // the geometric normal decides which face is hit
+        let face_normal = (vertex_1 - vertex_0)
+            .cross(&(vertex_2 - vertex_0))
+            .normalize();
+        let mut hit_record = HitRecord::new(ray, &ray.at(t), &face_normal, material, t);
+
+        if let Some(normals) = &self.normals {
+            let normal = (normals[i_0] * w + normals[i_1] * u + normals[i_2] * v).normalize();
+
+            // keeping the shading normal on the side of the ray, like the geometric one
+            hit_record.normal = if hit_record.front_face {
+                normal
+            } else {
+                -normal
+            };
+        }
+
+        (hit_record.u, hit_record.v) = match &self.uvs {
+            Some(uvs) => (
+                uvs[i_0].0 * w + uvs[i_1].0 * u + uvs[i_2].0 * v,
+                uvs[i_0].1 * w + uvs[i_1].1 * u + uvs[i_2].1 * v,
+            ),
+            None => (u, v),
+        };
+
+        hit_record.vertex_color = self
+            .colors
+            .as_ref()
+            .map(|colors| colors[i_0] * w + colors[i_1] * u + colors[i_2] * v);
+
+        Some(hit_record)
+    }
+}
+
+/// A triangle mesh made of a material.
+/// The geometry is borrowed: several objects can share the same mesh
+#[derive(Debug)]
+pub struct Mesh<'a> {
+    geometry: &'a TriangleMesh,
+    material: &'a dyn Material,
+}
+
+impl<'a> Mesh<'a> {
+    pub fn new(geometry: &'a TriangleMesh, material: &'a dyn Material) -> Self {
+        Self { geometry, material }
+    }
+
+    pub fn geometry(&self) -> &TriangleMesh {
+        self.geometry
+    }
+}
+
+impl<'a> Hittable for Mesh<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let mut closest_hit_record: Option<HitRecord> = None;
+        let mut closest_t = t_max;
+
+        for index in 0..self.geometry.triangles.len() {
+            if let Some(hit_record) =
+                self.geometry
+                    .hit_triangle(index, ray, t_min, closest_t, self.material)
+            {
+                closest_t = hit_record.t;
+                closest_hit_record = Some(hit_record);
+            }
+        }
+
+        closest_hit_record
+    }
+
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        (0..self.geometry.triangles.len())
+            .any(|index| self.geometry.occludes_triangle(index, ray, t_min, t_max))
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        Aabb::from_points(&self.geometry.positions)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::material::Lambertian;
+
+    /// a unit square in the xy plane, facing +z, made of two triangles
+    fn square() -> TriangleMesh {
+        TriangleMesh::new(
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+            ],
+            vec![[0, 1, 2], [0, 2, 3]],
+        )
+    }
+
+    /// the top of a pyramid: a vertex above the center of the unit square
+    fn pyramid() -> TriangleMesh {
+        TriangleMesh::new(
+            vec![
+                Point3::new(0.0, 0.0, 0.0),
+                Point3::new(1.0, 0.0, 0.0),
+                Point3::new(1.0, 1.0, 0.0),
+                Point3::new(0.0, 1.0, 0.0),
+                Point3::new(0.5, 0.5, 0.5),
+            ],
+            vec![[0, 1, 4], [1, 2, 4], [2, 3, 4], [3, 0, 4]],
+        )
+    }
+
+    fn ray_towards(x: f64, y: f64) -> Ray {
+        Ray::new(&Point3::new(x, y, 10.0), &Vec3::new(0.0, 0.0, -1.0))
+    }
+
+    #[test]
+    fn it_should_hit_the_closest_triangle() {
+        let material = Lambertian::new(&Color3::white());
+        let geometry = square();
+        let mesh = Mesh::new(&geometry, &material);
+
+        let hit_record = mesh
+            .hit(&ray_towards(0.25, 0.75), 0.0, f64::INFINITY)
+            .unwrap();
+        assert_eq!(hit_record.t, 10.0);
+        assert_eq!(hit_record.point, Point3::new(0.25, 0.75, 0.0));
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(hit_record.front_face);
+
+        assert!(mesh
+            .hit(&ray_towards(1.5, 0.5), 0.0, f64::INFINITY)
+            .is_none());
+        assert!(mesh.hit(&ray_towards(0.5, 0.5), 0.0, 5.0).is_none());
+    }
+
+    #[test]
+    fn it_should_interpolate_the_texture_coordinates() {
+        let material = Lambertian::new(&Color3::white());
+        let geometry = square().with_uvs(vec![(0.0, 0.0), (2.0, 0.0), (2.0, 4.0), (0.0, 4.0)]);
+        let mesh = Mesh::new(&geometry, &material);
+
+        for (x, y) in [(0.25, 0.75), (0.8, 0.1), (0.5, 0.5)] {
+            let hit_record = mesh.hit(&ray_towards(x, y), 0.0, f64::INFINITY).unwrap();
+
+            assert!((hit_record.u - 2.0 * x).abs() < 1e-9);
+            assert!((hit_record.v - 4.0 * y).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn it_should_interpolate_the_vertex_normals() {
+        let material = Lambertian::new(&Color3::white());
+        let tilted = Vec3::new(1.0, 0.0, 1.0);
+        let geometry = square().with_normals(vec![
+            Vec3::new(0.0, 0.0, 1.0),
+            tilted,
+            tilted,
+            Vec3::new(0.0, 0.0, 1.0),
+        ]);
+        let mesh = Mesh::new(&geometry, &material);
+
+        // on a vertex, the normal is the one of the vertex
+        let hit_record = mesh
+            .hit(&ray_towards(1.0, 0.0), 0.0, f64::INFINITY)
+            .unwrap();
+        assert!((hit_record.normal - tilted.normalize()).mag() < 1e-9);
+
+        // half way, the normal is half tilted
+        let hit_record = mesh
+            .hit(&ray_towards(0.5, 0.5), 0.0, f64::INFINITY)
+            .unwrap();
+        let expected = (Vec3::new(0.0, 0.0, 1.0) + tilted.normalize()).normalize();
+        assert!((hit_record.normal - expected).mag() < 1e-9);
+
+        // seen from the back, the normal still faces the ray
+        let ray = Ray::new(&Point3::new(0.5, 0.5, -10.0), &Vec3::new(0.0, 0.0, 1.0));
+        let hit_record = mesh.hit(&ray, 0.0, f64::INFINITY).unwrap();
+        assert!(!hit_record.front_face);
+        assert!((hit_record.normal + expected).mag() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_generate_smooth_normals() {
+        let geometry = pyramid().with_smooth_normals();
+        let normals = geometry.normals().unwrap();
+
+        // the apex is shared by four symmetric faces: its normal points up
+        assert!((normals[4] - Vec3::new(0.0, 0.0, 1.0)).mag() < 1e-9);
+
+        // a corner averages its two faces
+        let expected = Vec3::new(-1.0, -1.0, 2.0).normalize();
+        assert!((normals[0] - expected).mag() < 1e-9);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_reject_triangles_out_of_the_mesh() {
+        TriangleMesh::new(vec![Point3::new(0.0, 0.0, 0.0)], vec![[0, 0, 1]]);
+    }
+}
@@ -1,13 +1,49 @@
+mod aabb;
+mod bilinear_patch;
+mod bvh;
+mod cone;
+mod convex_polyhedron;
+mod csg;
+mod cuboid;
+mod cylinder;
+mod disk;
+mod distance_field;
+mod frame;
+mod heightfield;
+#[allow(clippy::module_inception)]
 mod hittable;
 mod hittable_list;
+mod instance;
+mod mesh;
+mod plane;
 mod quad;
+mod roots;
+pub mod sdf;
 mod sphere;
 mod tetrahedron;
+mod torus;
+mod transform;
 mod triangle;
 
+pub use aabb::Aabb;
+pub use bilinear_patch::BilinearPatch;
+pub use bvh::Bvh;
+pub use cone::Cone;
+pub use convex_polyhedron::ConvexPolyhedron;
+pub use csg::{Csg, CsgOperation};
+pub use cuboid::Cuboid;
+pub use cylinder::Cylinder;
+pub use disk::Disk;
+pub use distance_field::DistanceField;
+pub use heightfield::Heightfield;
 pub use hittable::{HitRecord, Hittable};
 pub use hittable_list::HittableList;
-pub use quad::Quad;
+pub use instance::{Instance, MeshBvh};
+pub use mesh::{Mesh, TriangleMesh};
+pub use plane::Plane;
+pub use quad::{Quad, QuadError};
 pub use sphere::Sphere;
 pub use tetrahedron::Tetrahedron;
+pub use torus::Torus;
+pub use transform::Transform;
 pub use triangle::Triangle;
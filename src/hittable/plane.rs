@@ -0,0 +1,142 @@
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray, Vec3,
+};
+
+use super::frame::Frame;
+
+/// An infinite plane, going through a point.
+/// Its front face is on the side of the normal.
+/// The texture coordinates are the coordinates of the hit point in the plane (in world units, not wrapped)
+#[derive(Debug)]
+pub struct Plane<'a> {
+    point: Point3,
+    frame: Frame,
+    material: &'a dyn Material,
+}
+
+impl<'a> Plane<'a> {
+    pub fn new(point: &Point3, normal: &Vec3, material: &'a dyn Material) -> Self {
+        Self {
+            point: *point,
+            frame: Frame::from_z(normal),
+            material,
+        }
+    }
+}
+
+impl<'a> Hittable for Plane<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let normal = self.frame.z();
+        let denominator = ray.direction().dot(&normal);
+
+        // the ray is parallel to the plane
+        if denominator.abs() < 1e-12 {
+            return None;
+        }
+
+        let t = (self.point - ray.origin()).dot(&normal) / denominator;
+        if t < t_min || t > t_max {
+            return None;
+        }
+
+        let point = ray.at(t);
+        let mut hit_record = HitRecord::new(ray, &point, &normal, self.material, t);
+
+        let local = self.frame.to_local(&(point - self.point));
+        hit_record.u = local.x();
+        hit_record.v = local.y();
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        // infinite
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{material::Lambertian, Color3, Point3, Ray, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn it_should_detect_intersection() {
+        let material_black = Lambertian::new(&Color3::black());
+
+        // the ground
+        let plane = Plane::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &material_black,
+        );
+
+        // ray comming from above, far from the origin
+        let ray = Ray::new(
+            &Point3::new(1000.0, 10.0, -50.0),
+            &Vec3::new(0.0, -2.0, 0.0),
+        );
+
+        let hit_record = plane.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert_eq!(hit_record.point, Point3::new(1000.0, 0.0, -50.0));
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 1.0, 0.0));
+        assert_eq!(hit_record.t, 5.0);
+        assert!(hit_record.front_face);
+    }
+
+    #[test]
+    fn it_should_detect_intersection_from_below() {
+        let material_black = Lambertian::new(&Color3::black());
+        let plane = Plane::new(
+            &Point3::new(0.0, 1.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &material_black,
+        );
+
+        let ray = Ray::new(&Point3::new(0.0, 0.0, 0.0), &Vec3::new(0.0, 1.0, 0.0));
+        let hit_record = plane.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert!(!hit_record.front_face);
+        assert_eq!(hit_record.normal, Vec3::new(0.0, -1.0, 0.0));
+    }
+
+    #[test]
+    fn it_should_ignore_parallel_rays() {
+        let material_black = Lambertian::new(&Color3::black());
+        let plane = Plane::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &material_black,
+        );
+
+        let ray = Ray::new(&Point3::new(0.0, 1.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+
+        assert!(plane.hit(&ray, 0.000, f64::INFINITY).is_none());
+        assert!(plane.bounding_box().is_none());
+    }
+
+    #[test]
+    fn it_should_map_the_plane_coordinates() {
+        let material_black = Lambertian::new(&Color3::black());
+        let plane = Plane::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            &material_black,
+        );
+
+        let uv = |x: f64, z: f64| {
+            let ray = Ray::new(&Point3::new(x, 1.0, z), &Vec3::new(0.0, -1.0, 0.0));
+            let hit_record = plane.hit(&ray, 0.000, f64::INFINITY).unwrap();
+            (hit_record.u, hit_record.v)
+        };
+
+        // moving in the plane moves the texture coordinates by the same distance
+        let (u_0, v_0) = uv(0.0, 0.0);
+        let (u_1, v_1) = uv(3.0, 4.0);
+        assert!(((u_1 - u_0).powi(2) + (v_1 - v_0).powi(2) - 25.0).abs() < 1e-9);
+    }
+}
@@ -1,42 +1,192 @@
+use std::{error::Error, fmt};
+
 use crate::{
-    hittable::{HitRecord, Hittable},
+    hittable::{Aabb, HitRecord, Hittable},
     material::Material,
     Point3, Ray,
 };
 
 use super::{hittable::MultiFaceHittable, Triangle};
 
-/// A quad shape. Can be used to create rectangles, squares
-/// TODO: Currenlty, we don't check if all the quad vertices are all within the same plane
+/// relative tolerance of the planarity and degeneracy checks, as a fraction of the size of the quad
+const TOLERANCE: f64 = 1e-6;
+
+/// Reasons four vertices do not make a quad
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuadError {
+    /// the vertices do not enclose an area (coincident or aligned vertices)
+    Degenerate,
+    /// the vertices are not in the same plane: the two diagonals are `distance` apart.
+    /// `BilinearPatch` renders such curved quads
+    NonPlanar { distance: f64 },
+}
+
+impl fmt::Display for QuadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            QuadError::Degenerate => write!(f, "the quad has no area"),
+            QuadError::NonPlanar { distance } => write!(
+                f,
+                "the quad is not planar: its diagonals are {distance} apart"
+            ),
+        }
+    }
+}
+
+impl Error for QuadError {}
+
+/// A flat quad shape, made of two triangles. Can be used to create rectangles, squares.
+/// The vertices go around the quad, counter clockwise seen from its front face
+#[derive(Debug)]
 pub struct Quad<'a> {
     faces: Vec<Triangle<'a>>,
 }
 
 impl<'a> Hittable for Quad<'a> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         self.get_closest_hit(ray, t_min, t_max)
     }
+
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.get_any_hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.get_faces_bounding_box()
+    }
 }
 
 impl<'a> MultiFaceHittable for Quad<'a> {
-    fn get_faces(&self) -> &Vec<Triangle> {
+    fn get_faces(&self) -> &Vec<Triangle<'_>> {
         &self.faces
     }
 }
 
 impl<'a> Quad<'a> {
+    /// # errors
+    /// if the vertices are not in the same plane, or do not enclose an area
     pub fn new(
         vertex_0: &Point3,
         vertex_1: &Point3,
         vertex_2: &Point3,
         vertex_3: &Point3,
         material: &'a dyn Material,
-    ) -> Self {
+    ) -> Result<Self, QuadError> {
+        let size = [
+            *vertex_1 - *vertex_0,
+            *vertex_2 - *vertex_1,
+            *vertex_3 - *vertex_2,
+            *vertex_0 - *vertex_3,
+        ]
+        .iter()
+        .map(|edge| edge.mag())
+        .fold(0.0, f64::max);
+
+        // both triangles must have an area
+        let area_0 = (*vertex_1 - *vertex_0)
+            .cross(&(*vertex_2 - *vertex_0))
+            .mag();
+        let area_1 = (*vertex_3 - *vertex_2)
+            .cross(&(*vertex_0 - *vertex_2))
+            .mag();
+        if area_0 <= TOLERANCE * size * size || area_1 <= TOLERANCE * size * size {
+            return Err(QuadError::Degenerate);
+        }
+
+        // the diagonals of a planar quad cross: the distance between their lines is 0
+        let normal = (*vertex_2 - *vertex_0)
+            .cross(&(*vertex_3 - *vertex_1))
+            .normalize();
+        let distance = (*vertex_1 - *vertex_0).dot(&normal).abs();
+        if distance > TOLERANCE * size {
+            return Err(QuadError::NonPlanar { distance });
+        }
+
         let triangle_0 = Triangle::new(vertex_0, vertex_1, vertex_2, material);
         let triangle_1 = Triangle::new(vertex_2, vertex_3, vertex_0, material);
 
-        Quad {
+        Ok(Quad {
             faces: vec![triangle_0, triangle_1],
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{material::Lambertian, Color3, Vec3};
+
+    use super::*;
+
+    #[test]
+    fn it_should_detect_intersection() {
+        let material_black = Lambertian::new(&Color3::black());
+        let quad = Quad::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(2.0, 0.0, 0.0),
+            &Point3::new(2.0, 1.0, 0.0),
+            &Point3::new(0.0, 1.0, 0.0),
+            &material_black,
+        )
+        .unwrap();
+
+        let ray = Ray::new(&Point3::new(0.5, 0.5, 10.0), &Vec3::new(0.0, 0.0, -1.0));
+        let hit_record = quad.hit(&ray, 0.0, f64::INFINITY).unwrap();
+
+        assert_eq!(hit_record.point, Point3::new(0.5, 0.5, 0.0));
+        assert_eq!(hit_record.normal, Vec3::new(0.0, 0.0, 1.0));
+        assert!(hit_record.front_face);
+
+        assert!(quad.occluded(&ray, 0.0, f64::INFINITY));
+        assert!(!quad.occluded(&ray, 0.0, 5.0));
+
+        let ray = Ray::new(&Point3::new(2.5, 0.5, 10.0), &Vec3::new(0.0, 0.0, -1.0));
+        assert!(quad.hit(&ray, 0.0, f64::INFINITY).is_none());
+        assert!(!quad.occluded(&ray, 0.0, f64::INFINITY));
+    }
+
+    #[test]
+    fn it_should_reject_non_planar_quads() {
+        let material_black = Lambertian::new(&Color3::black());
+        let quad = Quad::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Point3::new(1.0, 0.0, 0.0),
+            &Point3::new(1.0, 1.0, 0.5),
+            &Point3::new(0.0, 1.0, 0.0),
+            &material_black,
+        );
+
+        match quad {
+            Err(QuadError::NonPlanar { distance }) => {
+                // the common normal of the diagonals is (-0.5, -0.5, 2)
+                assert!((distance - 0.5 / 4.5_f64.sqrt()).abs() < 1e-9)
+            }
+            other => panic!("unexpected result {other:?}"),
         }
     }
+
+    #[test]
+    fn it_should_reject_degenerate_quads() {
+        let material_black = Lambertian::new(&Color3::black());
+        let point = Point3::new;
+
+        // aligned vertices
+        let quad = Quad::new(
+            &point(0.0, 0.0, 0.0),
+            &point(1.0, 0.0, 0.0),
+            &point(2.0, 0.0, 0.0),
+            &point(3.0, 0.0, 0.0),
+            &material_black,
+        );
+        assert_eq!(quad.unwrap_err(), QuadError::Degenerate);
+
+        // a triangle with a repeated vertex
+        let quad = Quad::new(
+            &point(0.0, 0.0, 0.0),
+            &point(1.0, 0.0, 0.0),
+            &point(1.0, 1.0, 0.0),
+            &point(1.0, 1.0, 0.0),
+            &material_black,
+        );
+        assert_eq!(quad.unwrap_err(), QuadError::Degenerate);
+    }
 }
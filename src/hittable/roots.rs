@@ -0,0 +1,144 @@
+//! Real roots of polynomials, for the intersection of rays with implicit surfaces
+
+use std::f64::consts::PI;
+
+/// real roots of a x² + b x + c, in increasing order
+pub(crate) fn solve_quadratic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    if a.abs() < 1e-12 {
+        if b.abs() < 1e-12 {
+            return Vec::new();
+        }
+        return vec![-c / b];
+    }
+
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant < 0.0 {
+        return Vec::new();
+    }
+
+    // avoiding the cancellation of -b + sqrt(discriminant) when b² >> 4ac
+    let q = -0.5 * (b + b.signum() * discriminant.sqrt());
+    let (x_0, x_1) = if q == 0.0 { (0.0, 0.0) } else { (q / a, c / q) };
+
+    vec![x_0.min(x_1), x_0.max(x_1)]
+}
+
+/// real roots of x³ + a x² + b x + c (Cardano's method)
+fn solve_monic_cubic(a: f64, b: f64, c: f64) -> Vec<f64> {
+    let q = (a * a - 3.0 * b) / 9.0;
+    let r = (2.0 * a * a * a - 9.0 * a * b + 27.0 * c) / 54.0;
+
+    if r * r < q * q * q {
+        // three real roots
+        let theta = (r / (q * q * q).sqrt()).clamp(-1.0, 1.0).acos();
+        let scale = -2.0 * q.sqrt();
+
+        vec![
+            scale * (theta / 3.0).cos() - a / 3.0,
+            scale * ((theta + 2.0 * PI) / 3.0).cos() - a / 3.0,
+            scale * ((theta - 2.0 * PI) / 3.0).cos() - a / 3.0,
+        ]
+    } else {
+        let big_a = -r.signum() * (r.abs() + (r * r - q * q * q).sqrt()).cbrt();
+        let big_b = if big_a == 0.0 { 0.0 } else { q / big_a };
+
+        vec![big_a + big_b - a / 3.0]
+    }
+}
+
+/// real roots of a x⁴ + b x³ + c x² + d x + e (Ferrari's method), in increasing order.
+/// The roots are refined with a few Newton iterations
+pub(crate) fn solve_quartic(a: f64, b: f64, c: f64, d: f64, e: f64) -> Vec<f64> {
+    let (b, c, d, e) = (b / a, c / a, d / a, e / a);
+
+    // depressed quartic y⁴ + p y² + q y + r, with x = y - b / 4
+    let p = c - 3.0 * b * b / 8.0;
+    let q = d - b * c / 2.0 + b * b * b / 8.0;
+    let r = e - b * d / 4.0 + b * b * c / 16.0 - 3.0 * b * b * b * b / 256.0;
+
+    let mut roots = if q.abs() < 1e-12 {
+        // biquadratic: y² is a root of z² + p z + r
+        solve_quadratic(1.0, p, r)
+            .into_iter()
+            .filter(|z| *z >= 0.0)
+            .flat_map(|z| [-z.sqrt(), z.sqrt()])
+            .collect::<Vec<_>>()
+    } else {
+        // a positive root m of the resolvent cubic turns the quartic into two quadratics
+        let m = solve_monic_cubic(p, p * p / 4.0 - r, -q * q / 8.0)
+            .into_iter()
+            .fold(f64::NEG_INFINITY, f64::max);
+        if m <= 0.0 {
+            return Vec::new();
+        }
+
+        let s = (2.0 * m).sqrt();
+        let mut roots = solve_quadratic(1.0, s, p / 2.0 + m - q / (2.0 * s));
+        roots.extend(solve_quadratic(1.0, -s, p / 2.0 + m + q / (2.0 * s)));
+        roots
+    };
+
+    let polynomial = |x: f64| (((x + b) * x + c) * x + d) * x + e;
+    let derivative = |x: f64| ((4.0 * x + 3.0 * b) * x + 2.0 * c) * x + d;
+
+    for root in roots.iter_mut() {
+        *root -= b / 4.0;
+
+        for _ in 0..3 {
+            let slope = derivative(*root);
+            if slope.abs() < 1e-12 {
+                break;
+            }
+            *root -= polynomial(*root) / slope;
+        }
+    }
+
+    roots.sort_by(f64::total_cmp);
+    roots
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn assert_roots(roots: &[f64], expected: &[f64]) {
+        assert_eq!(
+            roots.len(),
+            expected.len(),
+            "{roots:?} instead of {expected:?}"
+        );
+        for (root, expected) in roots.iter().zip(expected) {
+            assert!(
+                (root - expected).abs() < 1e-9,
+                "{roots:?} instead of {expected:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_solve_quadratics() {
+        assert_roots(&solve_quadratic(1.0, -3.0, 2.0), &[1.0, 2.0]);
+        assert_roots(&solve_quadratic(2.0, 0.0, -8.0), &[-2.0, 2.0]);
+        assert_roots(&solve_quadratic(1.0, 0.0, 1.0), &[]);
+        // degenerate: linear
+        assert_roots(&solve_quadratic(0.0, 2.0, -1.0), &[0.5]);
+    }
+
+    #[test]
+    fn it_should_solve_quartics() {
+        // (x - 1)(x - 2)(x + 3)(x - 4) = x⁴ - 4x³ - 7x² + 34x - 24
+        assert_roots(
+            &solve_quartic(1.0, -4.0, -7.0, 34.0, -24.0),
+            &[-3.0, 1.0, 2.0, 4.0],
+        );
+        // (x² + 1)(x - 1)(x - 5) = x⁴ - 6x³ + 6x² - 6x + 5
+        assert_roots(&solve_quartic(1.0, -6.0, 6.0, -6.0, 5.0), &[1.0, 5.0]);
+        // biquadratic: (x² - 1)(x² - 4) = x⁴ - 5x² + 4
+        assert_roots(
+            &solve_quartic(2.0, 0.0, -10.0, 0.0, 8.0),
+            &[-2.0, -1.0, 1.0, 2.0],
+        );
+        // no real root: x⁴ + 1
+        assert_roots(&solve_quartic(1.0, 0.0, 0.0, 0.0, 1.0), &[]);
+    }
+}
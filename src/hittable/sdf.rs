@@ -0,0 +1,164 @@
+//! Signed distance functions, to build shapes for `DistanceField`.
+//! A signed distance is negative inside the shape, positive outside, and never larger than the
+//! distance to the surface. The primitives are centered on the origin: `translate` places them
+
+use crate::{Point3, Vec3};
+
+/// A signed distance function
+pub trait Sdf: Fn(&Point3) -> f64 + Send + Sync {}
+
+impl<F: Fn(&Point3) -> f64 + Send + Sync> Sdf for F {}
+
+pub fn sphere(radius: f64) -> impl Sdf {
+    move |point: &Point3| point.mag() - radius
+}
+
+/// a box, from -`half_extents` to `half_extents`
+pub fn cuboid(half_extents: Vec3) -> impl Sdf {
+    move |point: &Point3| {
+        let q = Vec3::new(
+            point.x().abs() - half_extents.x(),
+            point.y().abs() - half_extents.y(),
+            point.z().abs() - half_extents.z(),
+        );
+        let outside = Vec3::new(q.x().max(0.0), q.y().max(0.0), q.z().max(0.0));
+
+        outside.mag() + q.x().max(q.y()).max(q.z()).min(0.0)
+    }
+}
+
+/// a torus around the y axis
+pub fn torus(major_radius: f64, minor_radius: f64) -> impl Sdf {
+    move |point: &Point3| {
+        let radial = (point.x() * point.x() + point.z() * point.z()).sqrt() - major_radius;
+
+        (radial * radial + point.y() * point.y()).sqrt() - minor_radius
+    }
+}
+
+/// a segment from `start` to `end`, thickened by `radius`
+pub fn capsule(start: Point3, end: Point3, radius: f64) -> impl Sdf {
+    move |point: &Point3| {
+        let segment = end - start;
+        let h = ((*point - start).dot(&segment) / segment.mag_squared()).clamp(0.0, 1.0);
+
+        (*point - start - segment * h).mag() - radius
+    }
+}
+
+/// the shape moved by `offset`
+pub fn translate(sdf: impl Sdf, offset: Vec3) -> impl Sdf {
+    move |point: &Point3| sdf(&(*point - offset))
+}
+
+pub fn union(a: impl Sdf, b: impl Sdf) -> impl Sdf {
+    move |point: &Point3| a(point).min(b(point))
+}
+
+pub fn intersection(a: impl Sdf, b: impl Sdf) -> impl Sdf {
+    move |point: &Point3| a(point).max(b(point))
+}
+
+/// `a` with the volume of `b` removed
+pub fn difference(a: impl Sdf, b: impl Sdf) -> impl Sdf {
+    move |point: &Point3| a(point).max(-b(point))
+}
+
+/// a union blending the shapes where they are closer than `smoothness` (polynomial smooth minimum)
+pub fn smooth_union(a: impl Sdf, b: impl Sdf, smoothness: f64) -> impl Sdf {
+    move |point: &Point3| {
+        let (distance_a, distance_b) = (a(point), b(point));
+        let h = (0.5 + 0.5 * (distance_b - distance_a) / smoothness).clamp(0.0, 1.0);
+
+        distance_b + (distance_a - distance_b) * h - smoothness * h * (1.0 - h)
+    }
+}
+
+/// the shape repeated infinitely, every `period` along each axis.
+/// The shape must fit in a cell of the repetition (centered on the origin)
+pub fn repeat(sdf: impl Sdf, period: Vec3) -> impl Sdf {
+    move |point: &Point3| {
+        let wrap = |value: f64, period: f64| value - period * (value / period).round();
+
+        sdf(&Point3::new(
+            wrap(point.x(), period.x()),
+            wrap(point.y(), period.y()),
+            wrap(point.z(), period.z()),
+        ))
+    }
+}
+
+/// the shape twisted around the y axis, by `rate` radians per unit of height.
+/// A twist stretches the space: the distance is no longer exact, the Lipschitz bound of the result
+/// is about sqrt(1 + (rate * r)²), for a shape within a radius r of the axis
+pub fn twist(sdf: impl Sdf, rate: f64) -> impl Sdf {
+    move |point: &Point3| {
+        let (sin, cos) = (rate * point.y()).sin_cos();
+
+        sdf(&Point3::new(
+            cos * point.x() - sin * point.z(),
+            point.y(),
+            sin * point.x() + cos * point.z(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_measure_the_distance_to_the_primitives() {
+        let sphere = sphere(1.0);
+        assert_eq!(sphere(&Point3::new(3.0, 0.0, 0.0)), 2.0);
+        assert_eq!(sphere(&Point3::new(0.0, 0.0, 0.0)), -1.0);
+
+        let cuboid = cuboid(Vec3::new(1.0, 2.0, 3.0));
+        assert_eq!(cuboid(&Point3::new(0.0, 5.0, 0.0)), 3.0);
+        assert_eq!(cuboid(&Point3::new(0.0, 0.0, 0.0)), -1.0);
+        // closest to a corner
+        assert_eq!(cuboid(&Point3::new(4.0, 6.0, 3.0)), 5.0);
+
+        let torus = torus(2.0, 0.5);
+        assert_eq!(torus(&Point3::new(0.0, 0.0, 2.0)), -0.5);
+        assert_eq!(torus(&Point3::new(0.0, 0.0, 0.0)), 1.5);
+
+        let capsule = capsule(Point3::new(0.0, 0.0, 0.0), Point3::new(0.0, 2.0, 0.0), 0.5);
+        assert_eq!(capsule(&Point3::new(1.0, 1.0, 0.0)), 0.5);
+        assert_eq!(capsule(&Point3::new(0.0, 4.0, 0.0)), 1.5);
+    }
+
+    #[test]
+    fn it_should_combine_shapes() {
+        let a = || sphere(1.0);
+        let b = || translate(sphere(1.0), Vec3::new(1.5, 0.0, 0.0));
+        let point = Point3::new(0.75, 0.0, 0.0);
+
+        assert_eq!(union(a(), b())(&point), -0.25);
+        assert_eq!(intersection(a(), b())(&point), -0.25);
+        assert_eq!(difference(a(), b())(&point), 0.25);
+
+        // the smooth union adds matter between the shapes
+        let smooth = smooth_union(a(), b(), 0.5);
+        assert!(smooth(&point) < union(a(), b())(&point));
+        // but not far from them
+        let far = Point3::new(-5.0, 0.0, 0.0);
+        assert_eq!(smooth(&far), union(a(), b())(&far));
+    }
+
+    #[test]
+    fn it_should_repeat_and_twist_the_space() {
+        let repeated = repeat(sphere(1.0), Vec3::new(4.0, 4.0, 4.0));
+        assert_eq!(repeated(&Point3::new(8.0, -4.0, 0.0)), -1.0);
+        assert_eq!(repeated(&Point3::new(2.0, 0.0, 0.0)), 1.0);
+
+        // a box twisted by a quarter turn at y = 1
+        let twisted = twist(
+            cuboid(Vec3::new(2.0, 10.0, 0.5)),
+            std::f64::consts::PI / 2.0,
+        );
+        assert!(twisted(&Point3::new(1.5, 0.0, 0.0)) < 0.0);
+        assert!(twisted(&Point3::new(0.0, 1.0, 1.5)) < 0.0);
+        assert!(twisted(&Point3::new(1.5, 1.0, 0.0)) > 0.0);
+    }
+}
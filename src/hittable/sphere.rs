@@ -1,9 +1,12 @@
+use std::f64::consts::PI;
+
 use crate::{
-    hittable::{HitRecord, Hittable},
+    hittable::{Aabb, HitRecord, Hittable},
     material::Material,
-    Point3,
+    Point3, Ray, Vec3,
 };
 
+#[derive(Debug)]
 pub struct Sphere<'a> {
     center: Point3,
     radius: f64,
@@ -11,7 +14,51 @@ pub struct Sphere<'a> {
 }
 
 impl<'a> Hittable for Sphere<'a> {
-    fn hit(&self, ray: &crate::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (near, far) = self.roots(ray)?;
+
+        let root = if near < t_min || t_max < near {
+            if far < t_min || t_max < far {
+                return None;
+            }
+            far
+        } else {
+            near
+        };
+
+        Some(self.hit_record(ray, root))
+    }
+
+    fn all_hits(&self, ray: &Ray, t_min: f64, t_max: f64) -> Vec<HitRecord<'_>> {
+        let Some((near, far)) = self.roots(ray) else {
+            return Vec::new();
+        };
+
+        [near, far]
+            .into_iter()
+            .filter(|t| (t_min..=t_max).contains(t))
+            .map(|t| self.hit_record(ray, t))
+            .collect()
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = Vec3::new(self.radius, self.radius, self.radius);
+
+        Some(Aabb::new(&(self.center - extent), &(self.center + extent)))
+    }
+}
+
+impl<'a> Sphere<'a> {
+    pub fn new(center: &Point3, radius: f64, material: &'a dyn Material) -> Self {
+        Sphere {
+            center: *center,
+            radius,
+            material,
+        }
+    }
+
+    /// the times the line of the ray enters and leaves the sphere
+    fn roots(&self, ray: &Ray) -> Option<(f64, f64)> {
         let oc = ray.origin() - self.center;
         let a = ray.direction().mag_squared();
         let half_b = oc.dot(&ray.direction());
@@ -25,32 +72,19 @@ impl<'a> Hittable for Sphere<'a> {
         }
 
         let sqrtd = discriminant.sqrt();
-        let mut root = (-half_b - sqrtd) / a;
 
-        if root < t_min || t_max < root {
-            root = (-half_b + sqrtd) / a;
-            if root < t_min || t_max < root {
-                return None;
-            }
-        }
+        Some(((-half_b - sqrtd) / a, (-half_b + sqrtd) / a))
+    }
 
-        // creating the hit record
-        let t = root;
+    fn hit_record(&self, ray: &Ray, t: f64) -> HitRecord<'_> {
         let point = ray.at(t);
         let outward_normal = (point - self.center) / self.radius;
-        let hit_record = HitRecord::new(ray, &point, &outward_normal, self.material, t);
+        let mut hit_record = HitRecord::new(ray, &point, &outward_normal, self.material, t);
+        // u is the angle around the y axis (from -x), v the angle from the bottom pole
+        hit_record.u = ((-outward_normal.z()).atan2(outward_normal.x()) + PI) / (2.0 * PI);
+        hit_record.v = (-outward_normal.y()).clamp(-1.0, 1.0).acos() / PI;
 
-        Some(hit_record)
-    }
-}
-
-impl<'a> Sphere<'a> {
-    pub fn new(center: &Point3, radius: f64, material: &'a dyn Material) -> Self {
-        Sphere {
-            center: *center,
-            radius,
-            material,
-        }
+        hit_record
     }
 }
 
@@ -77,8 +111,12 @@ mod test {
             front_face: true,
             material: &material_black,
             normal: Vec3::new(-1.0, 0.0, 0.0),
+            object_id: 0,
             point: Point3::new(-1.0, 0.0, 0.0),
             t: 99.0,
+            u: 0.0,
+            v: 0.0,
+            vertex_color: None,
         };
 
         assert_eq!(hit_record.point, expected_record.point);
@@ -104,8 +142,12 @@ mod test {
             front_face: false, //? notice that the inner colision is detected
             material: &material_black,
             normal: Vec3::new(-1.0, 0.0, 0.0), //? notice the normal oriented to the left
+            object_id: 0,
             point: Point3::new(1.0, 0.0, 0.0),
             t: 1.0,
+            u: 0.0,
+            v: 0.0,
+            vertex_color: None,
         };
 
         assert_eq!(hit_record.point, expected_record.point);
@@ -129,4 +171,31 @@ mod test {
 
         assert!(hit_record.is_none());
     }
+
+    #[test]
+    fn it_should_map_texture_coordinates() {
+        let material_black = Lambertian::new(&Color3::black());
+        let sphere = Sphere::new(&Vec3::new(0.0, 0.0, 0.0), 1.0, &material_black);
+
+        // on the equator, a quarter turn from -x
+        let ray = Ray::new(&Vec3::new(0.0, 0.0, 100.0), &Vec3::new(0.0, 0.0, -1.0));
+        let hit_record = sphere.hit(&ray, 0.000, f64::INFINITY).unwrap();
+        assert!((hit_record.u - 0.25).abs() < 1e-9);
+        assert!((hit_record.v - 0.5).abs() < 1e-9);
+
+        // the top pole
+        let ray = Ray::new(&Vec3::new(0.0, 100.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let hit_record = sphere.hit(&ray, 0.000, f64::INFINITY).unwrap();
+        assert!((hit_record.v - 1.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_be_bounded() {
+        let material_black = Lambertian::new(&Color3::black());
+        let sphere = Sphere::new(&Vec3::new(1.0, 2.0, 3.0), 0.5, &material_black);
+        let aabb = sphere.bounding_box().unwrap();
+
+        assert_eq!(aabb.min(), Point3::new(0.5, 1.5, 2.5));
+        assert_eq!(aabb.max(), Point3::new(1.5, 2.5, 3.5));
+    }
 }
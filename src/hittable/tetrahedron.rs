@@ -1,23 +1,34 @@
 use crate::{
-    hittable::{HitRecord, Hittable},
+    hittable::{Aabb, HitRecord, Hittable},
     material::Material,
     Point3, Ray,
 };
 
-use super::{hittable::MultiFaceHittable, Triangle};
+use super::{convex_polyhedron::orient_outward, hittable::MultiFaceHittable, Triangle};
 
+/// A tetrahedron, its four faces pointing outward.
+/// The vertices can be given in any order
+#[derive(Debug)]
 pub struct Tetrahedron<'a> {
     faces: Vec<Triangle<'a>>,
 }
 
 impl<'a> Hittable for Tetrahedron<'a> {
-    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
         self.get_closest_hit(ray, t_min, t_max)
     }
+
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        self.get_any_hit(ray, t_min, t_max)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        self.get_faces_bounding_box()
+    }
 }
 
 impl<'a> MultiFaceHittable for Tetrahedron<'a> {
-    fn get_faces(&self) -> &Vec<Triangle> {
+    fn get_faces(&self) -> &Vec<Triangle<'_>> {
         &self.faces
     }
 }
@@ -30,13 +41,139 @@ impl<'a> Tetrahedron<'a> {
         vertex_3: &Point3,
         material: &'a dyn Material,
     ) -> Self {
-        let face_0 = Triangle::new(vertex_0, vertex_1, vertex_2, material);
-        let face_1 = Triangle::new(vertex_0, vertex_1, vertex_3, material);
-        let face_2 = Triangle::new(vertex_1, vertex_2, vertex_3, material);
-        let face_3 = Triangle::new(vertex_0, vertex_3, vertex_2, material);
+        // each face is wound counter clockwise seen from outside, whatever the order of the vertices
+        let center = (*vertex_0 + *vertex_1 + *vertex_2 + *vertex_3) / 4.0;
+        let faces = [
+            [*vertex_0, *vertex_1, *vertex_2],
+            [*vertex_0, *vertex_1, *vertex_3],
+            [*vertex_1, *vertex_2, *vertex_3],
+            [*vertex_0, *vertex_3, *vertex_2],
+        ]
+        .into_iter()
+        .map(|face| {
+            let [vertex_0, vertex_1, vertex_2] = orient_outward(face, &center);
+            Triangle::new(&vertex_0, &vertex_1, &vertex_2, material)
+        })
+        .collect();
+
+        Tetrahedron { faces }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, SeedableRng};
+
+    use crate::{
+        material::{Dielectric, Material},
+        Vec3,
+    };
+
+    use super::*;
+
+    /// a regular tetrahedron centered on the origin
+    const VERTICES: [(f64, f64, f64); 4] = [
+        (1.0, 1.0, 1.0),
+        (1.0, -1.0, -1.0),
+        (-1.0, 1.0, -1.0),
+        (-1.0, -1.0, 1.0),
+    ];
+
+    /// points on each face, a bit off its center, and the outward normal of the face
+    fn points_on_faces() -> Vec<(Point3, Vec3)> {
+        let vertices = VERTICES.map(|(x, y, z)| Point3::new(x, y, z));
+
+        (0..4)
+            .map(|opposite| {
+                // the face opposite to a vertex faces away from it
+                let normal = -vertices[opposite].normalize();
+                let face: Vec<Point3> = (0..4)
+                    .filter(|&index| index != opposite)
+                    .map(|index| vertices[index])
+                    .collect();
+                let center = (face[0] + face[1] + face[2]) / 3.0;
+
+                (center + (face[0] - center) * 0.2, normal)
+            })
+            .collect()
+    }
+
+    /// scatter a ray on glass many times, and check the refracted rays follow Snell's law
+    /// going from a medium of index `index_in` to one of index `index_out`
+    fn assert_snell(
+        material: &Dielectric,
+        ray: &Ray,
+        hit_record: &HitRecord,
+        index_in: f64,
+        index_out: f64,
+    ) {
+        let mut rng = StdRng::seed_from_u64(5);
+        let unit_in = ray.direction().normalize();
+        let sin_in = unit_in.cross(&hit_record.normal).mag();
+
+        let mut nb_refracted = 0;
+        for _ in 0..50 {
+            let (scattered, _, _) = material.scatter(ray, hit_record, &mut rng);
+            let unit_out = scattered.direction().normalize();
+
+            // the normal faces the incoming ray: refracted rays go against it
+            if unit_out.dot(&hit_record.normal) < 0.0 {
+                let sin_out = unit_out.cross(&hit_record.normal).mag();
+                assert!((index_in * sin_in - index_out * sin_out).abs() < 1e-9);
+                nb_refracted += 1;
+            }
+        }
+
+        assert!(nb_refracted > 0);
+    }
+
+    #[test]
+    fn it_should_point_every_face_outward() {
+        let glass = Dielectric::new(1.5);
+
+        // whatever the order of the vertices
+        for permutation in [[0, 1, 2, 3], [3, 2, 1, 0], [1, 3, 0, 2]] {
+            let [vertex_0, vertex_1, vertex_2, vertex_3] = permutation.map(|index| {
+                let (x, y, z) = VERTICES[index];
+                Point3::new(x, y, z)
+            });
+            let tetrahedron = Tetrahedron::new(&vertex_0, &vertex_1, &vertex_2, &vertex_3, &glass);
+
+            for (point, normal) in points_on_faces() {
+                // from outside
+                let ray = Ray::new(&(point + normal * 5.0), &-normal);
+                let hit_record = tetrahedron.hit(&ray, 0.0, f64::INFINITY).unwrap();
+                assert!(hit_record.front_face);
+                assert!((hit_record.normal - normal).mag() < 1e-9);
+
+                // from inside
+                let ray = Ray::new(&Point3::new(0.0, 0.0, 0.0), &point);
+                let hit_record = tetrahedron.hit(&ray, 0.0, f64::INFINITY).unwrap();
+                assert!(!hit_record.front_face);
+                assert!((hit_record.normal + normal).mag() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_refract_through_glass() {
+        let glass = Dielectric::new(1.5);
+        let [vertex_0, vertex_1, vertex_2, vertex_3] =
+            VERTICES.map(|(x, y, z)| Point3::new(x, y, z));
+        let tetrahedron = Tetrahedron::new(&vertex_0, &vertex_1, &vertex_2, &vertex_3, &glass);
+
+        for (point, normal) in points_on_faces() {
+            // entering the glass with an angle
+            let tangent = normal.cross(&Vec3::new(0.3, 0.5, 0.8)).normalize();
+            let origin = point + (normal + tangent * 0.5) * 5.0;
+            let ray = Ray::new(&origin, &(point - origin));
+            let hit_record = tetrahedron.hit(&ray, 0.0, f64::INFINITY).unwrap();
+            assert_snell(&glass, &ray, &hit_record, 1.0, 1.5);
 
-        Tetrahedron {
-            faces: vec![face_0, face_1, face_2, face_3],
+            // leaving the glass, from its center
+            let ray = Ray::new(&Point3::new(0.0, 0.0, 0.0), &point);
+            let hit_record = tetrahedron.hit(&ray, 0.0, f64::INFINITY).unwrap();
+            assert_snell(&glass, &ray, &hit_record, 1.5, 1.0);
         }
     }
 }
@@ -0,0 +1,184 @@
+use std::f64::consts::PI;
+
+use crate::{
+    hittable::{Aabb, HitRecord, Hittable},
+    material::Material,
+    Point3, Ray, Vec3,
+};
+
+use super::{frame::Frame, roots::solve_quartic};
+
+/// A torus: a tube of radius `minor_radius` around a circle of radius `major_radius`.
+/// Texture coordinates: u is the angle around the axis, v the angle around the tube (both from 0 to 1)
+#[derive(Debug)]
+pub struct Torus<'a> {
+    center: Point3,
+    major_radius: f64,
+    minor_radius: f64,
+    /// the z axis is the axis of the torus
+    frame: Frame,
+    material: &'a dyn Material,
+}
+
+impl<'a> Torus<'a> {
+    pub fn new(
+        center: &Point3,
+        axis: &Vec3,
+        major_radius: f64,
+        minor_radius: f64,
+        material: &'a dyn Material,
+    ) -> Self {
+        Self {
+            center: *center,
+            major_radius,
+            minor_radius,
+            frame: Frame::from_z(axis),
+            material,
+        }
+    }
+}
+
+impl<'a> Hittable for Torus<'a> {
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let length = ray.direction().mag();
+        let direction = self.frame.to_local(&ray.direction()) / length;
+        let origin = self.frame.to_local(&(ray.origin() - self.center));
+
+        // the quartic is solved from the point of the ray closest to the center:
+        // the coefficients stay small for rays coming from far away
+        let shift = -origin.dot(&direction);
+        let origin = origin + direction * shift;
+
+        // (|p|² + R² - r²)² = 4 R² (x² + y²), with p = origin + s direction
+        let r2 = self.major_radius * self.major_radius;
+        let n = origin.dot(&direction);
+        let q = origin.mag_squared() + r2 - self.minor_radius * self.minor_radius;
+        let roots = solve_quartic(
+            1.0,
+            4.0 * n,
+            4.0 * n * n + 2.0 * q
+                - 4.0 * r2 * (direction.x() * direction.x() + direction.y() * direction.y()),
+            4.0 * n * q - 8.0 * r2 * (origin.x() * direction.x() + origin.y() * direction.y()),
+            q * q - 4.0 * r2 * (origin.x() * origin.x() + origin.y() * origin.y()),
+        );
+
+        // back to the time of the ray
+        let (s, t) = roots
+            .into_iter()
+            .map(|s| (s, (s + shift) / length))
+            .find(|(_, t)| *t >= t_min && *t <= t_max)?;
+
+        let local_point = origin + direction * s;
+        // the normal goes from the center of the tube to the point
+        let radial = Vec3::new(local_point.x(), local_point.y(), 0.0);
+        let tube_center = if radial.is_near_zero() {
+            radial
+        } else {
+            radial.normalize() * self.major_radius
+        };
+        let local_normal = (local_point - tube_center).normalize();
+
+        let point = ray.at(t);
+        let outward_normal = self.frame.to_world(&local_normal);
+
+        let mut hit_record = HitRecord::new(ray, &point, &outward_normal, self.material, t);
+        hit_record.u = (local_point.y().atan2(local_point.x()) + PI) / (2.0 * PI);
+        hit_record.v = (local_point.z().atan2(radial.mag() - self.major_radius) + PI) / (2.0 * PI);
+
+        Some(hit_record)
+    }
+
+    fn bounding_box(&self) -> Option<Aabb> {
+        let extent = self.frame.circle_extent(self.major_radius)
+            + Vec3::new(self.minor_radius, self.minor_radius, self.minor_radius);
+
+        Some(Aabb::new(&(self.center - extent), &(self.center + extent)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::FRAC_PI_4;
+
+    use crate::{material::Lambertian, Color3, Point3, Ray, Vec3};
+
+    use super::*;
+
+    /// a torus lying on the xz plane, of radii 2 and 0.5
+    fn torus(material: &dyn Material) -> Torus<'_> {
+        Torus::new(
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            2.0,
+            0.5,
+            material,
+        )
+    }
+
+    #[test]
+    fn it_should_detect_intersection() {
+        let material_black = Lambertian::new(&Color3::black());
+        let torus = torus(&material_black);
+
+        // ray comming from the left, through the hole
+        let ray = Ray::new(&Point3::new(-100.0, 0.0, 0.0), &Vec3::new(2.0, 0.0, 0.0));
+        let hit_record = torus.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point - Point3::new(-2.5, 0.0, 0.0)).mag() < 1e-9);
+        assert!((hit_record.normal - Vec3::new(-1.0, 0.0, 0.0)).mag() < 1e-9);
+        assert!((hit_record.t - 97.5 / 2.0).abs() < 1e-9);
+        assert!(hit_record.front_face);
+
+        // the next hit is the inner side of the tube
+        let hit_record = torus.hit(&ray, 48.8, f64::INFINITY).unwrap();
+        assert!((hit_record.point - Point3::new(-1.5, 0.0, 0.0)).mag() < 1e-9);
+        assert!(!hit_record.front_face);
+    }
+
+    #[test]
+    fn it_should_detect_intersection_from_above() {
+        let material_black = Lambertian::new(&Color3::black());
+        let torus = torus(&material_black);
+
+        let ray = Ray::new(&Point3::new(0.0, 10.0, 2.0), &Vec3::new(0.0, -1.0, 0.0));
+        let hit_record = torus.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point - Point3::new(0.0, 0.5, 2.0)).mag() < 1e-9);
+        assert!((hit_record.normal - Vec3::new(0.0, 1.0, 0.0)).mag() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_map_the_angle_around_the_tube() {
+        let material_black = Lambertian::new(&Color3::black());
+        let torus = torus(&material_black);
+
+        // on the outer side of the tube, 45° above its equator
+        let x = 2.0 + 0.5 * FRAC_PI_4.cos();
+        let ray = Ray::new(&Point3::new(x, 10.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        let hit_record = torus.hit(&ray, 0.000, f64::INFINITY).unwrap();
+
+        assert!((hit_record.point.y() - 0.5 * FRAC_PI_4.sin()).abs() < 1e-9);
+        assert!((hit_record.v - (FRAC_PI_4 + PI) / (2.0 * PI)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_ignore_rays_through_the_hole() {
+        let material_black = Lambertian::new(&Color3::black());
+        let torus = torus(&material_black);
+
+        let ray = Ray::new(&Point3::new(0.0, 10.0, 0.0), &Vec3::new(0.0, -1.0, 0.0));
+        assert!(torus.hit(&ray, 0.000, f64::INFINITY).is_none());
+
+        let ray = Ray::new(&Point3::new(-100.0, 0.6, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        assert!(torus.hit(&ray, 0.000, f64::INFINITY).is_none());
+    }
+
+    #[test]
+    fn it_should_be_bounded() {
+        let material_black = Lambertian::new(&Color3::black());
+        let aabb = torus(&material_black).bounding_box().unwrap();
+
+        assert!((aabb.min() - Point3::new(-2.5, -0.5, -2.5)).mag() < 1e-9);
+        assert!((aabb.max() - Point3::new(2.5, 0.5, 2.5)).mag() < 1e-9);
+    }
+}
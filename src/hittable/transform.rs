@@ -0,0 +1,212 @@
+use crate::{Point3, Ray, Vec3};
+
+/// An affine transform (rotation, scaling and translation), with its inverse.
+/// Shapes defined in a local space are placed in the world with it
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    /// rows of the linear part
+    matrix: [Vec3; 3],
+    translation: Vec3,
+    /// rows of the inverse of the linear part
+    inverse: [Vec3; 3],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl Transform {
+    pub fn identity() -> Self {
+        Self::from_matrix(
+            [
+                Vec3::new(1.0, 0.0, 0.0),
+                Vec3::new(0.0, 1.0, 0.0),
+                Vec3::new(0.0, 0.0, 1.0),
+            ],
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    pub fn translation(offset: &Vec3) -> Self {
+        Self {
+            translation: *offset,
+            ..Self::identity()
+        }
+    }
+
+    /// a scaling along each axis
+    /// # panics
+    /// if a factor is 0
+    pub fn scaling(factors: &Vec3) -> Self {
+        Self::from_matrix(
+            [
+                Vec3::new(factors.x(), 0.0, 0.0),
+                Vec3::new(0.0, factors.y(), 0.0),
+                Vec3::new(0.0, 0.0, factors.z()),
+            ],
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    /// a rotation of `angle` radians around `axis` (counter clockwise, looking from the tip of the axis)
+    pub fn rotation(axis: &Vec3, angle: f64) -> Self {
+        let axis = axis.normalize();
+        let (x, y, z) = (axis.x(), axis.y(), axis.z());
+        let (sin, cos) = angle.sin_cos();
+        let one_minus_cos = 1.0 - cos;
+
+        // Rodrigues' rotation formula
+        Self::from_matrix(
+            [
+                Vec3::new(
+                    cos + x * x * one_minus_cos,
+                    x * y * one_minus_cos - z * sin,
+                    x * z * one_minus_cos + y * sin,
+                ),
+                Vec3::new(
+                    y * x * one_minus_cos + z * sin,
+                    cos + y * y * one_minus_cos,
+                    y * z * one_minus_cos - x * sin,
+                ),
+                Vec3::new(
+                    z * x * one_minus_cos - y * sin,
+                    z * y * one_minus_cos + x * sin,
+                    cos + z * z * one_minus_cos,
+                ),
+            ],
+            Vec3::new(0.0, 0.0, 0.0),
+        )
+    }
+
+    /// # panics
+    /// if the linear part can not be inverted
+    fn from_matrix(matrix: [Vec3; 3], translation: Vec3) -> Self {
+        // the inverse is the transposed matrix of the cofactors over the determinant
+        let [row_0, row_1, row_2] = matrix;
+        let determinant = row_0.dot(&row_1.cross(&row_2));
+        assert!(
+            determinant.abs() > 1e-12,
+            "the transform can not be inverted"
+        );
+
+        let columns = [
+            row_1.cross(&row_2) / determinant,
+            row_2.cross(&row_0) / determinant,
+            row_0.cross(&row_1) / determinant,
+        ];
+
+        Self {
+            matrix,
+            translation,
+            inverse: transpose(&columns),
+        }
+    }
+
+    /// the transform applying `self`, then `other`
+    pub fn then(&self, other: &Self) -> Self {
+        let columns = transpose(&self.matrix).map(|column| other.apply_vector(&column));
+
+        Self::from_matrix(transpose(&columns), other.apply_point(&self.translation))
+    }
+
+    /// the transform undoing this one
+    pub fn inverse(&self) -> Self {
+        Self {
+            matrix: self.inverse,
+            translation: -multiply(&self.inverse, &self.translation),
+            inverse: self.matrix,
+        }
+    }
+
+    pub fn apply_point(&self, point: &Point3) -> Point3 {
+        multiply(&self.matrix, point) + self.translation
+    }
+
+    pub fn apply_vector(&self, vector: &Vec3) -> Vec3 {
+        multiply(&self.matrix, vector)
+    }
+
+    /// normals are transformed by the transposed inverse, to stay orthogonal to the surface.
+    /// The result is not normalized
+    pub fn apply_normal(&self, normal: &Vec3) -> Vec3 {
+        multiply(&transpose(&self.inverse), normal)
+    }
+
+    /// the ray in the space before the transform.
+    /// The direction is not normalized: the times of the hits are the same in both spaces
+    pub fn inverse_ray(&self, ray: &Ray) -> Ray {
+        Ray::new(
+            &multiply(&self.inverse, &(ray.origin() - self.translation)),
+            &multiply(&self.inverse, &ray.direction()),
+        )
+    }
+}
+
+fn multiply(rows: &[Vec3; 3], vector: &Vec3) -> Vec3 {
+    Vec3::new(
+        rows[0].dot(vector),
+        rows[1].dot(vector),
+        rows[2].dot(vector),
+    )
+}
+
+fn transpose(rows: &[Vec3; 3]) -> [Vec3; 3] {
+    [
+        Vec3::new(rows[0].x(), rows[1].x(), rows[2].x()),
+        Vec3::new(rows[0].y(), rows[1].y(), rows[2].y()),
+        Vec3::new(rows[0].z(), rows[1].z(), rows[2].z()),
+    ]
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    fn assert_near(a: &Vec3, b: &Vec3) {
+        assert!((*a - *b).mag() < 1e-9, "{a:?} != {b:?}");
+    }
+
+    #[test]
+    fn it_should_compose_transforms() {
+        let transform = Transform::scaling(&Vec3::new(2.0, 1.0, 1.0))
+            .then(&Transform::rotation(&Vec3::new(0.0, 0.0, 1.0), PI / 2.0))
+            .then(&Transform::translation(&Vec3::new(0.0, 0.0, 5.0)));
+
+        // scaled to (2, 0, 0), turned to (0, 2, 0), moved to (0, 2, 5)
+        let point = transform.apply_point(&Point3::new(1.0, 0.0, 0.0));
+        assert_near(&point, &Point3::new(0.0, 2.0, 5.0));
+
+        // vectors are not translated
+        let vector = transform.apply_vector(&Vec3::new(1.0, 0.0, 0.0));
+        assert_near(&vector, &Vec3::new(0.0, 2.0, 0.0));
+
+        let back = transform.inverse().apply_point(&point);
+        assert_near(&back, &Point3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn it_should_keep_normals_orthogonal() {
+        // a slope of 45° squashed along y
+        let transform = Transform::scaling(&Vec3::new(1.0, 0.5, 1.0));
+        let tangent = transform.apply_vector(&Vec3::new(1.0, 1.0, 0.0));
+        let normal = transform.apply_normal(&Vec3::new(-1.0, 1.0, 0.0));
+
+        assert!(tangent.dot(&normal).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_bring_rays_back_to_the_local_space() {
+        let transform = Transform::translation(&Vec3::new(1.0, 2.0, 3.0))
+            .then(&Transform::scaling(&Vec3::new(2.0, 2.0, 2.0)));
+        let ray = Ray::new(&Point3::new(0.0, 0.0, 0.0), &Vec3::new(1.0, 0.0, 0.0));
+        let local_ray = transform.inverse_ray(&ray);
+
+        // the same time gives the same point in both spaces
+        let local_point = local_ray.at(3.0);
+        assert_near(&transform.apply_point(&local_point), &ray.at(3.0));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::{
-    hittable::{HitRecord, Hittable},
+    hittable::{Aabb, HitRecord, Hittable},
     material::Material,
-    Point3,
+    Point3, Ray,
 };
 
+#[derive(Debug)]
 pub struct Triangle<'a> {
     vertex_0: Point3,
     vertex_1: Point3,
@@ -12,61 +13,97 @@ pub struct Triangle<'a> {
 }
 
 impl<'a> Hittable for Triangle<'a> {
-    fn hit(&self, ray: &crate::Ray, t_min: f64, t_max: f64) -> Option<HitRecord> {
-        // Möller–Trumbore algorithm
-        let vertex_0 = self.vertex_0;
-        let vertex_1 = self.vertex_1;
-        let vertex_2 = self.vertex_2;
+    fn hit(&self, ray: &Ray, t_min: f64, t_max: f64) -> Option<HitRecord<'_>> {
+        let (t, u, v) = intersect(
+            ray,
+            &self.vertex_0,
+            &self.vertex_1,
+            &self.vertex_2,
+            t_min,
+            t_max,
+        )?;
 
-        // edges of the triangle
-        let edge_1 = vertex_1 - vertex_0;
-        let edge_2 = vertex_2 - vertex_0;
+        let point = ray.at(t);
 
-        let h = ray.direction().cross(&edge_2);
-        let a = edge_1.dot(&h);
+        // the cross product gives a normal vector, we just need to normalize it
+        let edge_1 = self.vertex_1 - self.vertex_0;
+        let edge_2 = self.vertex_2 - self.vertex_0;
+        let outward_normal = edge_1.cross(&edge_2).normalize();
 
-        const EPSILON: f64 = 0.0000001;
+        let mut hit_record = HitRecord::new(ray, &point, &outward_normal, self.material, t);
+        // without texture coordinates, the barycentric coordinates are used
+        hit_record.u = u;
+        hit_record.v = v;
 
-        if a.abs() < EPSILON {
-            return None; // ray parallel to triangle
-        }
+        Some(hit_record)
+    }
 
-        let f = 1.0 / a;
-        let s = ray.origin() - vertex_0;
-        let u = f * s.dot(&h);
+    fn occluded(&self, ray: &Ray, t_min: f64, t_max: f64) -> bool {
+        intersect(
+            ray,
+            &self.vertex_0,
+            &self.vertex_1,
+            &self.vertex_2,
+            t_min,
+            t_max,
+        )
+        .is_some()
+    }
 
-        if u < 0.0 || u > 1.0 {
-            return None; // no solution
-        }
+    fn bounding_box(&self) -> Option<Aabb> {
+        Aabb::from_points([&self.vertex_0, &self.vertex_1, &self.vertex_2])
+    }
+}
 
-        let q = s.cross(&edge_1);
-        let v = f * ray.direction().dot(&q);
-        if v < 0.0 || u + v > 1.0 {
-            return None;
-        }
+/// Möller–Trumbore algorithm
+/// # returns
+/// the time of the hit and the barycentric coordinates (u, v) of the hit point:
+/// point = (1 - u - v) * vertex_0 + u * vertex_1 + v * vertex_2
+pub(crate) fn intersect(
+    ray: &Ray,
+    vertex_0: &Point3,
+    vertex_1: &Point3,
+    vertex_2: &Point3,
+    t_min: f64,
+    t_max: f64,
+) -> Option<(f64, f64, f64)> {
+    // edges of the triangle
+    let edge_1 = *vertex_1 - *vertex_0;
+    let edge_2 = *vertex_2 - *vertex_0;
 
-        // We know now that an intersection happened
-        // computing t to know where it happened
-        let t = f * edge_2.dot(&q);
+    let h = ray.direction().cross(&edge_2);
+    let a = edge_1.dot(&h);
 
-        // if the intersection is not in the expected range, abort the ray
-        if t < t_min || t > t_max {
-            return None;
-        }
+    const EPSILON: f64 = 0.0000001;
 
-        let point = ray.at(t);
+    if a.abs() < EPSILON {
+        return None; // ray parallel to triangle
+    }
 
-        // the cross product gives a normal vector, we just need to normalize it
-        let outward_normal = edge_1.cross(&edge_2).normalize();
+    let f = 1.0 / a;
+    let s = ray.origin() - *vertex_0;
+    let u = f * s.dot(&h);
 
-        Some(HitRecord::new(
-            ray,
-            &point,
-            &outward_normal,
-            self.material,
-            t,
-        ))
+    if !(0.0..=1.0).contains(&u) {
+        return None; // no solution
     }
+
+    let q = s.cross(&edge_1);
+    let v = f * ray.direction().dot(&q);
+    if v < 0.0 || u + v > 1.0 {
+        return None;
+    }
+
+    // We know now that an intersection happened
+    // computing t to know where it happened
+    let t = f * edge_2.dot(&q);
+
+    // if the intersection is not in the expected range, abort the ray
+    if t < t_min || t > t_max {
+        return None;
+    }
+
+    Some((t, u, v))
 }
 
 impl<'a> Triangle<'a> {
@@ -0,0 +1,68 @@
+use crate::{image_io::FloatImage, Color3};
+
+/// A per pixel error between two images, line after line from the top.
+/// Errors are expected between 0 and 1
+#[derive(Debug, Clone, PartialEq)]
+pub struct ErrorMap {
+    width: u32,
+    height: u32,
+    errors: Vec<f64>,
+}
+
+impl ErrorMap {
+    pub fn new(width: u32, height: u32, errors: Vec<f64>) -> Self {
+        assert_eq!(errors.len(), width as usize * height as usize);
+
+        Self {
+            width,
+            height,
+            errors,
+        }
+    }
+
+    pub fn errors(&self) -> &[f64] {
+        &self.errors
+    }
+
+    pub fn mean(&self) -> f64 {
+        if self.errors.is_empty() {
+            return 0.0;
+        }
+
+        self.errors.iter().sum::<f64>() / self.errors.len() as f64
+    }
+
+    pub fn max(&self) -> f64 {
+        self.errors.iter().copied().fold(0.0, f64::max)
+    }
+
+    /// a false color image of the errors: black without error, then purple, orange and light yellow
+    /// (a magma like color map)
+    pub fn to_false_color(&self) -> FloatImage {
+        let pixels = self.errors.iter().map(|error| magma(*error)).collect();
+
+        FloatImage::new(self.width, self.height, pixels)
+    }
+}
+
+/// approximation of the magma color map, as linear colors
+fn magma(value: f64) -> Color3 {
+    // displayed colors, from 0 to 1 every quarter
+    const KEYS: [(f64, f64, f64); 5] = [
+        (0.001, 0.000, 0.014),
+        (0.316, 0.071, 0.485),
+        (0.716, 0.215, 0.475),
+        (0.986, 0.535, 0.382),
+        (0.987, 0.991, 0.750),
+    ];
+
+    let position = value.clamp(0.0, 1.0) * (KEYS.len() - 1) as f64;
+    let index = (position.floor() as usize).min(KEYS.len() - 2);
+    let t = position - index as f64;
+
+    let (from, to) = (KEYS[index], KEYS[index + 1]);
+    let displayed = Color3::new(from.0, from.1, from.2).lerp(&Color3::new(to.0, to.1, to.2), t);
+
+    // images store linear colors: removing the sRGB encoding used to save images
+    Color3::from_srgb(&displayed)
+}
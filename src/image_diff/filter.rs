@@ -0,0 +1,93 @@
+/// A single channel image of floats, line after line from the top
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Channel {
+    pub width: usize,
+    pub height: usize,
+    pub values: Vec<f64>,
+}
+
+impl Channel {
+    pub fn new(width: usize, height: usize, values: Vec<f64>) -> Self {
+        debug_assert_eq!(values.len(), width * height);
+
+        Self {
+            width,
+            height,
+            values,
+        }
+    }
+
+    fn at(&self, column: isize, line: isize) -> f64 {
+        // clamping to the border of the image
+        let column = column.clamp(0, self.width as isize - 1) as usize;
+        let line = line.clamp(0, self.height as isize - 1) as usize;
+
+        self.values[line * self.width + column]
+    }
+
+    /// convolve with the separable kernel kernel_x(x) * kernel_y(y).
+    /// Kernels have an odd length and are centered
+    pub fn convolve(&self, kernel_x: &[f64], kernel_y: &[f64]) -> Self {
+        let half_x = (kernel_x.len() / 2) as isize;
+        let half_y = (kernel_y.len() / 2) as isize;
+
+        let horizontal = Channel::new(
+            self.width,
+            self.height,
+            (0..self.height as isize)
+                .flat_map(|line| {
+                    (0..self.width as isize).map(move |column| {
+                        (0..kernel_x.len() as isize)
+                            .map(|k| kernel_x[k as usize] * self.at(column + k - half_x, line))
+                            .sum()
+                    })
+                })
+                .collect(),
+        );
+
+        Channel::new(
+            self.width,
+            self.height,
+            (0..self.height as isize)
+                .flat_map(|line| {
+                    let horizontal = &horizontal;
+                    (0..self.width as isize).map(move |column| {
+                        (0..kernel_y.len() as isize)
+                            .map(|k| {
+                                kernel_y[k as usize] * horizontal.at(column, line + k - half_y)
+                            })
+                            .sum()
+                    })
+                })
+                .collect(),
+        )
+    }
+
+    /// combine two channels of the same size value by value
+    pub fn zip_with(&self, other: &Self, f: impl Fn(f64, f64) -> f64) -> Self {
+        Channel::new(
+            self.width,
+            self.height,
+            self.values
+                .iter()
+                .zip(&other.values)
+                .map(|(a, b)| f(*a, *b))
+                .collect(),
+        )
+    }
+}
+
+/// a gaussian of standard deviation sigma (in pixels), normalized to a sum of 1
+pub(crate) fn gaussian_kernel(sigma: f64, radius: usize) -> Vec<f64> {
+    normalize(
+        (-(radius as isize)..=radius as isize)
+            .map(|x| (-((x * x) as f64) / (2.0 * sigma * sigma)).exp())
+            .collect(),
+    )
+}
+
+/// scale a kernel to a sum of 1
+fn normalize(kernel: Vec<f64>) -> Vec<f64> {
+    let sum: f64 = kernel.iter().sum();
+    kernel.into_iter().map(|value| value / sum).collect()
+}
@@ -0,0 +1,276 @@
+use std::f64::consts::PI;
+
+use super::{
+    filter::{gaussian_kernel, Channel},
+    ErrorMap,
+};
+use crate::{image_io::FloatImage, Color3, Vec3};
+
+/// pixels per visual degree of a 0.7 meter wide 4k monitor, seen from 0.7 meter
+pub const DEFAULT_PIXELS_PER_DEGREE: f64 = 67.0;
+
+/// A perceptual difference between two images, in the style of FLIP (Andersson et al. 2020).
+/// Both images are first blurred like the human eye would (contrast sensitivity),
+/// then compared with a perceptual color distance. The color error is amplified
+/// where edges or points differ, as the eye is drawn to them.
+/// Errors are between 0 (no visible difference) and 1, per pixel.
+/// `pixels_per_degree` depends on the display and the viewing distance (see `DEFAULT_PIXELS_PER_DEGREE`)
+/// # panics
+/// if the images do not have the same size
+pub fn flip(reference: &FloatImage, test: &FloatImage, pixels_per_degree: f64) -> ErrorMap {
+    assert!(reference.same_size(test), "images must have the same size");
+
+    let color_errors = color_errors(reference, test, pixels_per_degree);
+    let feature_errors = feature_errors(reference, test, pixels_per_degree);
+
+    let errors = color_errors
+        .iter()
+        .zip(&feature_errors)
+        .map(|(color, feature)| color.powf(1.0 - feature))
+        .collect();
+
+    ErrorMap::new(reference.width(), reference.height(), errors)
+}
+
+// Color pipeline ---------------------------
+
+/// contrast sensitivity parameters (a1, b1, a2, b2) of the achromatic, red-green and blue-yellow channels
+const CONTRAST_SENSITIVITY: [(f64, f64, f64, f64); 3] = [
+    (1.0, 0.0047, 0.0, 1e-5),
+    (1.0, 0.0053, 0.0, 1e-5),
+    (34.1, 0.04, 13.5, 0.025),
+];
+
+fn color_errors(reference: &FloatImage, test: &FloatImage, pixels_per_degree: f64) -> Vec<f64> {
+    let reference = filtered_lab(reference, pixels_per_degree);
+    let test = filtered_lab(test, pixels_per_degree);
+
+    let max_error = hyab(
+        &hunt(&lab(&Color3::new(0.0, 1.0, 0.0))),
+        &hunt(&lab(&Color3::new(0.0, 0.0, 1.0))),
+    )
+    .powf(0.7);
+
+    reference
+        .iter()
+        .zip(&test)
+        .map(|(reference, test)| {
+            let error = hyab(&hunt(reference), &hunt(test)).powf(0.7);
+
+            // compressing the large errors: above 40% of the max, every error is clearly visible
+            let (p_c, p_t) = (0.4, 0.95);
+            if error < p_c * max_error {
+                p_t / (p_c * max_error) * error
+            } else {
+                p_t + (error - p_c * max_error) / (max_error - p_c * max_error) * (1.0 - p_t)
+            }
+        })
+        .collect()
+}
+
+/// blur the image in an opponent color space like the eye would, then convert it to CIELab
+fn filtered_lab(image: &FloatImage, pixels_per_degree: f64) -> Vec<Vec3> {
+    let width = image.width() as usize;
+    let height = image.height() as usize;
+    let opponent = image
+        .pixels()
+        .iter()
+        .map(|color| ycxcz(&color.clamp(0.0, 1.0).to_xyz()))
+        .collect::<Vec<_>>();
+
+    let max_b = CONTRAST_SENSITIVITY
+        .iter()
+        .map(|(_, b1, _, b2)| b1.max(*b2))
+        .fold(0.0, f64::max);
+    let radius = (3.0 * (max_b / (2.0 * PI * PI)).sqrt() * pixels_per_degree).ceil() as isize;
+
+    let filtered = CONTRAST_SENSITIVITY
+        .iter()
+        .enumerate()
+        .map(|(index, &(a1, b1, a2, b2))| {
+            let channel = Channel::new(
+                width,
+                height,
+                opponent.iter().map(|color| color[index]).collect(),
+            );
+
+            // each term of the sensitivity is a separable gaussian, of weight a * pi / b in 2d
+            let terms = [(a1, b1), (a2, b2)]
+                .into_iter()
+                .filter(|(a, _)| *a > 0.0)
+                .map(|(a, b)| {
+                    let kernel = (-radius..=radius)
+                        .map(|x| {
+                            let degrees = x as f64 / pixels_per_degree;
+                            (-PI * PI * degrees * degrees / b).exp()
+                        })
+                        .collect::<Vec<_>>();
+                    let sum: f64 = kernel.iter().sum();
+                    (a * PI / b, kernel, sum * sum)
+                })
+                .collect::<Vec<_>>();
+
+            let total: f64 = terms.iter().map(|(weight, _, sum)| weight * sum).sum();
+
+            terms
+                .iter()
+                .map(|(weight, kernel, _)| {
+                    let mut filtered = channel.convolve(kernel, kernel);
+                    filtered
+                        .values
+                        .iter_mut()
+                        .for_each(|value| *value *= weight / total);
+                    filtered
+                })
+                .reduce(|sum, term| sum.zip_with(&term, |a, b| a + b))
+                .expect("every channel has at least one term")
+        })
+        .collect::<Vec<_>>();
+
+    (0..width * height)
+        .map(|i| {
+            let opponent = Vec3::new(
+                filtered[0].values[i],
+                filtered[1].values[i],
+                filtered[2].values[i],
+            );
+            lab(&Color3::from_xyz(&xyz_from_ycxcz(&opponent)).clamp(0.0, 1.0))
+        })
+        .collect()
+}
+
+// Feature pipeline -------------------------
+
+fn feature_errors(reference: &FloatImage, test: &FloatImage, pixels_per_degree: f64) -> Vec<f64> {
+    let sigma = 0.5 * 0.082 * pixels_per_degree;
+    let radius = (3.0 * sigma).ceil() as usize;
+
+    let positions = (-(radius as isize)..=radius as isize)
+        .map(|x| x as f64)
+        .collect::<Vec<_>>();
+    let smoothing = gaussian_kernel(sigma, radius);
+    let gaussian = |x: f64| (-x * x / (2.0 * sigma * sigma)).exp();
+
+    // first derivative: positive weights sum to 1 (and negative ones to -1, by symmetry)
+    let edge_kernel = normalize_positive(positions.iter().map(|&x| -x * gaussian(x)).collect());
+    // second derivative: positive and negative weights are normalized separately
+    let point_kernel = normalize_signed(
+        positions
+            .iter()
+            .map(|&x| (x * x / (sigma * sigma) - 1.0) * gaussian(x))
+            .collect(),
+    );
+
+    let features = |image: &FloatImage| {
+        let luminance = Channel::new(
+            image.width() as usize,
+            image.height() as usize,
+            image
+                .pixels()
+                .iter()
+                .map(|color| color.clamp(0.0, 1.0).to_xyz().y())
+                .collect(),
+        );
+
+        let magnitude = |kernel: &[f64]| {
+            let along_x = luminance.convolve(kernel, &smoothing);
+            let along_y = luminance.convolve(&smoothing, kernel);
+            along_x.zip_with(&along_y, |x, y| (x * x + y * y).sqrt())
+        };
+
+        (magnitude(&edge_kernel), magnitude(&point_kernel))
+    };
+
+    let (reference_edges, reference_points) = features(reference);
+    let (test_edges, test_points) = features(test);
+
+    (0..reference_edges.values.len())
+        .map(|i| {
+            let edge = (reference_edges.values[i] - test_edges.values[i]).abs();
+            let point = (reference_points.values[i] - test_points.values[i]).abs();
+
+            (edge.max(point) / 2.0_f64.sqrt()).powf(0.5)
+        })
+        .collect()
+}
+
+fn normalize_positive(kernel: Vec<f64>) -> Vec<f64> {
+    let positive_sum: f64 = kernel.iter().filter(|value| **value > 0.0).sum();
+    kernel
+        .into_iter()
+        .map(|value| value / positive_sum)
+        .collect()
+}
+
+fn normalize_signed(kernel: Vec<f64>) -> Vec<f64> {
+    let positive_sum: f64 = kernel.iter().filter(|value| **value > 0.0).sum();
+    let negative_sum: f64 = -kernel.iter().filter(|value| **value < 0.0).sum::<f64>();
+
+    kernel
+        .into_iter()
+        .map(|value| {
+            if value > 0.0 {
+                value / positive_sum
+            } else {
+                value / negative_sum
+            }
+        })
+        .collect()
+}
+
+// Color spaces -----------------------------
+
+/// xyz coordinates of the white of linear srgb (d65)
+const WHITE: (f64, f64, f64) = (0.950_470, 1.0, 1.088_830);
+
+/// a linear opponent space: luminance, red-green and blue-yellow
+fn ycxcz(xyz: &Vec3) -> Vec3 {
+    let (x, y, z) = (xyz.x() / WHITE.0, xyz.y() / WHITE.1, xyz.z() / WHITE.2);
+
+    Vec3::new(116.0 * y - 16.0, 500.0 * (x - y), 200.0 * (y - z))
+}
+
+fn xyz_from_ycxcz(ycxcz: &Vec3) -> Vec3 {
+    let y = (ycxcz.x() + 16.0) / 116.0;
+    let x = ycxcz.y() / 500.0 + y;
+    let z = y - ycxcz.z() / 200.0;
+
+    Vec3::new(x * WHITE.0, y * WHITE.1, z * WHITE.2)
+}
+
+/// CIELab coordinates of a linear rgb color
+fn lab(rgb: &Color3) -> Vec3 {
+    let xyz = rgb.to_xyz();
+    let f = |t: f64| {
+        let delta: f64 = 6.0 / 29.0;
+        if t > delta.powi(3) {
+            t.cbrt()
+        } else {
+            t / (3.0 * delta * delta) + 4.0 / 29.0
+        }
+    };
+    let (fx, fy, fz) = (
+        f(xyz.x() / WHITE.0),
+        f(xyz.y() / WHITE.1),
+        f(xyz.z() / WHITE.2),
+    );
+
+    Vec3::new(116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+/// Hunt effect: colors look less saturated when they are dark
+fn hunt(lab: &Vec3) -> Vec3 {
+    let scale = 0.01 * lab.x();
+    Vec3::new(lab.x(), lab.y() * scale, lab.z() * scale)
+}
+
+/// a color distance that handles large differences better than the euclidean one
+fn hyab(lab_1: &Vec3, lab_2: &Vec3) -> f64 {
+    let (dl, da, db) = (
+        lab_1.x() - lab_2.x(),
+        lab_1.y() - lab_2.y(),
+        lab_1.z() - lab_2.z(),
+    );
+
+    dl.abs() + (da * da + db * db).sqrt()
+}
@@ -0,0 +1,111 @@
+mod error_map;
+pub use error_map::ErrorMap;
+
+mod filter;
+
+mod flip;
+pub use flip::{flip, DEFAULT_PIXELS_PER_DEGREE};
+
+mod pixel_metrics;
+pub use pixel_metrics::{mse, psnr};
+
+mod ssim;
+pub use ssim::ssim;
+
+use crate::image_io::FloatImage;
+use filter::Channel;
+
+/// the displayed values of the channels of all the pixels (clamped and sRGB encoded)
+fn display_values(image: &FloatImage) -> Vec<f64> {
+    image
+        .pixels()
+        .iter()
+        .map(|color| color.to_srgb())
+        .flat_map(|color| [color.r(), color.g(), color.b()])
+        .collect()
+}
+
+/// the luminance of the displayed colors
+fn display_luminance(image: &FloatImage) -> Channel {
+    let values = image
+        .pixels()
+        .iter()
+        .map(|color| color.to_srgb().luminance())
+        .collect();
+
+    Channel::new(image.width() as usize, image.height() as usize, values)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Color3;
+
+    /// a checkerboard of 4x4 pixel squares
+    fn checkerboard(dark: f64, light: f64) -> FloatImage {
+        let pixels = (0..32 * 32)
+            .map(|i| {
+                let (column, line) = (i % 32, i / 32);
+                let value = if (column / 4 + line / 4) % 2 == 0 {
+                    dark
+                } else {
+                    light
+                };
+                Color3::new(value, value, value)
+            })
+            .collect();
+
+        FloatImage::new(32, 32, pixels)
+    }
+
+    fn uniform(value: f64) -> FloatImage {
+        FloatImage::new(32, 32, vec![Color3::new(value, value, value); 32 * 32])
+    }
+
+    #[test]
+    fn it_should_find_no_difference_between_identical_images() {
+        let image = checkerboard(0.1, 0.8);
+
+        assert_eq!(mse(&image, &image), 0.0);
+        assert_eq!(psnr(&image, &image), f64::INFINITY);
+        assert!((ssim(&image, &image) - 1.0).abs() < 1e-9);
+
+        let errors = flip(&image, &image, DEFAULT_PIXELS_PER_DEGREE);
+        assert!(errors.max() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_compute_the_mse_on_displayed_values() {
+        // displayed values are sRGB encoded: the linear middle grey is displayed at 0.7354
+        let error = mse(&uniform(0.0), &uniform(0.5));
+
+        assert!((error - 0.7354_f64.powi(2)).abs() < 1e-4);
+        assert!((psnr(&uniform(0.0), &uniform(0.5)) - 2.670).abs() < 1e-3);
+    }
+
+    #[test]
+    fn it_should_rank_differences() {
+        let reference = checkerboard(0.1, 0.8);
+        let close = checkerboard(0.12, 0.78);
+        let far = uniform(0.45);
+
+        assert!(mse(&reference, &close) < mse(&reference, &far));
+        assert!(ssim(&reference, &close) > ssim(&reference, &far));
+        assert!(ssim(&reference, &far) < 0.5);
+
+        let close_errors = flip(&reference, &close, DEFAULT_PIXELS_PER_DEGREE);
+        let far_errors = flip(&reference, &far, DEFAULT_PIXELS_PER_DEGREE);
+        assert!(close_errors.mean() < far_errors.mean());
+        assert!(far_errors.max() <= 1.0);
+    }
+
+    #[test]
+    fn it_should_map_errors_to_false_colors() {
+        let errors = ErrorMap::new(2, 1, vec![0.0, 1.0]);
+        let image = errors.to_false_color();
+
+        let (low, high) = (image.pixel(0, 0), image.pixel(1, 0));
+        assert!(low.luminance() < 0.01);
+        assert!(high.luminance() > 0.5);
+    }
+}
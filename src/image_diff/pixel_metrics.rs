@@ -0,0 +1,39 @@
+use crate::image_io::FloatImage;
+
+use super::display_values;
+
+/// mean squared error between two images, on the displayed values (clamped, sRGB encoded, between 0 and 1),
+/// averaged over the pixels and the three channels
+/// # panics
+/// if the images do not have the same size
+pub fn mse(reference: &FloatImage, test: &FloatImage) -> f64 {
+    assert!(reference.same_size(test), "images must have the same size");
+
+    let reference = display_values(reference);
+    let test = display_values(test);
+
+    if reference.is_empty() {
+        return 0.0;
+    }
+
+    let sum: f64 = reference
+        .iter()
+        .zip(&test)
+        .map(|(a, b)| (a - b) * (a - b))
+        .sum();
+
+    sum / reference.len() as f64
+}
+
+/// peak signal to noise ratio, in decibels (infinite for identical images).
+/// The greater, the closer the images: ~30 dB is a visible difference, ~50 dB is hardly noticeable
+pub fn psnr(reference: &FloatImage, test: &FloatImage) -> f64 {
+    let mse = mse(reference, test);
+
+    if mse == 0.0 {
+        return f64::INFINITY;
+    }
+
+    // the peak value of displayed values is 1
+    -10.0 * mse.log10()
+}
@@ -0,0 +1,48 @@
+use super::{
+    display_luminance,
+    filter::{gaussian_kernel, Channel},
+};
+use crate::image_io::FloatImage;
+
+/// mean structural similarity index of two images, on the luminance of the displayed values.
+/// 1 for identical images, lower for different images.
+/// Uses the usual gaussian window of standard deviation 1.5 pixel
+/// # panics
+/// if the images do not have the same size
+pub fn ssim(reference: &FloatImage, test: &FloatImage) -> f64 {
+    assert!(reference.same_size(test), "images must have the same size");
+
+    if reference.pixels().is_empty() {
+        return 1.0;
+    }
+
+    // stabilization constants, for a dynamic range of 1
+    const C1: f64 = 0.01 * 0.01;
+    const C2: f64 = 0.03 * 0.03;
+
+    let x = display_luminance(reference);
+    let y = display_luminance(test);
+
+    let window = gaussian_kernel(1.5, 5);
+    let blur = |channel: &Channel| channel.convolve(&window, &window);
+
+    let mean_x = blur(&x);
+    let mean_y = blur(&y);
+    let mean_xx = blur(&x.zip_with(&x, |a, b| a * b));
+    let mean_yy = blur(&y.zip_with(&y, |a, b| a * b));
+    let mean_xy = blur(&x.zip_with(&y, |a, b| a * b));
+
+    let sum: f64 = (0..x.values.len())
+        .map(|i| {
+            let (mx, my) = (mean_x.values[i], mean_y.values[i]);
+            let variance_x = mean_xx.values[i] - mx * mx;
+            let variance_y = mean_yy.values[i] - my * my;
+            let covariance = mean_xy.values[i] - mx * my;
+
+            ((2.0 * mx * my + C1) * (2.0 * covariance + C2))
+                / ((mx * mx + my * my + C1) * (variance_x + variance_y + C2))
+        })
+        .sum();
+
+    sum / x.values.len() as f64
+}
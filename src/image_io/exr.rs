@@ -0,0 +1,47 @@
+use std::path::Path;
+
+use exr::prelude::{read_first_rgba_layer_from_file, write_rgb_file};
+
+use super::{FloatImage, ImageError};
+use crate::Color3;
+
+/// read the first rgb layer of an exr file. Alpha is ignored
+pub fn read_exr(path: &Path) -> Result<FloatImage, ImageError> {
+    let image = read_first_rgba_layer_from_file(
+        path,
+        |resolution, _| {
+            (
+                resolution.width(),
+                vec![Color3::black(); resolution.width() * resolution.height()],
+            )
+        },
+        |(width, pixels), position, (r, g, b, _a): (f32, f32, f32, f32)| {
+            pixels[position.y() * *width + position.x()] =
+                Color3::new(r as f64, g as f64, b as f64);
+        },
+    )
+    .map_err(ImageError::Exr)?;
+
+    let size = image.layer_data.size;
+    let (_, pixels) = image.layer_data.channel_data.pixels;
+
+    Ok(FloatImage::new(
+        size.width() as u32,
+        size.height() as u32,
+        pixels,
+    ))
+}
+
+/// write an exr file with 32 bits float r, g and b channels
+pub fn write_exr(path: &Path, image: &FloatImage) -> Result<(), ImageError> {
+    write_rgb_file(
+        path,
+        image.width() as usize,
+        image.height() as usize,
+        |x, y| {
+            let color = image.pixel(x as u32, y as u32);
+            (color.r() as f32, color.g() as f32, color.b() as f32)
+        },
+    )
+    .map_err(ImageError::Exr)
+}
@@ -0,0 +1,64 @@
+use crate::{render::PixelStats, Color3};
+
+/// An image of linear rgb colors, stored line after line from the top of the image.
+/// It is the common representation every format is converted from and to
+#[derive(Debug, Clone, PartialEq)]
+pub struct FloatImage {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color3>,
+}
+
+impl FloatImage {
+    /// # panics
+    /// if there is not exactly width * height pixels
+    pub fn new(width: u32, height: u32, pixels: Vec<Color3>) -> Self {
+        assert_eq!(
+            pixels.len(),
+            width as usize * height as usize,
+            "the number of pixels does not match the image size"
+        );
+
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// the image of a rendered scene. Scenes are vectors of rows from the bottom line to the top one
+    pub fn from_scene(scene: &[Vec<PixelStats>]) -> Self {
+        let height = scene.len() as u32;
+        let width = scene.first().map_or(0, |row| row.len()) as u32;
+        let pixels = scene
+            .iter()
+            .rev()
+            .flatten()
+            .map(|pixel| pixel.color())
+            .collect();
+
+        Self::new(width, height, pixels)
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn pixels(&self) -> &[Color3] {
+        &self.pixels
+    }
+
+    /// (0, 0) is the top left corner
+    pub fn pixel(&self, column: u32, line: u32) -> Color3 {
+        self.pixels[line as usize * self.width as usize + column as usize]
+    }
+
+    /// true if both images have the same width and height
+    pub fn same_size(&self, other: &Self) -> bool {
+        self.width == other.width && self.height == other.height
+    }
+}
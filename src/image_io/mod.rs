@@ -0,0 +1,186 @@
+use std::{error::Error, fmt, fs, io, path::Path};
+
+mod exr;
+pub use self::exr::{read_exr, write_exr};
+
+mod float_image;
+pub use float_image::FloatImage;
+
+mod netpbm;
+pub use netpbm::{encode_ppm, NetpbmError, NetpbmImage, NetpbmKind};
+
+mod pfm;
+pub use pfm::{decode_pfm, encode_pfm};
+
+mod png;
+pub use self::png::{decode_png, encode_png};
+
+/// Reasons an image cannot be loaded or saved
+#[derive(Debug)]
+pub enum ImageError {
+    Io(io::Error),
+    Netpbm(NetpbmError),
+    Png(::png::DecodingError),
+    PngEncoding(::png::EncodingError),
+    Exr(::exr::error::Error),
+    InvalidPfm(&'static str),
+    /// the extension of the file is not one of the supported formats
+    UnsupportedFormat(String),
+}
+
+impl fmt::Display for ImageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImageError::Io(error) => write!(f, "{error}"),
+            ImageError::Netpbm(error) => write!(f, "invalid netpbm image: {error}"),
+            ImageError::Png(error) => write!(f, "invalid png image: {error}"),
+            ImageError::PngEncoding(error) => write!(f, "could not encode the png image: {error}"),
+            ImageError::Exr(error) => write!(f, "exr error: {error}"),
+            ImageError::InvalidPfm(reason) => write!(f, "invalid pfm image: {reason}"),
+            ImageError::UnsupportedFormat(extension) => write!(
+                f,
+                "unsupported format {extension:?}, expected ppm, pgm, pbm, pnm, png, pfm or exr"
+            ),
+        }
+    }
+}
+
+impl Error for ImageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ImageError::Io(error) => Some(error),
+            ImageError::Netpbm(error) => Some(error),
+            ImageError::Png(error) => Some(error),
+            ImageError::PngEncoding(error) => Some(error),
+            ImageError::Exr(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for ImageError {
+    fn from(error: io::Error) -> Self {
+        ImageError::Io(error)
+    }
+}
+
+impl From<NetpbmError> for ImageError {
+    fn from(error: NetpbmError) -> Self {
+        ImageError::Netpbm(error)
+    }
+}
+
+/// the supported formats, from the extension of a path
+enum Format {
+    Netpbm,
+    Png,
+    Pfm,
+    Exr,
+}
+
+impl Format {
+    fn from_path(path: &Path) -> Result<Self, ImageError> {
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+
+        match extension.as_str() {
+            "ppm" | "pgm" | "pbm" | "pnm" => Ok(Format::Netpbm),
+            "png" => Ok(Format::Png),
+            "pfm" => Ok(Format::Pfm),
+            "exr" => Ok(Format::Exr),
+            _ => Err(ImageError::UnsupportedFormat(extension)),
+        }
+    }
+}
+
+/// load an image in linear colors, the format is deduced from the extension
+pub fn load_image(path: impl AsRef<Path>) -> Result<FloatImage, ImageError> {
+    let path = path.as_ref();
+
+    match Format::from_path(path)? {
+        Format::Netpbm => Ok(NetpbmImage::parse(&fs::read(path)?)?.to_float_image()),
+        Format::Png => decode_png(&fs::read(path)?),
+        Format::Pfm => decode_pfm(&fs::read(path)?),
+        Format::Exr => read_exr(path),
+    }
+}
+
+/// save an image, the format is deduced from the extension.
+/// ppm and png files are 8 bits: colors are clamped and sRGB encoded
+pub fn save_image(path: impl AsRef<Path>, image: &FloatImage) -> Result<(), ImageError> {
+    let path = path.as_ref();
+
+    match Format::from_path(path)? {
+        Format::Netpbm => fs::write(path, encode_ppm(image))?,
+        Format::Png => fs::write(path, encode_png(image)?)?,
+        Format::Pfm => fs::write(path, encode_pfm(image))?,
+        Format::Exr => write_exr(path, image)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Color3;
+
+    fn gradient() -> FloatImage {
+        let pixels = (0..12)
+            .map(|i| Color3::new(i as f64 / 11.0, 1.0 - i as f64 / 11.0, 0.5))
+            .collect();
+
+        FloatImage::new(4, 3, pixels)
+    }
+
+    fn max_difference(image: &FloatImage, other: &FloatImage) -> f64 {
+        image
+            .pixels()
+            .iter()
+            .zip(other.pixels())
+            .map(|(color, other)| color.distance(other))
+            .fold(0.0, f64::max)
+    }
+
+    #[test]
+    fn it_should_round_trip_every_format() {
+        let image = gradient();
+
+        for (extension, tolerance) in [("pfm", 1e-6), ("exr", 1e-6), ("png", 0.01), ("ppm", 0.01)] {
+            let path =
+                std::env::temp_dir().join(format!("round-trip-{}.{extension}", std::process::id()));
+
+            save_image(&path, &image).unwrap();
+            let loaded = load_image(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert!(loaded.same_size(&image), "{extension} changed the size");
+            let difference = max_difference(&loaded, &image);
+            assert!(difference < tolerance, "{extension} is off by {difference}");
+        }
+    }
+
+    #[test]
+    fn it_should_reject_unknown_formats() {
+        assert!(matches!(
+            load_image("image.gif"),
+            Err(ImageError::UnsupportedFormat(_))
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_pfm_sizes_too_large_to_be_counted() {
+        for header in [
+            b"PF 4294967295 4294967295 -1.0\n",
+            b"Pf 4294967295 4294967295 -1.0\n",
+        ] {
+            assert!(matches!(
+                decode_pfm(header),
+                Err(ImageError::InvalidPfm("image too large"))
+            ));
+        }
+    }
+}
@@ -0,0 +1,560 @@
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use super::FloatImage;
+use crate::Color3;
+
+/// The three families of Netpbm images, each with a plain (ascii) and a raw (binary) encoding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NetpbmKind {
+    /// PBM (P1, P4): black and white, 1 means black
+    Bitmap,
+    /// PGM (P2, P5): one gray sample per pixel
+    Graymap,
+    /// PPM (P3, P6): red, green and blue samples per pixel
+    Pixmap,
+}
+
+impl NetpbmKind {
+    /// number of samples per pixel
+    pub fn nb_channels(&self) -> usize {
+        match self {
+            NetpbmKind::Bitmap | NetpbmKind::Graymap => 1,
+            NetpbmKind::Pixmap => 3,
+        }
+    }
+}
+
+/// Reasons a Netpbm file cannot be parsed
+#[derive(Debug)]
+pub enum NetpbmError {
+    Io(io::Error),
+    /// the file does not start with P1 to P6
+    UnknownMagicNumber(String),
+    /// a header value (width, height, max value) is missing or not a positive number
+    InvalidHeader(&'static str),
+    /// the max value must be between 1 and 65535
+    InvalidMaxValue(u32),
+    /// a plain sample is not a number
+    InvalidSample(String),
+    /// a sample is greater than the max value of the image
+    SampleOutOfRange {
+        value: u32,
+        max_value: u32,
+    },
+    /// the file ends before all the samples were read
+    Truncated {
+        expected: usize,
+        found: usize,
+    },
+}
+
+impl fmt::Display for NetpbmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NetpbmError::Io(error) => write!(f, "could not read the image: {error}"),
+            NetpbmError::UnknownMagicNumber(magic) => {
+                write!(f, "unknown magic number {magic:?}, expected P1 to P6")
+            }
+            NetpbmError::InvalidHeader(field) => write!(f, "invalid or missing {field}"),
+            NetpbmError::InvalidMaxValue(value) => {
+                write!(f, "invalid max value {value}, expected 1 to 65535")
+            }
+            NetpbmError::InvalidSample(sample) => write!(f, "invalid sample {sample:?}"),
+            NetpbmError::SampleOutOfRange { value, max_value } => {
+                write!(
+                    f,
+                    "sample {value} is greater than the max value {max_value}"
+                )
+            }
+            NetpbmError::Truncated { expected, found } => write!(
+                f,
+                "truncated image: {found} samples found, {expected} expected"
+            ),
+        }
+    }
+}
+
+impl Error for NetpbmError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NetpbmError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for NetpbmError {
+    fn from(error: io::Error) -> Self {
+        NetpbmError::Io(error)
+    }
+}
+
+/// A decoded PBM, PGM or PPM image.
+/// Samples are kept as stored in the file, line after line from the top of the image
+#[derive(Debug, Clone, PartialEq)]
+pub struct NetpbmImage {
+    kind: NetpbmKind,
+    width: u32,
+    height: u32,
+    /// value of a full intensity sample (1 for bitmaps)
+    max_value: u32,
+    samples: Vec<u16>,
+}
+
+impl NetpbmImage {
+    /// read and parse an image file
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, NetpbmError> {
+        Self::parse(&fs::read(path)?)
+    }
+
+    /// parse the first image of a Netpbm file (P1 to P6)
+    pub fn parse(bytes: &[u8]) -> Result<Self, NetpbmError> {
+        let mut parser = Parser::new(bytes);
+
+        let magic = parser
+            .next_token()
+            .ok_or(NetpbmError::InvalidHeader("magic number"))?;
+        let (kind, is_plain) = match magic {
+            b"P1" => (NetpbmKind::Bitmap, true),
+            b"P2" => (NetpbmKind::Graymap, true),
+            b"P3" => (NetpbmKind::Pixmap, true),
+            b"P4" => (NetpbmKind::Bitmap, false),
+            b"P5" => (NetpbmKind::Graymap, false),
+            b"P6" => (NetpbmKind::Pixmap, false),
+            _ => {
+                return Err(NetpbmError::UnknownMagicNumber(
+                    String::from_utf8_lossy(magic).into_owned(),
+                ))
+            }
+        };
+
+        let width = parser.header_number("width")?;
+        let height = parser.header_number("height")?;
+
+        let max_value = if kind == NetpbmKind::Bitmap {
+            1
+        } else {
+            let max_value = parser.header_number("max value")?;
+            if max_value > u16::MAX as u32 {
+                return Err(NetpbmError::InvalidMaxValue(max_value));
+            }
+            max_value
+        };
+
+        let nb_samples = (width as usize)
+            .checked_mul(height as usize)
+            .and_then(|nb_pixels| nb_pixels.checked_mul(kind.nb_channels()))
+            .ok_or(NetpbmError::InvalidHeader("image size"))?;
+
+        let samples = match (kind, is_plain) {
+            (NetpbmKind::Bitmap, true) => parser.plain_bits(nb_samples)?,
+            (_, true) => parser.plain_samples(nb_samples, max_value)?,
+            (NetpbmKind::Bitmap, false) => parser.raw_bits(width as usize, height as usize)?,
+            (_, false) => parser.raw_samples(nb_samples, max_value)?,
+        };
+
+        Ok(Self {
+            kind,
+            width,
+            height,
+            max_value,
+            samples,
+        })
+    }
+
+    pub fn kind(&self) -> NetpbmKind {
+        self.kind
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn max_value(&self) -> u32 {
+        self.max_value
+    }
+
+    /// raw samples, as stored in the file
+    pub fn samples(&self) -> &[u16] {
+        &self.samples
+    }
+
+    /// color of a pixel, each channel between 0 and 1 (still sRGB encoded).
+    /// (0, 0) is the top left corner of the image
+    pub fn color(&self, column: u32, line: u32) -> Color3 {
+        let channels = self.kind.nb_channels();
+        let index = (line as usize * self.width as usize + column as usize) * channels;
+        let value = |channel: usize| self.samples[index + channel] as f64 / self.max_value as f64;
+
+        match self.kind {
+            // in bitmaps, 1 is black
+            NetpbmKind::Bitmap => Color3::white() * (1.0 - value(0)),
+            NetpbmKind::Graymap => Color3::white() * value(0),
+            NetpbmKind::Pixmap => Color3::new(value(0), value(1), value(2)),
+        }
+    }
+
+    /// colors of all the pixels, line after line from the top of the image
+    pub fn colors(&self) -> Vec<Color3> {
+        (0..self.height)
+            .flat_map(|line| (0..self.width).map(move |column| self.color(column, line)))
+            .collect()
+    }
+
+    /// the image in linear colors (the samples are sRGB encoded)
+    pub fn to_float_image(&self) -> FloatImage {
+        let pixels = self.colors().iter().map(Color3::from_srgb).collect();
+
+        FloatImage::new(self.width, self.height, pixels)
+    }
+}
+
+/// encode a raw (P6) 8 bits ppm file, sRGB encoded
+pub fn encode_ppm(image: &FloatImage) -> Vec<u8> {
+    let mut bytes = format!("P6\n{} {}\n255\n", image.width(), image.height()).into_bytes();
+
+    for color in image.pixels().iter().map(Color3::to_srgb) {
+        for value in [color.r(), color.g(), color.b()] {
+            bytes.push((value * 255.0).round() as u8);
+        }
+    }
+
+    bytes
+}
+
+/// Reads a Netpbm file byte after byte
+struct Parser<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, position: 0 }
+    }
+
+    /// skip whitespaces and comments. A comment starts with # and ends with the line
+    fn skip_separators(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            if byte == b'#' {
+                while self
+                    .bytes
+                    .get(self.position)
+                    .is_some_and(|&byte| byte != b'\n' && byte != b'\r')
+                {
+                    self.position += 1;
+                }
+            } else if byte.is_ascii_whitespace() {
+                self.position += 1;
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// the next run of characters that are neither whitespaces nor comments
+    fn next_token(&mut self) -> Option<&'a [u8]> {
+        self.skip_separators();
+
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|&byte| !byte.is_ascii_whitespace() && byte != b'#')
+        {
+            self.position += 1;
+        }
+
+        (self.position > start).then(|| &self.bytes[start..self.position])
+    }
+
+    fn header_number(&mut self, field: &'static str) -> Result<u32, NetpbmError> {
+        self.next_token()
+            .and_then(|token| std::str::from_utf8(token).ok())
+            .and_then(|token| token.parse::<u32>().ok())
+            .filter(|&value| value > 0)
+            .ok_or(NetpbmError::InvalidHeader(field))
+    }
+
+    /// P1: '0' and '1' characters, whitespaces between them are optional
+    fn plain_bits(&mut self, nb_samples: usize) -> Result<Vec<u16>, NetpbmError> {
+        let mut samples = Vec::new();
+
+        while samples.len() < nb_samples {
+            self.skip_separators();
+
+            match self.bytes.get(self.position) {
+                Some(b'0') => samples.push(0),
+                Some(b'1') => samples.push(1),
+                Some(&byte) => {
+                    return Err(NetpbmError::InvalidSample((byte as char).to_string()));
+                }
+                None => {
+                    return Err(NetpbmError::Truncated {
+                        expected: nb_samples,
+                        found: samples.len(),
+                    })
+                }
+            }
+            self.position += 1;
+        }
+
+        Ok(samples)
+    }
+
+    /// P2, P3: decimal numbers separated by whitespaces
+    fn plain_samples(
+        &mut self,
+        nb_samples: usize,
+        max_value: u32,
+    ) -> Result<Vec<u16>, NetpbmError> {
+        let mut samples = Vec::new();
+
+        while samples.len() < nb_samples {
+            let token = self.next_token().ok_or(NetpbmError::Truncated {
+                expected: nb_samples,
+                found: samples.len(),
+            })?;
+
+            let value = std::str::from_utf8(token)
+                .ok()
+                .and_then(|token| token.parse::<u32>().ok())
+                .ok_or_else(|| {
+                    NetpbmError::InvalidSample(String::from_utf8_lossy(token).into_owned())
+                })?;
+
+            samples.push(checked_sample(value, max_value)?);
+        }
+
+        Ok(samples)
+    }
+
+    /// the raster of raw images starts after exactly one whitespace following the header
+    fn start_raster(&mut self) -> &'a [u8] {
+        self.position += 1;
+        self.bytes.get(self.position..).unwrap_or_default()
+    }
+
+    /// P4: 8 pixels per byte, most significant bit first, each line padded to a whole byte
+    fn raw_bits(&mut self, width: usize, height: usize) -> Result<Vec<u16>, NetpbmError> {
+        let raster = self.start_raster();
+        let bytes_per_line = width.div_ceil(8);
+
+        let raster_size = bytes_per_line
+            .checked_mul(height)
+            .ok_or(NetpbmError::InvalidHeader("image size"))?;
+        if raster.len() < raster_size {
+            return Err(NetpbmError::Truncated {
+                expected: width * height,
+                found: raster.len() / bytes_per_line * width,
+            });
+        }
+
+        let samples = raster
+            .chunks(bytes_per_line)
+            .take(height)
+            .flat_map(|line| (0..width).map(move |j| ((line[j / 8] >> (7 - j % 8)) & 1) as u16))
+            .collect();
+
+        Ok(samples)
+    }
+
+    /// P5, P6: one byte per sample, or two (big endian) if the max value is greater than 255
+    fn raw_samples(&mut self, nb_samples: usize, max_value: u32) -> Result<Vec<u16>, NetpbmError> {
+        let raster = self.start_raster();
+        let bytes_per_sample = if max_value > 255 { 2 } else { 1 };
+
+        let raster_size = nb_samples
+            .checked_mul(bytes_per_sample)
+            .ok_or(NetpbmError::InvalidHeader("image size"))?;
+        if raster.len() < raster_size {
+            return Err(NetpbmError::Truncated {
+                expected: nb_samples,
+                found: raster.len() / bytes_per_sample,
+            });
+        }
+
+        raster
+            .chunks(bytes_per_sample)
+            .take(nb_samples)
+            .map(|bytes| {
+                let value = bytes
+                    .iter()
+                    .fold(0, |value, &byte| value << 8 | byte as u32);
+                checked_sample(value, max_value)
+            })
+            .collect()
+    }
+}
+
+fn checked_sample(value: u32, max_value: u32) -> Result<u16, NetpbmError> {
+    if value > max_value {
+        return Err(NetpbmError::SampleOutOfRange { value, max_value });
+    }
+
+    Ok(value as u16)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_parse_plain_pixmaps_with_comments_and_any_whitespace() {
+        let image = NetpbmImage::parse(
+            b"P3 # a comment after the magic number\n\
+              # a full line comment\n\
+              2 1\t255\n\
+              255 0 0   0 0 # end of line comment\n\
+              128\n",
+        )
+        .unwrap();
+
+        assert_eq!(image.kind(), NetpbmKind::Pixmap);
+        assert_eq!((image.width(), image.height()), (2, 1));
+        assert_eq!(image.samples(), &[255, 0, 0, 0, 0, 128]);
+        assert_eq!(image.color(0, 0), Color3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn it_should_parse_plain_bitmaps_without_separators() {
+        let image = NetpbmImage::parse(b"P1\n3 2\n010\n1 1 0").unwrap();
+
+        assert_eq!(image.kind(), NetpbmKind::Bitmap);
+        assert_eq!(image.samples(), &[0, 1, 0, 1, 1, 0]);
+        // 1 is black
+        assert_eq!(image.color(1, 0), Color3::black());
+        assert_eq!(image.color(2, 1), Color3::white());
+    }
+
+    #[test]
+    fn it_should_parse_plain_graymaps() {
+        let image = NetpbmImage::parse(b"P2\n2 2\n4\n0 1\n2 4\n").unwrap();
+
+        assert_eq!(image.kind(), NetpbmKind::Graymap);
+        assert_eq!(image.color(0, 1), Color3::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn it_should_parse_raw_bitmaps() {
+        // 10 pixels wide: 2 bytes per line, the last 6 bits are padding
+        let mut bytes = b"P4\n10 2\n".to_vec();
+        bytes.extend([0b1010_0000, 0b0100_0000, 0b0000_0000, 0b1111_1111]);
+
+        let image = NetpbmImage::parse(&bytes).unwrap();
+
+        assert_eq!(
+            image.samples(),
+            &[1, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1, 1]
+        );
+    }
+
+    #[test]
+    fn it_should_parse_raw_graymaps_and_pixmaps() {
+        let mut bytes = b"P5 3 1 255\n".to_vec();
+        // the single whitespace after the header is followed by samples that look like whitespaces
+        bytes.extend([b' ', b'\n', 255]);
+        let image = NetpbmImage::parse(&bytes).unwrap();
+        assert_eq!(image.samples(), &[32, 10, 255]);
+
+        let mut bytes = b"P6\n1 1\n# comment\n100\n".to_vec();
+        bytes.extend([100, 50, 0]);
+        let image = NetpbmImage::parse(&bytes).unwrap();
+        assert_eq!(image.color(0, 0), Color3::new(1.0, 0.5, 0.0));
+    }
+
+    #[test]
+    fn it_should_parse_16_bits_samples() {
+        let mut bytes = b"P6 1 1 65535\n".to_vec();
+        bytes.extend([0xff, 0xff, 0x80, 0x00, 0x00, 0x01]);
+
+        let image = NetpbmImage::parse(&bytes).unwrap();
+
+        assert_eq!(image.max_value(), 65535);
+        assert_eq!(image.samples(), &[65535, 32768, 1]);
+    }
+
+    #[test]
+    fn it_should_read_back_encoded_images() {
+        let image = FloatImage::new(
+            2,
+            1,
+            vec![Color3::new(1.0, 0.25, 0.0), Color3::new(0.0, 0.0, 1.0)],
+        );
+
+        let decoded = NetpbmImage::parse(&encode_ppm(&image)).unwrap();
+
+        assert_eq!(decoded.kind(), NetpbmKind::Pixmap);
+        // 0.25 is stored as 0.537 once sRGB encoded
+        assert_eq!(decoded.samples(), &[255, 137, 0, 0, 0, 255]);
+    }
+
+    #[test]
+    fn it_should_report_truncated_files() {
+        let result = NetpbmImage::parse(b"P3\n2 2\n255\n1 2 3\n4 5 6\n");
+        assert!(matches!(
+            result,
+            Err(NetpbmError::Truncated {
+                expected: 12,
+                found: 6
+            })
+        ));
+
+        let mut bytes = b"P5 2 2 65535\n".to_vec();
+        bytes.extend([0, 1, 0, 2, 0]);
+        assert!(matches!(
+            NetpbmImage::parse(&bytes),
+            Err(NetpbmError::Truncated {
+                expected: 4,
+                found: 2
+            })
+        ));
+
+        assert!(matches!(
+            NetpbmImage::parse(b"P3\n2"),
+            Err(NetpbmError::InvalidHeader("height"))
+        ));
+    }
+
+    #[test]
+    fn it_should_reject_malformed_files() {
+        assert!(matches!(
+            NetpbmImage::parse(b"P7\n1 1\n255\n"),
+            Err(NetpbmError::UnknownMagicNumber(_))
+        ));
+        assert!(matches!(
+            NetpbmImage::parse(b"P3\n1 1\n70000\n"),
+            Err(NetpbmError::InvalidMaxValue(70000))
+        ));
+        assert!(matches!(
+            NetpbmImage::parse(b"P3\n1 1\n255\n1 2 x"),
+            Err(NetpbmError::InvalidSample(_))
+        ));
+        assert!(matches!(
+            NetpbmImage::parse(b"P2\n1 1\n15\n16"),
+            Err(NetpbmError::SampleOutOfRange {
+                value: 16,
+                max_value: 15
+            })
+        ));
+        assert!(matches!(
+            NetpbmImage::parse(b"P2\n0 1\n15\n"),
+            Err(NetpbmError::InvalidHeader("width"))
+        ));
+
+        // sizes too large to be counted, rather than an overflow
+        for header in [
+            b"P5 4294967295 4294967295 65535\n".as_slice(),
+            b"P6 4294967295 4294967295 255\n",
+        ] {
+            assert!(matches!(
+                NetpbmImage::parse(header),
+                Err(NetpbmError::InvalidHeader("image size"))
+            ));
+        }
+    }
+}
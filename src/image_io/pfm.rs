@@ -0,0 +1,96 @@
+use super::{FloatImage, ImageError};
+use crate::Color3;
+
+// Portable float map: "PF" (rgb) or "Pf" (gray), the size, then a scale whose sign gives the byte order
+// (negative: little endian). 32 bits floats follow, line after line from the BOTTOM of the image
+
+/// decode a pfm file
+pub fn decode_pfm(bytes: &[u8]) -> Result<FloatImage, ImageError> {
+    let mut tokens = Vec::new();
+    let mut position = 0;
+
+    // the header is made of 4 tokens, the raster starts after the single whitespace following the last one
+    while tokens.len() < 4 {
+        while bytes.get(position).is_some_and(u8::is_ascii_whitespace) {
+            position += 1;
+        }
+        let start = position;
+        while bytes
+            .get(position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace())
+        {
+            position += 1;
+        }
+        if start == position {
+            return Err(ImageError::InvalidPfm("truncated header"));
+        }
+        tokens.push(String::from_utf8_lossy(&bytes[start..position]).into_owned());
+    }
+    position += 1;
+
+    let nb_channels = match tokens[0].as_str() {
+        "PF" => 3,
+        "Pf" => 1,
+        _ => return Err(ImageError::InvalidPfm("unknown magic number")),
+    };
+    let width = tokens[1]
+        .parse::<u32>()
+        .map_err(|_| ImageError::InvalidPfm("invalid width"))?;
+    let height = tokens[2]
+        .parse::<u32>()
+        .map_err(|_| ImageError::InvalidPfm("invalid height"))?;
+    let scale = tokens[3]
+        .parse::<f32>()
+        .map_err(|_| ImageError::InvalidPfm("invalid scale"))?;
+
+    let (nb_samples, raster_size) = (width as usize)
+        .checked_mul(height as usize)
+        .and_then(|nb_pixels| nb_pixels.checked_mul(nb_channels))
+        .and_then(|nb_samples| Some((nb_samples, nb_samples.checked_mul(4)?)))
+        .ok_or(ImageError::InvalidPfm("image too large"))?;
+    let raster = bytes.get(position..).unwrap_or_default();
+    if raster.len() < raster_size {
+        return Err(ImageError::InvalidPfm("truncated raster"));
+    }
+
+    let samples = raster
+        .chunks_exact(4)
+        .take(nb_samples)
+        .map(|bytes| {
+            let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+            if scale < 0.0 {
+                f32::from_le_bytes(bytes) as f64
+            } else {
+                f32::from_be_bytes(bytes) as f64
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let line_size = width as usize * nb_channels;
+    let pixels = samples
+        .chunks(line_size.max(1))
+        .rev()
+        .flat_map(|line| line.chunks(nb_channels))
+        .map(|sample| match sample {
+            [gray] => Color3::new(*gray, *gray, *gray),
+            _ => Color3::new(sample[0], sample[1], sample[2]),
+        })
+        .collect();
+
+    Ok(FloatImage::new(width, height, pixels))
+}
+
+/// encode an rgb pfm file, little endian
+pub fn encode_pfm(image: &FloatImage) -> Vec<u8> {
+    let mut bytes = format!("PF\n{} {}\n-1.0\n", image.width(), image.height()).into_bytes();
+
+    for line in image.pixels().chunks(image.width().max(1) as usize).rev() {
+        for color in line {
+            for value in [color.r(), color.g(), color.b()] {
+                bytes.extend((value as f32).to_le_bytes());
+            }
+        }
+    }
+
+    bytes
+}
@@ -0,0 +1,70 @@
+use super::{FloatImage, ImageError};
+use crate::Color3;
+
+/// decode a png file (8 or 16 bits, gray or rgb, with or without alpha, palettes are expanded)
+pub fn decode_png(bytes: &[u8]) -> Result<FloatImage, ImageError> {
+    let mut decoder = png::Decoder::new(bytes);
+    decoder.set_transformations(png::Transformations::EXPAND);
+
+    let (info, mut reader) = decoder.read_info().map_err(ImageError::Png)?;
+    let mut buffer = vec![0; info.buffer_size()];
+    reader.next_frame(&mut buffer).map_err(ImageError::Png)?;
+
+    let nb_channels = info.color_type.samples();
+    let (bytes_per_sample, max_value) = match info.bit_depth {
+        png::BitDepth::Sixteen => (2, u16::MAX as f64),
+        _ => (1, u8::MAX as f64),
+    };
+
+    let pixels = buffer
+        .chunks(info.line_size)
+        .take(info.height as usize)
+        .flat_map(|line| {
+            line.chunks(nb_channels * bytes_per_sample)
+                .take(info.width as usize)
+        })
+        .map(|pixel| {
+            let sample = |channel: usize| {
+                let bytes = &pixel[channel * bytes_per_sample..(channel + 1) * bytes_per_sample];
+                let value = bytes
+                    .iter()
+                    .fold(0.0, |value, &byte| value * 256.0 + byte as f64);
+                value / max_value
+            };
+
+            // gray (and gray + alpha) images only have one color channel. Alpha is ignored
+            let encoded = if nb_channels < 3 {
+                Color3::white() * sample(0)
+            } else {
+                Color3::new(sample(0), sample(1), sample(2))
+            };
+            Color3::from_srgb(&encoded)
+        })
+        .collect();
+
+    Ok(FloatImage::new(info.width, info.height, pixels))
+}
+
+/// encode an 8 bits rgb png file
+pub fn encode_png(image: &FloatImage) -> Result<Vec<u8>, ImageError> {
+    let mut bytes = Vec::new();
+
+    let mut encoder = png::Encoder::new(&mut bytes, image.width(), image.height());
+    encoder.set_color(png::ColorType::RGB);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data = image
+        .pixels()
+        .iter()
+        .map(Color3::to_srgb)
+        .flat_map(|color| [color.r(), color.g(), color.b()])
+        .map(|value| (value * 255.0).round() as u8)
+        .collect::<Vec<_>>();
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(ImageError::PngEncoding)?;
+
+    Ok(bytes)
+}
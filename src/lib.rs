@@ -1,11 +1,20 @@
 pub mod hittable;
+pub mod image_diff;
+pub mod image_io;
 pub mod material;
+pub mod mesh_io;
+pub mod packet;
+pub mod render;
+pub mod spectrum;
 
 mod camera;
 pub use camera::Camera;
 
+mod color;
+pub use color::Color3;
+
 mod ray;
 pub use ray::Ray;
 
 mod vec3;
-pub use vec3::{Color3, Point3, Vec3};
+pub use vec3::{Point3, Vec3};
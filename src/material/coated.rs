@@ -0,0 +1,321 @@
+use std::ops;
+
+use rand::{Rng, RngCore};
+
+use super::{thin_film::fresnel_dielectric, Material};
+use crate::{
+    hittable::HitRecord,
+    spectrum::{SampledSpectrum, SampledWavelengths},
+    Color3, Ray,
+};
+
+/// the number of times the light may bounce between the base and the coat before it is given up
+const MAX_INTERNAL_BOUNCES: u32 = 64;
+
+/// A material under a clear varnish (e.g. car paint, or lacquered wood): a thin dielectric layer
+/// on top of any base material.
+/// The coat reflects a part of the light (more at grazing angles, Fresnel law), the rest is
+/// refracted to the base. The light the base scatters back leaves through the coat,
+/// or is reflected inside it towards the base again. The light a transmissive base
+/// (e.g. glass) lets through continues below the surface, refracted from the coat into the base.
+/// The light can leave a coated object from inside, through the base then the coat
+#[derive(Debug)]
+pub struct Coated {
+    base: Box<dyn Material>,
+    /// the index of refraction of the coat
+    refraction_index: f64,
+}
+
+impl Coated {
+    pub fn new(base: impl Material + 'static, refraction_index: f64) -> Self {
+        Self {
+            base: Box::new(base),
+            refraction_index,
+        }
+    }
+
+    /// the random walk of the light through the layers, for any kind of attenuation (`one` lets it all through).
+    /// The coat is thin: the light enters and leaves it at the hit point.
+    /// The light coming from the air meets the coat first, the light coming from inside the object
+    /// (through a transmissive base) meets the base first.
+    /// `scatter_base` scatters the light on the base, seen from the coat
+    fn scatter_layers<S: Copy + ops::Mul<Output = S>>(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        one: S,
+        mut scatter_base: impl FnMut(&Ray, &HitRecord, &mut dyn RngCore) -> (Ray, S, bool),
+        rng: &mut dyn RngCore,
+    ) -> (Ray, S, bool) {
+        // the normal going from the base to the air
+        let outward = if hit_record.front_face {
+            hit_record.normal
+        } else {
+            -hit_record.normal
+        };
+        // the base as the light in the coat sees it
+        let base_record = HitRecord {
+            front_face: true,
+            normal: outward,
+            ..*hit_record
+        };
+        // the scattered rays are as long as the incoming one, the base measures distances with them
+        let length = ray_in.direction().mag();
+
+        let (mut scattered, mut attenuation, mut is_reflected) = if hit_record.front_face {
+            // the light coming from the air meets the top of the coat
+            let unit_direction = ray_in.direction() / length;
+            let cos_theta = (-unit_direction).dot(&outward).min(1.0);
+            if fresnel_dielectric(cos_theta, 1.0, self.refraction_index) > rng.gen() {
+                // reflected by the coat, whatever the color of the base
+                let ray_scattered =
+                    Ray::new(&hit_record.point, &ray_in.direction().reflect(&outward));
+                return (ray_scattered, one, true);
+            }
+
+            let direction = unit_direction.refract(&outward, 1.0 / self.refraction_index) * length;
+            scatter_base(&Ray::new(&hit_record.point, &direction), &base_record, rng)
+        } else {
+            // the light coming from inside the object meets the bottom of the base
+            scatter_base(ray_in, hit_record, rng)
+        };
+
+        for _ in 0..MAX_INTERNAL_BOUNCES {
+            if !is_reflected {
+                return (scattered, attenuation, false);
+            }
+
+            // the light going down went through the base, or stays inside the object
+            let up = scattered.direction().normalize();
+            let cos_inside = up.dot(&outward);
+            if cos_inside <= 0.0 {
+                return (scattered, attenuation, true);
+            }
+
+            // the light going up meets the top of the coat
+            if fresnel_dielectric(cos_inside, self.refraction_index, 1.0) <= rng.gen() {
+                let direction = up.refract(&-outward, self.refraction_index) * length;
+                return (Ray::new(&hit_record.point, &direction), attenuation, true);
+            }
+
+            let direction = up.reflect(&outward) * length;
+            let base_attenuation;
+            (scattered, base_attenuation, is_reflected) =
+                scatter_base(&Ray::new(&hit_record.point, &direction), &base_record, rng);
+            attenuation = attenuation * base_attenuation;
+        }
+
+        // trapped between the layers
+        (Ray::new(&hit_record.point, &outward), attenuation, false)
+    }
+}
+
+impl Material for Coated {
+    fn scatter(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, Color3, bool) {
+        self.scatter_layers(
+            ray_in,
+            hit_record,
+            Color3::white(),
+            |ray, base_record, rng| {
+                self.base
+                    .scatter_from_medium(ray, base_record, self.refraction_index, rng)
+            },
+            rng,
+        )
+    }
+
+    fn scatter_spectral(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, SampledSpectrum, bool) {
+        self.scatter_layers(
+            ray_in,
+            hit_record,
+            SampledSpectrum::constant(1.0),
+            |ray, base_record, rng| {
+                self.base.scatter_spectral_from_medium(
+                    ray,
+                    base_record,
+                    wavelengths,
+                    self.refraction_index,
+                    rng,
+                )
+            },
+            rng,
+        )
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color3 {
+        // the coat is clear
+        self.base.albedo(hit_record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::material::{
+        testing::{furnace, hit_record, incoming_ray, rng},
+        Dielectric, Lambertian, Metal,
+    };
+
+    #[test]
+    fn it_should_conserve_energy_in_a_white_furnace() {
+        let varnished = Coated::new(Lambertian::new(&Color3::white()), 1.5);
+
+        // the light the coat does not reflect ends up leaving it after bouncing on the base
+        for cos_theta in [1.0, 0.7, 0.3, 0.05] {
+            let energy = furnace(&varnished, &incoming_ray(cos_theta), true, 1000);
+            assert!(energy.distance(&Color3::white()) < 1e-9, "{energy:?}");
+        }
+
+        // or goes through a transmissive base
+        let coated_glass = Coated::new(Dielectric::new(1.5), 1.5);
+        for cos_theta in [1.0, 0.7, 0.3, 0.05] {
+            let energy = furnace(&coated_glass, &incoming_ray(cos_theta), true, 1000);
+            assert!(energy.distance(&Color3::white()) < 1e-9, "{energy:?}");
+        }
+    }
+
+    #[test]
+    fn it_should_reflect_white_highlights_over_the_base_color() {
+        let red = Color3::new(0.8, 0.1, 0.1);
+        let car_paint = Coated::new(Lambertian::new(&red), 1.5);
+        let hit_record = hit_record(&car_paint, true);
+        let mut rng = rng();
+
+        for cos_theta in [1.0, 0.3, 0.05] {
+            let ray_in = incoming_ray(cos_theta);
+            let mirror = ray_in.direction().reflect(&hit_record.normal);
+
+            let nb_samples = 100_000;
+            let nb_mirrored = (0..nb_samples)
+                .filter(|_| {
+                    let (scattered, attenuation, _) =
+                        car_paint.scatter(&ray_in, &hit_record, &mut rng);
+                    let is_mirrored = (scattered.direction().normalize() - mirror).mag() < 1e-9;
+
+                    // the highlights are not tinted by the base
+                    assert!(!is_mirrored || attenuation == Color3::white());
+                    is_mirrored
+                })
+                .count();
+
+            // the coat reflects with the Fresnel probability, more at grazing angles
+            let expected = fresnel_dielectric(cos_theta, 1.0, 1.5);
+            let observed = nb_mirrored as f64 / nb_samples as f64;
+            assert!((observed - expected).abs() < 5e-3, "{observed} {expected}");
+        }
+    }
+
+    #[test]
+    fn it_should_coat_any_base() {
+        // a perfect mirror under a coat stays a perfect mirror, the light refracts in and out
+        let coated_mirror = Coated::new(Metal::new(&Color3::white(), 0.0), 1.5);
+        let hit_record = hit_record(&coated_mirror, true);
+        let mut rng = rng();
+
+        for cos_theta in [1.0, 0.7, 0.3] {
+            let ray_in = incoming_ray(cos_theta);
+            let mirror = ray_in.direction().reflect(&hit_record.normal);
+
+            for _ in 0..100 {
+                let (scattered, attenuation, is_reflected) =
+                    coated_mirror.scatter(&ray_in, &hit_record, &mut rng);
+                assert!(is_reflected);
+                assert_eq!(attenuation, Color3::white());
+                assert!((scattered.direction().normalize() - mirror).mag() < 1e-9);
+            }
+        }
+
+        // spectrally too
+        let mut wavelengths = SampledWavelengths::sample(0.5);
+        let (_, attenuation, _) = coated_mirror.scatter_spectral(
+            &incoming_ray(0.7),
+            &hit_record,
+            &mut wavelengths,
+            &mut rng,
+        );
+        assert!(attenuation
+            .values()
+            .iter()
+            .all(|value| (value - 1.0).abs() < 1e-9));
+    }
+
+    #[test]
+    fn it_should_refract_into_the_base_from_the_coat() {
+        // a coat of the same index as the glass below: the light goes from one to the other
+        // without any reflection nor deviation, as if the glass were bare
+        let coated_glass = Coated::new(Dielectric::new(1.5), 1.5);
+        let hit_record = hit_record(&coated_glass, true);
+        let mut rng = rng();
+
+        for cos_theta in [1.0, 0.7, 0.3] {
+            let ray_in = incoming_ray(cos_theta);
+            let mirror = ray_in.direction().reflect(&hit_record.normal);
+            let refracted = ray_in.direction().refract(&hit_record.normal, 1.0 / 1.5);
+
+            for _ in 0..1000 {
+                let (scattered, _, _) = coated_glass.scatter(&ray_in, &hit_record, &mut rng);
+                let direction = scattered.direction();
+                assert!(
+                    (direction - mirror).mag() < 1e-9 || (direction - refracted).mag() < 1e-9,
+                    "{direction:?} at a cosine of {cos_theta}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_let_the_light_out_of_a_coated_object() {
+        let coated_glass = Coated::new(Dielectric::new(1.5), 1.5);
+        let hit_record = hit_record(&coated_glass, false);
+        let mut rng = rng();
+
+        // from inside the glass, below the critical angle: the light leaves into the air,
+        // or is reflected back inside
+        let ray_in = incoming_ray(0.9);
+        let mirror = ray_in.direction().reflect(&hit_record.normal);
+        let refracted = ray_in.direction().refract(&hit_record.normal, 1.5);
+
+        let mut nb_refracted = 0;
+        for _ in 0..1000 {
+            let (scattered, _, is_reflected) = coated_glass.scatter(&ray_in, &hit_record, &mut rng);
+            let direction = scattered.direction();
+            assert!(is_reflected);
+            assert!((direction - mirror).mag() < 1e-9 || (direction - refracted).mag() < 1e-9);
+            if (direction - refracted).mag() < 1e-9 {
+                nb_refracted += 1;
+            }
+        }
+        assert!(
+            nb_refracted > 900,
+            "{nb_refracted} rays out of 1000 left the glass"
+        );
+    }
+
+    #[test]
+    fn it_should_absorb_light_with_the_distance_inside_the_base() {
+        let tinted = Dielectric::new(1.5).with_transmittance(&Color3::new(0.25, 0.5, 1.0), 2.0);
+        let coated = Coated::new(tinted, 1.5);
+        let mut rng = rng();
+
+        // a direction of length 2 and a hit at t = 1: a distance of 2 inside the glass,
+        // whether the light leaves it or is reflected back
+        let ray_in = incoming_ray(0.9);
+        let long_ray = Ray::new(&ray_in.origin(), &(ray_in.direction() * 2.0));
+        for _ in 0..100 {
+            let (_, attenuation, _) =
+                coated.scatter(&long_ray, &hit_record(&coated, false), &mut rng);
+            assert!(attenuation.distance(&Color3::new(0.25, 0.5, 1.0)) < 1e-12);
+        }
+    }
+}
@@ -1,18 +1,86 @@
-use rand::{rngs::ThreadRng, Rng};
+use rand::{Rng, RngCore};
 
-use super::Material;
-use crate::{hittable::HitRecord, Color3, Ray};
+use super::{thin_film::RGB_WAVELENGTHS, Material, RefractiveIndex, ThinFilm, D_LINE};
+use crate::{
+    hittable::HitRecord,
+    spectrum::{RgbSpectrum, SampledSpectrum, SampledWavelengths},
+    Color3, Ray, Vec3,
+};
 
-/// A Dielectric material that reflects and refracts light
+/// A Dielectric material that reflects and refracts light.
+/// The medium can absorb light (Beer-Lambert law): the light crossing it is attenuated
+/// exponentially with the distance traveled inside, so thick parts of tinted glass look darker.
+/// The objects must be closed, the distance is measured between the hits entering and leaving them
 #[derive(Debug)]
 pub struct Dielectric {
     // the index of refraction of the material ( c / v) with c, celrity, v, speed of light in the material
-    refraction_index: f64,
+    refraction_index: RefractiveIndex,
+    /// fraction of the light absorbed per unit of distance traveled inside, per channel (0 for clear glass)
+    absorption: Color3,
+    /// a film on the surface, whose interferences make the reflection iridescent
+    thin_film: Option<ThinFilm>,
 }
 
 impl Dielectric {
     pub fn new(refraction_index: f64) -> Self {
-        Self { refraction_index }
+        Self::with_dispersion(RefractiveIndex::Constant(refraction_index))
+    }
+
+    /// a dielectric whose index depends on the wavelength (e.g. `RefractiveIndex::bk7()`).
+    /// The dispersion only shows with the spectral integrator: rgb renders use the index of the d line
+    pub fn with_dispersion(refraction_index: RefractiveIndex) -> Self {
+        Self {
+            refraction_index,
+            absorption: Color3::black(),
+            thin_film: None,
+        }
+    }
+
+    /// the same dielectric, absorbing light with the given coefficients (per unit of distance)
+    pub fn with_absorption(self, absorption: &Color3) -> Self {
+        Self {
+            absorption: *absorption,
+            ..self
+        }
+    }
+
+    /// the same dielectric, tinted: `transmittance` is the color of white light
+    /// after traveling `distance` inside the medium
+    /// # panics
+    /// if the distance is not positive, or if a channel of the transmittance is not in (0, 1]
+    /// (an opaque medium has an infinite absorption, see `with_absorption`)
+    pub fn with_transmittance(self, transmittance: &Color3, distance: f64) -> Self {
+        assert!(distance > 0.0, "the reference distance must be positive");
+        assert!(
+            [transmittance.r(), transmittance.g(), transmittance.b()]
+                .iter()
+                .all(|channel| *channel > 0.0 && *channel <= 1.0),
+            "a transmittance is in (0, 1]"
+        );
+
+        self.with_absorption(&transmittance.map(|channel| -channel.ln() / distance))
+    }
+
+    /// the same dielectric, covered by a thin film (e.g. a soap bubble: a film of water around the air)
+    pub fn with_thin_film(self, thin_film: ThinFilm) -> Self {
+        Self {
+            thin_film: Some(thin_film),
+            ..self
+        }
+    }
+
+    /// the distance traveled inside the medium by a ray leaving it (0 when entering)
+    fn distance_inside(ray_in: &Ray, hit_record: &HitRecord) -> f64 {
+        if hit_record.front_face {
+            0.0
+        } else {
+            hit_record.t * ray_in.direction().mag()
+        }
+    }
+
+    /// the fraction of light crossing a unit of distance inside the medium
+    fn unit_transmittance(&self) -> Color3 {
+        self.absorption.map(|absorption| (-absorption).exp())
     }
 }
 
@@ -24,6 +92,90 @@ impl Dielectric {
 
         r1 + (1.0 - r1) * (1.0 - cosine).powi(5)
     }
+
+    /// the probability for a ray to be reflected rather than refracted,
+    /// knowing the cosine of its incidence angle and the ratio of indices.
+    /// Schlick's approximation is enough for bare glass, the exact Fresnel equations
+    /// are only needed under a thin film, where the interferences depend on them
+    fn reflection_probability(cos_theta: f64, refraction_ratio: f64) -> f64 {
+        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+
+        if refraction_ratio * sin_theta > 1.0 {
+            // total internal reflection
+            return 1.0;
+        }
+
+        Self::reflectance(cos_theta, refraction_ratio)
+    }
+
+    /// the ratio of the indices on both sides of the surface, for a dielectric of index
+    /// `refraction_index` in a medium of index `outer_index`
+    fn refraction_ratio(hit_record: &HitRecord, refraction_index: f64, outer_index: f64) -> f64 {
+        if hit_record.front_face {
+            outer_index / refraction_index
+        } else {
+            refraction_index / outer_index
+        }
+    }
+
+    /// the reflected or the refracted direction, chosen with the probability of reflection
+    fn scatter_direction(
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        refraction_ratio: f64,
+        rng: &mut dyn RngCore,
+    ) -> Vec3 {
+        let unit_direction = ray_in.direction().normalize();
+
+        let cos_theta = (-unit_direction).dot(&hit_record.normal).min(1.0);
+        let reflection_probability = Self::reflection_probability(cos_theta, refraction_ratio);
+
+        if reflection_probability >= 1.0 || reflection_probability > rng.gen() {
+            unit_direction.reflect(&hit_record.normal)
+        } else {
+            unit_direction.refract(&hit_record.normal, refraction_ratio)
+        }
+    }
+
+    /// the reflected or the refracted direction, through a surface whose reflectance depends on
+    /// the wavelength: the direction is chosen with the average reflectance of the wavelengths,
+    /// then each wavelength is weighted by its own reflectance (or transmittance).
+    /// `reflectances` gives them knowing the cosine of the angle of incidence outside the medium
+    fn scatter_direction_weighted<const N: usize>(
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        refraction_ratio: f64,
+        reflectances: impl Fn(f64) -> [f64; N],
+        rng: &mut dyn RngCore,
+    ) -> (Vec3, [f64; N]) {
+        let unit_direction = ray_in.direction().normalize();
+        let reflected = unit_direction.reflect(&hit_record.normal);
+
+        let cos_theta = (-unit_direction).dot(&hit_record.normal).min(1.0);
+        let sin_squared = refraction_ratio * refraction_ratio * (1.0 - cos_theta * cos_theta);
+        if sin_squared > 1.0 {
+            // total internal reflection
+            return (reflected, [1.0; N]);
+        }
+
+        // a film is as reflective from both sides: leaving the medium, the angle outside is the refracted one
+        let cos_outside = if hit_record.front_face {
+            cos_theta
+        } else {
+            (1.0 - sin_squared).sqrt()
+        };
+        let reflectances = reflectances(cos_outside);
+        let probability = reflectances.iter().sum::<f64>() / N as f64;
+
+        if probability > rng.gen() {
+            (reflected, reflectances.map(|r| r / probability))
+        } else {
+            (
+                unit_direction.refract(&hit_record.normal, refraction_ratio),
+                reflectances.map(|r| (1.0 - r) / (1.0 - probability)),
+            )
+        }
+    }
 }
 
 impl Material for Dielectric {
@@ -31,30 +183,366 @@ impl Material for Dielectric {
         &self,
         ray_in: &Ray,
         hit_record: &HitRecord,
-        rng: &mut ThreadRng,
+        rng: &mut dyn RngCore,
     ) -> (Ray, Color3, bool) {
-        let refraction_ratio = if hit_record.front_face {
-            1.0 / self.refraction_index
-        } else {
-            self.refraction_index
+        self.scatter_from_medium(ray_in, hit_record, 1.0, rng)
+    }
+
+    fn scatter_spectral(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, SampledSpectrum, bool) {
+        self.scatter_spectral_from_medium(ray_in, hit_record, wavelengths, 1.0, rng)
+    }
+
+    /// the film, if any, is taken as lying in the air
+    fn scatter_from_medium(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        outer_index: f64,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, Color3, bool) {
+        let refraction_index = self.refraction_index.at(D_LINE);
+        let refraction_ratio = Self::refraction_ratio(hit_record, refraction_index, outer_index);
+        let (direction, weights) = match &self.thin_film {
+            None => (
+                Self::scatter_direction(ray_in, hit_record, refraction_ratio, rng),
+                Color3::white(),
+            ),
+            Some(film) => {
+                let (direction, [r, g, b]) = Self::scatter_direction_weighted(
+                    ray_in,
+                    hit_record,
+                    refraction_ratio,
+                    |cos_theta| {
+                        RGB_WAVELENGTHS.map(|wavelength| {
+                            film.reflectance_over_dielectric(
+                                cos_theta,
+                                refraction_index,
+                                wavelength,
+                            )
+                        })
+                    },
+                    rng,
+                );
+                (direction, Color3::new(r, g, b))
+            }
         };
+        let ray_scattered = Ray::new(&hit_record.point, &direction);
 
-        let unit_direction = ray_in.direction().normalize();
+        let distance = Self::distance_inside(ray_in, hit_record);
+        let attenuation = self
+            .unit_transmittance()
+            .map(|transmittance| transmittance.powf(distance))
+            * weights;
 
-        let cos_theta = (unit_direction * -1.0).dot(&hit_record.normal).min(1.0);
-        let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+        (ray_scattered, attenuation, true)
+    }
 
-        let cannot_refract = refraction_ratio * sin_theta > 1.0;
+    fn scatter_spectral_from_medium(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        outer_index: f64,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, SampledSpectrum, bool) {
+        // every wavelength takes its own direction: only the hero is followed
+        if self.refraction_index.is_dispersive() {
+            wavelengths.terminate_secondary();
+        }
+
+        let refraction_index = self.refraction_index.at(wavelengths.hero());
+        let refraction_ratio = Self::refraction_ratio(hit_record, refraction_index, outer_index);
+        let (direction, weights) = match &self.thin_film {
+            None => (
+                Self::scatter_direction(ray_in, hit_record, refraction_ratio, rng),
+                SampledSpectrum::constant(1.0),
+            ),
+            Some(film) => {
+                let lambdas = *wavelengths.lambdas();
+                let (direction, weights) = Self::scatter_direction_weighted(
+                    ray_in,
+                    hit_record,
+                    refraction_ratio,
+                    |cos_theta| {
+                        lambdas.map(|wavelength| {
+                            film.reflectance_over_dielectric(
+                                cos_theta,
+                                refraction_index,
+                                wavelength,
+                            )
+                        })
+                    },
+                    rng,
+                );
+                (direction, SampledSpectrum::new(weights))
+            }
+        };
+        let ray_scattered = Ray::new(&hit_record.point, &direction);
+
+        // the transmittance through a unit of distance is a color: it is upsampled like albedos
+        let distance = Self::distance_inside(ray_in, hit_record);
+        let attenuation = RgbSpectrum::new(&self.unit_transmittance())
+            .sample(wavelengths)
+            .map(|transmittance| transmittance.powf(distance))
+            * weights;
+
+        (ray_scattered, attenuation, true)
+    }
+
+    fn albedo(&self, _hit_record: &HitRecord) -> Color3 {
+        // the tint of a unit of distance (white for clear glass)
+        self.unit_transmittance()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::material::testing::{
+        chi_square, chi_square_critical_value, furnace, hit_record, incoming_ray, rng,
+    };
+    use crate::Vec3;
+
+    #[test]
+    fn it_should_conserve_energy_in_a_white_furnace() {
+        let glass = Dielectric::new(1.5);
+
+        // clear glass reflects or refracts every ray, from outside and from inside
+        for cos_theta in [1.0, 0.7, 0.3, 0.05] {
+            for front_face in [true, false] {
+                let energy = furnace(&glass, &incoming_ray(cos_theta), front_face, 1000);
+                assert!(energy.distance(&Color3::white()) < 1e-9);
+            }
+        }
+    }
 
-        let direction =
-            if cannot_refract || Self::reflectance(cos_theta, refraction_ratio) > rng.gen() {
-                unit_direction.reflect(&hit_record.normal)
+    #[test]
+    fn it_should_refract_reciprocally() {
+        let glass = Dielectric::new(1.5);
+        let mut rng = rng();
+
+        for cos_theta in [1.0, 0.7, 0.3] {
+            let ray_in = incoming_ray(cos_theta);
+
+            // keeping a refracted ray (pointing inside, along -y)
+            let refracted = loop {
+                let (scattered, _, _) = glass.scatter(&ray_in, &hit_record(&glass, true), &mut rng);
+                if scattered.direction().y() < 0.0 {
+                    break scattered.direction();
+                }
+            };
+
+            // the reverse ray leaves the medium: it hits the back face
+            let reversed = Ray::new(&-refracted, &-refracted);
+            let back_face = HitRecord {
+                normal: Vec3::new(0.0, -1.0, 0.0),
+                ..hit_record(&glass, false)
+            };
+            let refracted_back = loop {
+                let (scattered, _, _) = glass.scatter(&reversed, &back_face, &mut rng);
+                if scattered.direction().y() > 0.0 {
+                    break scattered.direction();
+                }
+            };
+
+            let expected = -ray_in.direction().normalize();
+            assert!((refracted_back.normalize() - expected).mag() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn it_should_reflect_with_the_fresnel_probability() {
+        let refraction_index = 1.5;
+        let glass = Dielectric::new(refraction_index);
+        let mut rng = rng();
+        let nb_samples = 100_000;
+
+        // from outside, and from inside below the critical angle (cosine ~0.75)
+        for (cos_theta, front_face) in [(1.0, true), (0.3, true), (0.1, true), (0.9, false)] {
+            let ray_in = incoming_ray(cos_theta);
+            let hit_record = hit_record(&glass, front_face);
+
+            let nb_reflected = (0..nb_samples)
+                .filter(|_| {
+                    let (scattered, _, _) = glass.scatter(&ray_in, &hit_record, &mut rng);
+                    scattered.direction().y() > 0.0
+                })
+                .count() as u64;
+
+            let refraction_ratio = if front_face {
+                1.0 / refraction_index
             } else {
-                unit_direction.refract(&hit_record.normal, refraction_ratio)
+                refraction_index
             };
+            let probability = Dielectric::reflection_probability(cos_theta, refraction_ratio);
 
-        let ray_scattered = Ray::new(&hit_record.point, &direction);
+            let statistic = chi_square(
+                &[nb_reflected, nb_samples - nb_reflected],
+                &[probability, 1.0 - probability],
+            );
+            assert!(
+                statistic < chi_square_critical_value(1),
+                "chi-square {statistic} at a cosine of {cos_theta}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_disperse_white_light() {
+        let prism = Dielectric::with_dispersion(RefractiveIndex::bk7());
+        let ray_in = incoming_ray(0.5);
+        let mut rng = rng();
+
+        // the direction of the refracted hero wavelength (pointing inside, along -y)
+        let mut refract = |u: f64| {
+            loop {
+                let mut wavelengths = SampledWavelengths::sample(u);
+                let (scattered, attenuation, _) = prism.scatter_spectral(
+                    &ray_in,
+                    &hit_record(&prism, true),
+                    &mut wavelengths,
+                    &mut rng,
+                );
+
+                // the other wavelengths can not follow the hero
+                assert!(wavelengths.is_secondary_terminated());
+                assert_eq!(attenuation, SampledSpectrum::constant(1.0));
+
+                if scattered.direction().y() < 0.0 {
+                    break (wavelengths.hero(), scattered.direction().normalize());
+                }
+            }
+        };
+
+        // blue light bends closer to the normal than red light
+        let (blue, blue_direction) = refract(0.2);
+        let (red, red_direction) = refract(0.8);
+        assert!(blue < 500.0 && red > 600.0);
+        assert!(blue_direction.x() < red_direction.x());
+
+        // Snell's law for each wavelength
+        let sin_theta = (1.0 - 0.5_f64 * 0.5).sqrt();
+        let index = RefractiveIndex::bk7();
+        assert!((blue_direction.x() - sin_theta / index.at(blue)).abs() < 1e-9);
+        assert!((red_direction.x() - sin_theta / index.at(red)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn it_should_follow_every_wavelength_without_dispersion() {
+        let glass = Dielectric::new(1.5);
+        let mut wavelengths = SampledWavelengths::sample(0.3);
+
+        glass.scatter_spectral(
+            &incoming_ray(0.5),
+            &hit_record(&glass, true),
+            &mut wavelengths,
+            &mut rng(),
+        );
+        assert!(!wavelengths.is_secondary_terminated());
+    }
+
+    #[test]
+    fn it_should_absorb_light_with_the_distance_inside() {
+        let tinted = Dielectric::new(1.5).with_transmittance(&Color3::new(0.25, 0.5, 1.0), 2.0);
+        let ray_in = incoming_ray(0.7);
+        let mut rng = rng();
+
+        // entering the medium: nothing was absorbed yet
+        let (_, attenuation, _) = tinted.scatter(&ray_in, &hit_record(&tinted, true), &mut rng);
+        assert_eq!(attenuation, Color3::white());
+
+        // even for an opaque medium
+        let opaque = Dielectric::new(1.5).with_absorption(&Color3::new(
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::INFINITY,
+        ));
+        let (_, attenuation, _) = opaque.scatter(&ray_in, &hit_record(&opaque, true), &mut rng);
+        assert_eq!(attenuation, Color3::white());
+
+        // leaving it, after a distance of 1 then 4 (the direction of the ray is a unit vector)
+        for (t, expected) in [
+            (1.0, Color3::new(0.5, 0.5_f64.sqrt(), 1.0)),
+            (4.0, Color3::new(0.0625, 0.25, 1.0)),
+        ] {
+            let leaving = HitRecord {
+                t,
+                ..hit_record(&tinted, false)
+            };
+            let (_, attenuation, _) = tinted.scatter(&ray_in, &leaving, &mut rng);
+            assert!(
+                attenuation.distance(&expected) < 1e-12,
+                "{attenuation:?} after {t}"
+            );
+        }
+
+        // the distance is in world units, not in lengths of the direction
+        let long_ray = Ray::new(&ray_in.origin(), &(ray_in.direction() * 2.0));
+        let (_, attenuation, _) = tinted.scatter(&long_ray, &hit_record(&tinted, false), &mut rng);
+        assert!(attenuation.distance(&Color3::new(0.25, 0.5, 1.0)) < 1e-12);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_reject_a_transmittance_above_one() {
+        Dielectric::new(1.5).with_transmittance(&Color3::new(0.5, 1.5, 0.5), 1.0);
+    }
+
+    #[test]
+    fn it_should_absorb_light_spectrally() {
+        let grey = Dielectric::new(1.5).with_absorption(&Color3::new(0.5, 0.5, 0.5));
+        let leaving = HitRecord {
+            t: 3.0,
+            ..hit_record(&grey, false)
+        };
+        let mut wavelengths = SampledWavelengths::sample(0.6);
+
+        let (_, attenuation, _) =
+            grey.scatter_spectral(&incoming_ray(1.0), &leaving, &mut wavelengths, &mut rng());
+
+        // a grey medium absorbs every wavelength alike
+        for value in attenuation.values() {
+            assert!((value - (-1.5_f64).exp()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn it_should_reflect_iridescent_colors_through_a_thin_film() {
+        let bubble = Dielectric::new(1.0).with_thin_film(ThinFilm::new(400.0, 1.33));
+
+        for cos_theta in [1.0, 0.7, 0.3] {
+            let ray_in = incoming_ray(cos_theta);
+            let expected = RGB_WAVELENGTHS.map(|wavelength| {
+                ThinFilm::new(400.0, 1.33).reflectance_over_dielectric(cos_theta, 1.0, wavelength)
+            });
+
+            // the light is split between the reflection and the refraction without loss, on average
+            let energy = furnace(&bubble, &ray_in, true, 10_000);
+            assert!(energy.distance(&Color3::white()) < 2e-2, "{energy:?}");
 
-        (ray_scattered, Color3::white(), true)
+            // and the average reflected light is the reflectance of the film
+            let hit_record = hit_record(&bubble, true);
+            let mut rng = rng();
+            let nb_samples = 100_000;
+            let mut reflected = Color3::black();
+            for _ in 0..nb_samples {
+                let (scattered, attenuation, _) = bubble.scatter(&ray_in, &hit_record, &mut rng);
+                if scattered.direction().y() > 0.0 {
+                    reflected += attenuation;
+                }
+            }
+            let reflected = reflected / nb_samples as f64;
+            let expected = Color3::new(expected[0], expected[1], expected[2]);
+            assert!(
+                reflected.distance(&expected) < 1e-2,
+                "{reflected:?} {expected:?}"
+            );
+        }
     }
 }
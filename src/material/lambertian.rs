@@ -1,16 +1,26 @@
-use rand::rngs::ThreadRng;
+use rand::RngCore;
 
-use super::Material;
+use super::{Material, Texture};
 use crate::{hittable::HitRecord, Color3, Ray, Vec3};
 
 #[derive(Debug)]
 pub struct Lambertian {
-    albedo: Color3,
+    albedo: Box<dyn Texture>,
 }
 
 impl Lambertian {
     pub fn new(color: &Color3) -> Self {
-        Self { albedo: *color }
+        Self {
+            albedo: Box::new(*color),
+        }
+    }
+
+    /// a diffuse material whose color varies over the surface
+    /// (e.g. `VertexColors` for scanned meshes)
+    pub fn textured(texture: impl Texture + 'static) -> Self {
+        Self {
+            albedo: Box::new(texture),
+        }
     }
 }
 
@@ -19,7 +29,7 @@ impl Material for Lambertian {
         &self,
         _ray_in: &Ray, //? the incomming ray is not used for lambertian materials
         hit_record: &HitRecord,
-        rng: &mut ThreadRng,
+        rng: &mut dyn RngCore,
     ) -> (Ray, Color3, bool) {
         let mut scatter_direction = hit_record.normal + Vec3::new_randow_unit_vector(rng);
 
@@ -30,6 +40,82 @@ impl Material for Lambertian {
 
         let ray_scattered = Ray::new(&hit_record.point, &scatter_direction);
 
-        (ray_scattered, self.albedo, true)
+        (ray_scattered, self.albedo.value(hit_record), true)
+    }
+
+    fn albedo(&self, hit_record: &HitRecord) -> Color3 {
+        self.albedo.value(hit_record)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::material::testing::{
+        bin, chi_square, chi_square_critical_value, furnace, hit_record, incoming_ray, rng,
+    };
+
+    #[test]
+    fn it_should_conserve_energy_in_a_white_furnace() {
+        let white = Lambertian::new(&Color3::white());
+        let red = Lambertian::new(&Color3::new(0.8, 0.1, 0.1));
+
+        for cos_theta in [1.0, 0.5, 0.05] {
+            // a white diffuse surface reflects every ray: it disappears in a white furnace
+            let white_energy = furnace(&white, &incoming_ray(cos_theta), true, 1000);
+            assert!(white_energy.distance(&Color3::white()) < 1e-9);
+
+            let red_energy = furnace(&red, &incoming_ray(cos_theta), true, 1000);
+            assert!(red_energy.distance(&Color3::new(0.8, 0.1, 0.1)) < 1e-9);
+        }
+    }
+
+    #[test]
+    fn it_should_scatter_above_the_surface() {
+        let material = Lambertian::new(&Color3::white());
+        let hit_record = hit_record(&material, true);
+        let mut rng = rng();
+
+        for _ in 0..10_000 {
+            let (scattered, _, _) = material.scatter(&incoming_ray(0.3), &hit_record, &mut rng);
+            assert!(scattered.direction().dot(&hit_record.normal) >= 0.0);
+        }
+    }
+
+    /// the scattered directions follow the cosine distribution cos(theta) / pi, whatever the incoming direction.
+    /// The brdf is then the constant albedo / pi, which satisfies the Helmholtz reciprocity
+    #[test]
+    fn it_should_follow_a_cosine_distribution_for_every_incoming_direction() {
+        let material = Lambertian::new(&Color3::white());
+        let hit_record = hit_record(&material, true);
+        let mut rng = rng();
+
+        // with a cosine distribution, cos²(theta) and phi are uniform
+        let nb_bins = 10;
+        let nb_samples = 100_000;
+
+        for cos_theta in [1.0, 0.5, 0.05] {
+            let ray_in = incoming_ray(cos_theta);
+            let mut observed = vec![0; nb_bins * nb_bins];
+
+            for _ in 0..nb_samples {
+                let (scattered, _, _) = material.scatter(&ray_in, &hit_record, &mut rng);
+                let direction = scattered.direction().normalize();
+
+                let cos_squared = direction.y() * direction.y();
+                let phi = (direction.z().atan2(direction.x()) + PI) / (2.0 * PI);
+                observed[bin(cos_squared, nb_bins) * nb_bins + bin(phi, nb_bins)] += 1;
+            }
+
+            let expected = vec![1.0 / (nb_bins * nb_bins) as f64; nb_bins * nb_bins];
+            let statistic = chi_square(&observed, &expected);
+
+            assert!(
+                statistic < chi_square_critical_value(nb_bins * nb_bins - 1),
+                "chi-square {statistic} for an incoming cosine of {cos_theta}"
+            );
+        }
     }
 }
@@ -1,8 +1,12 @@
 use std::fmt::Debug;
 
-use rand::rngs::ThreadRng;
+use rand::RngCore;
 
-use crate::{hittable::HitRecord, Color3, Ray};
+use crate::{
+    hittable::HitRecord,
+    spectrum::{RgbSpectrum, SampledSpectrum, SampledWavelengths},
+    Color3, Ray,
+};
 
 pub trait Material: Debug + Sync {
     /// # returns
@@ -13,6 +17,54 @@ pub trait Material: Debug + Sync {
         &self,
         ray_in: &Ray,
         hit_record: &HitRecord,
-        rng: &mut ThreadRng,
+        rng: &mut dyn RngCore,
     ) -> (Ray, Color3, bool);
+
+    /// the same scattering, for light of the wavelengths of a spectral path.
+    /// The default upsamples the rgb attenuation. Materials whose scattering depends on the wavelength
+    /// may terminate the secondary wavelengths, and scatter the hero wavelength only
+    fn scatter_spectral(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, SampledSpectrum, bool) {
+        let (scattered, attenuation, is_reflected) = self.scatter(ray_in, hit_record, rng);
+
+        (
+            scattered,
+            RgbSpectrum::new(&attenuation).sample(wavelengths),
+            is_reflected,
+        )
+    }
+
+    /// the same scattering, for light coming from a medium of index `outer_index` rather than
+    /// from the air (e.g. from the coat of a `Coated` material).
+    /// Only materials refracting the light depend on the medium: the default ignores it
+    fn scatter_from_medium(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        _outer_index: f64,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, Color3, bool) {
+        self.scatter(ray_in, hit_record, rng)
+    }
+
+    /// the spectral scattering, for light coming from a medium of index `outer_index`
+    fn scatter_spectral_from_medium(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        _outer_index: f64,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, SampledSpectrum, bool) {
+        self.scatter_spectral(ray_in, hit_record, wavelengths, rng)
+    }
+
+    /// the base color of the material at the hit point, independent from the lighting
+    /// (used by the albedo output variable and by the denoiser)
+    fn albedo(&self, hit_record: &HitRecord) -> Color3;
 }
@@ -1,7 +1,11 @@
-use rand::rngs::ThreadRng;
+use rand::RngCore;
 
-use super::Material;
-use crate::{hittable::HitRecord, Color3, Ray, Vec3};
+use super::{thin_film::RGB_WAVELENGTHS, Material, ThinFilm};
+use crate::{
+    hittable::HitRecord,
+    spectrum::{RgbSpectrum, SampledSpectrum, SampledWavelengths},
+    Color3, Ray, Vec3,
+};
 
 /// A metal material that reflects light
 #[derive(Debug)]
@@ -10,6 +14,8 @@ pub struct Metal {
     albedo: Color3,
     /// The greater, the blurrer the reflection. Should be 0 and 1
     fuzziness: f64,
+    /// an oxide or a coating on the metal, whose interferences make the reflection iridescent
+    thin_film: Option<ThinFilm>,
 }
 
 impl Metal {
@@ -17,8 +23,35 @@ impl Metal {
         Self {
             albedo: *color,
             fuzziness,
+            thin_film: None,
         }
     }
+
+    /// the same metal, covered by a thin film (e.g. the oxide layer of heated steel, or of titanium)
+    pub fn with_thin_film(self, thin_film: ThinFilm) -> Self {
+        Self {
+            thin_film: Some(thin_film),
+            ..self
+        }
+    }
+
+    /// the fuzzy reflection of the ray, and if it is still above the surface
+    fn reflect(&self, ray_in: &Ray, hit_record: &HitRecord, rng: &mut dyn RngCore) -> (Ray, bool) {
+        // reflecting the incoming ray along the the hit normal
+        let reflected = ray_in.direction().normalize().reflect(&hit_record.normal);
+
+        let random_fuziness_direction = Vec3::new_randow_in_unit_sphere(rng) * self.fuzziness;
+        let ray_scattered = Ray::new(&hit_record.point, &(reflected + random_fuziness_direction));
+
+        let is_reflected = ray_scattered.direction().dot(&hit_record.normal) > 0.0;
+
+        (ray_scattered, is_reflected)
+    }
+
+    /// the cosine of the angle of incidence, on which the reflectance of the film depends
+    fn cos_theta(ray_in: &Ray, hit_record: &HitRecord) -> f64 {
+        (-ray_in.direction().normalize()).dot(&hit_record.normal)
+    }
 }
 
 impl Material for Metal {
@@ -26,16 +59,197 @@ impl Material for Metal {
         &self,
         ray_in: &Ray,
         hit_record: &HitRecord,
-        rng: &mut ThreadRng,
+        rng: &mut dyn RngCore,
     ) -> (Ray, Color3, bool) {
-        // reflecting the incoming ray along the the hit normal
-        let reflected = ray_in.direction().normalize().reflect(&hit_record.normal);
+        let (ray_scattered, is_reflected) = self.reflect(ray_in, hit_record, rng);
 
-        let random_fuziness_direction = Vec3::new_randow_in_unit_sphere(rng) * self.fuzziness;
-        let ray_scattered = Ray::new(&hit_record.point, &(reflected + random_fuziness_direction));
+        let attenuation = match &self.thin_film {
+            None => self.albedo,
+            Some(film) => {
+                let cos_theta = Self::cos_theta(ray_in, hit_record);
+                let [r, g, b] = RGB_WAVELENGTHS;
 
-        let is_reflected = ray_scattered.direction().dot(&hit_record.normal) > 0.0;
+                Color3::new(
+                    film.reflectance_over_conductor(cos_theta, self.albedo.r(), r),
+                    film.reflectance_over_conductor(cos_theta, self.albedo.g(), g),
+                    film.reflectance_over_conductor(cos_theta, self.albedo.b(), b),
+                )
+            }
+        };
+
+        (ray_scattered, attenuation, is_reflected)
+    }
+
+    fn scatter_spectral(
+        &self,
+        ray_in: &Ray,
+        hit_record: &HitRecord,
+        wavelengths: &mut SampledWavelengths,
+        rng: &mut dyn RngCore,
+    ) -> (Ray, SampledSpectrum, bool) {
+        let (ray_scattered, is_reflected) = self.reflect(ray_in, hit_record, rng);
+
+        let albedo = RgbSpectrum::new(&self.albedo).sample(wavelengths);
+        let attenuation = match &self.thin_film {
+            None => albedo,
+            Some(film) => {
+                let cos_theta = Self::cos_theta(ray_in, hit_record);
+                let lambdas = wavelengths.lambdas();
 
-        (ray_scattered, self.albedo, is_reflected)
+                SampledSpectrum::new(std::array::from_fn(|i| {
+                    film.reflectance_over_conductor(cos_theta, albedo.values()[i], lambdas[i])
+                }))
+            }
+        };
+
+        (ray_scattered, attenuation, is_reflected)
+    }
+
+    fn albedo(&self, _hit_record: &HitRecord) -> Color3 {
+        self.albedo
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::f64::consts::PI;
+
+    use super::*;
+    use crate::material::testing::{
+        bin, chi_square, chi_square_critical_value, furnace, hit_record, incoming_ray, rng,
+    };
+
+    #[test]
+    fn it_should_conserve_energy_in_a_white_furnace() {
+        let mirror = Metal::new(&Color3::white(), 0.0);
+        let fuzzy = Metal::new(&Color3::white(), 0.5);
+
+        // a perfect mirror reflects everything
+        for cos_theta in [1.0, 0.7, 0.3, 0.05] {
+            let energy = furnace(&mirror, &incoming_ray(cos_theta), true, 1000);
+            assert!(energy.distance(&Color3::white()) < 1e-9);
+        }
+
+        // with a fuzziness below 1, no ray can go below the surface at normal incidence
+        let normal = furnace(&fuzzy, &incoming_ray(1.0), true, 10_000).g();
+        assert!((normal - 1.0).abs() < 1e-9);
+
+        // fuzzy rays pushed below the surface are absorbed, so fuzzy metals get darker
+        // at grazing angles: close to the surface, about half of the fuzzy lobe is below it
+        let grazing = furnace(&fuzzy, &incoming_ray(0.05), true, 10_000).g();
+        assert!(
+            grazing < 0.8 && grazing < normal,
+            "{grazing} at grazing angles"
+        );
+    }
+
+    #[test]
+    fn it_should_reflect_reciprocally() {
+        let mirror = Metal::new(&Color3::white(), 0.0);
+        let hit_record = hit_record(&mirror, true);
+        let mut rng = rng();
+
+        for cos_theta in [1.0, 0.7, 0.3, 0.05] {
+            let ray_in = incoming_ray(cos_theta);
+            let (scattered, _, _) = mirror.scatter(&ray_in, &hit_record, &mut rng);
+
+            // following the light path backwards gives the same path
+            let reversed = Ray::new(&scattered.direction(), &-scattered.direction());
+            let (scattered_back, _, _) = mirror.scatter(&reversed, &hit_record, &mut rng);
+
+            let expected = -ray_in.direction().normalize();
+            assert!((scattered_back.direction().normalize() - expected).mag() < 1e-9);
+        }
+    }
+
+    /// the fuzzy directions are the mirror direction plus a random point of a ball of radius `fuzziness`
+    #[test]
+    fn it_should_follow_the_fuzzy_lobe_distribution() {
+        let fuzziness = 0.5;
+        let material = Metal::new(&Color3::white(), fuzziness);
+        let hit_record = hit_record(&material, true);
+        let mut rng = rng();
+
+        // at normal incidence, the mirror direction is the normal (+y).
+        // Binning the cosine of the angle with the mirror direction, and the azimuth around it
+        let min_cos = (1.0 - fuzziness * fuzziness).sqrt();
+        let nb_bins = 8;
+        let nb_samples = 100_000;
+
+        let mut observed = vec![0; nb_bins * nb_bins];
+        for _ in 0..nb_samples {
+            let (scattered, _, _) = material.scatter(&incoming_ray(1.0), &hit_record, &mut rng);
+            let direction = scattered.direction().normalize();
+
+            let cos = (direction.y() - min_cos) / (1.0 - min_cos);
+            let phi = (direction.z().atan2(direction.x()) + PI) / (2.0 * PI);
+            observed[bin(cos, nb_bins) * nb_bins + bin(phi, nb_bins)] += 1;
+        }
+
+        // a direction at an angle alpha of the center of the ball crosses it between the distances r1 and r2.
+        // The density per solid angle is the volume of that cone part over the volume of the ball:
+        // p = (r2³ - r1³) / 3 / (4/3 pi f³). Integrated over the azimuth: (r2³ - r1³) / (2 f³) per unit of cosine
+        let density = |cos: f64| {
+            let half_chord = (fuzziness * fuzziness - (1.0 - cos * cos)).max(0.0).sqrt();
+            let (r1, r2) = (cos - half_chord, cos + half_chord);
+
+            (r2.powi(3) - r1.powi(3)) / (2.0 * fuzziness.powi(3))
+        };
+
+        let bin_width = (1.0 - min_cos) / nb_bins as f64;
+        let steps = 1000;
+        let cos_probabilities = (0..nb_bins)
+            .map(|i| {
+                // midpoint integration
+                let start = min_cos + i as f64 * bin_width;
+                (0..steps)
+                    .map(|step| density(start + (step as f64 + 0.5) * bin_width / steps as f64))
+                    .sum::<f64>()
+                    * bin_width
+                    / steps as f64
+            })
+            .collect::<Vec<_>>();
+        assert!((cos_probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-3);
+
+        let expected = cos_probabilities
+            .iter()
+            .flat_map(|probability| vec![probability / nb_bins as f64; nb_bins])
+            .collect::<Vec<_>>();
+        let statistic = chi_square(&observed, &expected);
+
+        assert!(
+            statistic < chi_square_critical_value(nb_bins * nb_bins - 1),
+            "chi-square {statistic}"
+        );
+    }
+
+    #[test]
+    fn it_should_tint_the_reflection_with_a_thin_film() {
+        let steel = Color3::new(0.6, 0.6, 0.6);
+        let heated = Metal::new(&steel, 0.0).with_thin_film(ThinFilm::new(250.0, 2.5));
+        let ray_in = incoming_ray(0.7);
+        let hit_record = hit_record(&heated, true);
+
+        let (scattered, attenuation, _) = heated.scatter(&ray_in, &hit_record, &mut rng());
+        let (plain, _, _) = Metal::new(&steel, 0.0).scatter(&ray_in, &hit_record, &mut rng());
+
+        // the film changes the color of a grey metal, not the direction of the reflection
+        assert_eq!(scattered.direction(), plain.direction());
+        let channels = [attenuation.r(), attenuation.g(), attenuation.b()];
+        assert!(channels.iter().all(|channel| (0.0..=1.0).contains(channel)));
+        assert!(channels
+            .iter()
+            .any(|channel| (channel - channels[0]).abs() > 0.05));
+
+        // the spectral path follows the same reflectance
+        let mut wavelengths = SampledWavelengths::sample(0.3);
+        let (_, spectrum, _) =
+            heated.scatter_spectral(&ray_in, &hit_record, &mut wavelengths, &mut rng());
+        for (value, wavelength) in spectrum.values().iter().zip(wavelengths.lambdas()) {
+            let base = RgbSpectrum::new(&steel).at(*wavelength);
+            let expected =
+                ThinFilm::new(250.0, 2.5).reflectance_over_conductor(0.7, base, *wavelength);
+            assert!((value - expected).abs() < 1e-9);
+        }
     }
 }
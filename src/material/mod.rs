@@ -1,3 +1,4 @@
+#[allow(clippy::module_inception)]
 mod material;
 pub use material::Material;
 
@@ -9,3 +10,18 @@ pub use lambertian::Lambertian;
 
 mod dielectric;
 pub use dielectric::Dielectric;
+
+mod coated;
+pub use coated::Coated;
+
+mod thin_film;
+pub use thin_film::ThinFilm;
+
+mod refractive_index;
+pub use refractive_index::{RefractiveIndex, D_LINE};
+
+mod texture;
+pub use texture::{Texture, VertexColors};
+
+#[cfg(test)]
+mod testing;
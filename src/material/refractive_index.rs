@@ -0,0 +1,97 @@
+/// wavelength (in nanometers) of the helium d line, where the index of glasses is usually given
+pub const D_LINE: f64 = 587.56;
+/// wavelengths of the hydrogen F and C lines (blue and red), used to measure the dispersion
+const F_LINE: f64 = 486.13;
+const C_LINE: f64 = 656.27;
+
+/// How the index of refraction of a medium varies with the wavelength of light.
+/// A varying index disperses white light, like a prism.
+/// The equations take wavelengths in micrometers, as their coefficients are usually published
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefractiveIndex {
+    /// the same index for every wavelength
+    Constant(f64),
+    /// n = a + b / λ²
+    Cauchy { a: f64, b: f64 },
+    /// n² = 1 + Σ b_i λ² / (λ² - c_i)
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl RefractiveIndex {
+    /// Schott N-BK7, the common borosilicate crown glass
+    pub fn bk7() -> Self {
+        RefractiveIndex::Sellmeier {
+            b: [1.039_612_12, 0.231_792_344, 1.010_469_45],
+            c: [0.006_000_698_67, 0.020_017_914_4, 103.560_653],
+        }
+    }
+
+    /// Schott SF11, a dense flint glass: about 4 times the dispersion of BK7
+    pub fn sf11() -> Self {
+        RefractiveIndex::Sellmeier {
+            b: [1.737_596_95, 0.313_747_346, 1.898_781_01],
+            c: [0.013_188_707, 0.062_306_814_2, 155.236_29],
+        }
+    }
+
+    /// the index at a wavelength in nanometers
+    pub fn at(&self, lambda: f64) -> f64 {
+        let micrometers = lambda / 1000.0;
+        let squared = micrometers * micrometers;
+
+        match self {
+            RefractiveIndex::Constant(index) => *index,
+            RefractiveIndex::Cauchy { a, b } => a + b / squared,
+            RefractiveIndex::Sellmeier { b, c } => (1.0
+                + b.iter()
+                    .zip(c)
+                    .map(|(b, c)| b * squared / (squared - c))
+                    .sum::<f64>())
+            .sqrt(),
+        }
+    }
+
+    /// true if the index depends on the wavelength
+    pub fn is_dispersive(&self) -> bool {
+        !matches!(self, RefractiveIndex::Constant(_))
+    }
+
+    /// the Abbe number: the lower, the stronger the dispersion (infinite without dispersion)
+    pub fn abbe_number(&self) -> f64 {
+        (self.at(D_LINE) - 1.0) / (self.at(F_LINE) - self.at(C_LINE))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_match_the_glass_catalog() {
+        // published n_d and Abbe numbers
+        let bk7 = RefractiveIndex::bk7();
+        assert!((bk7.at(D_LINE) - 1.5168).abs() < 1e-4);
+        assert!((bk7.abbe_number() - 64.17).abs() < 0.1);
+
+        let sf11 = RefractiveIndex::sf11();
+        assert!((sf11.at(D_LINE) - 1.7847).abs() < 1e-3);
+        assert!((sf11.abbe_number() - 25.68).abs() < 0.2);
+    }
+
+    #[test]
+    fn it_should_bend_blue_light_more() {
+        for index in [
+            RefractiveIndex::bk7(),
+            RefractiveIndex::Cauchy { a: 1.5, b: 0.004 },
+        ] {
+            assert!(index.is_dispersive());
+            assert!(index.at(450.0) > index.at(550.0));
+            assert!(index.at(550.0) > index.at(650.0));
+        }
+
+        let constant = RefractiveIndex::Constant(1.5);
+        assert!(!constant.is_dispersive());
+        assert_eq!(constant.at(450.0), constant.at(650.0));
+        assert!(constant.abbe_number().is_infinite());
+    }
+}
@@ -0,0 +1,94 @@
+//! Helpers for the statistical tests of the materials.
+//! Every test uses a surface at the origin whose normal is +y, hit from its front face
+
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::Material;
+use crate::{hittable::HitRecord, Color3, Point3, Ray, Vec3};
+
+/// a seeded generator: the statistical tests must not be flaky
+pub fn rng() -> StdRng {
+    StdRng::seed_from_u64(1234)
+}
+
+/// a hit at the origin, on a surface whose normal is +y.
+/// Hitting the back face means the ray comes from inside the object
+pub fn hit_record(material: &dyn Material, front_face: bool) -> HitRecord<'_> {
+    HitRecord {
+        front_face,
+        material,
+        normal: Vec3::new(0.0, 1.0, 0.0),
+        object_id: 0,
+        point: Point3::new(0.0, 0.0, 0.0),
+        t: 1.0,
+        u: 0.0,
+        v: 0.0,
+        vertex_color: None,
+    }
+}
+
+/// a ray coming towards the origin, with an angle of incidence of acos(`cos_theta`) with the normal
+/// (the ray comes from +y, in the xy plane)
+pub fn incoming_ray(cos_theta: f64) -> Ray {
+    let sin_theta = (1.0 - cos_theta * cos_theta).sqrt();
+    let direction = Vec3::new(sin_theta, -cos_theta, 0.0);
+
+    Ray::new(&-direction, &direction)
+}
+
+/// white furnace: the average light a surface sends back when it is lit by a uniform white environment
+/// from every scattering direction. Absorbed rays carry no light.
+/// A material that does not create energy returns at most 1 per channel
+pub fn furnace(material: &dyn Material, ray_in: &Ray, front_face: bool, nb_samples: u32) -> Color3 {
+    let hit_record = hit_record(material, front_face);
+    let mut rng = rng();
+
+    let mut sum = Color3::black();
+    for _ in 0..nb_samples {
+        let (_, attenuation, is_reflected) = material.scatter(ray_in, &hit_record, &mut rng);
+
+        if is_reflected {
+            sum += attenuation;
+        }
+    }
+
+    sum / nb_samples as f64
+}
+
+/// Pearson's chi-square statistic of observed counts against the expected probabilities of each bin
+pub fn chi_square(observed: &[u64], expected_probabilities: &[f64]) -> f64 {
+    assert_eq!(observed.len(), expected_probabilities.len());
+
+    let total: u64 = observed.iter().sum();
+
+    observed
+        .iter()
+        .zip(expected_probabilities)
+        .map(|(observed, probability)| {
+            let expected = probability * total as f64;
+            assert!(
+                expected >= 5.0,
+                "the bins must expect at least 5 samples for the test to be valid"
+            );
+
+            (*observed as f64 - expected).powi(2) / expected
+        })
+        .sum()
+}
+
+/// the value the chi-square statistic exceeds with a probability of 0.1% when the samples follow
+/// the expected distribution, with `degrees_of_freedom` (Wilson–Hilferty approximation)
+pub fn chi_square_critical_value(degrees_of_freedom: usize) -> f64 {
+    // quantile of the standard normal distribution at 99.9%
+    const Z: f64 = 3.090_232;
+
+    let k = degrees_of_freedom as f64;
+    let variance = 2.0 / (9.0 * k);
+
+    k * (1.0 - variance + Z * variance.sqrt()).powi(3)
+}
+
+/// index of the bin of a value in [0, 1] among `nb_bins` bins of the same size
+pub fn bin(value: f64, nb_bins: usize) -> usize {
+    ((value * nb_bins as f64) as usize).min(nb_bins - 1)
+}
@@ -0,0 +1,37 @@
+use std::fmt::Debug;
+
+use crate::{hittable::HitRecord, Color3};
+
+/// A color that varies over the surface of an object
+pub trait Texture: Debug + Send + Sync {
+    /// the color at the hit point
+    fn value(&self, hit_record: &HitRecord) -> Color3;
+}
+
+/// a plain color is a uniform texture
+impl Texture for Color3 {
+    fn value(&self, _hit_record: &HitRecord) -> Color3 {
+        *self
+    }
+}
+
+/// The colors of the vertices of a mesh, interpolated over its triangles.
+/// Objects without vertex colors get the fallback color
+#[derive(Debug)]
+pub struct VertexColors {
+    fallback: Color3,
+}
+
+impl VertexColors {
+    pub fn new(fallback: &Color3) -> Self {
+        Self {
+            fallback: *fallback,
+        }
+    }
+}
+
+impl Texture for VertexColors {
+    fn value(&self, hit_record: &HitRecord) -> Color3 {
+        hit_record.vertex_color.unwrap_or(self.fallback)
+    }
+}
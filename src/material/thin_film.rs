@@ -0,0 +1,220 @@
+use std::{array, f64::consts::PI};
+
+/// the wavelengths (nm) standing for the red, green and blue channels, where the rgb integrator
+/// evaluates the wavelength dependent terms
+pub(crate) const RGB_WAVELENGTHS: [f64; 3] = [630.0, 532.0, 465.0];
+
+/// A thin transparent layer on a surface (soap, oil, oxide...), a few hundred nanometers thick.
+/// The light reflected on its top and on its bottom interferes: some wavelengths are reinforced,
+/// others cancelled, depending on the thickness and on the angle, which gives iridescent colors.
+/// The light comes from the air (index 1) and the film is lossless
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ThinFilm {
+    /// in nanometers
+    thickness: f64,
+    refraction_index: f64,
+}
+
+impl ThinFilm {
+    /// # panics
+    /// if the thickness is negative
+    pub fn new(thickness: f64, refraction_index: f64) -> Self {
+        assert!(
+            thickness >= 0.0,
+            "the thickness of a film can not be negative"
+        );
+
+        Self {
+            thickness,
+            refraction_index,
+        }
+    }
+
+    /// the fraction of the light of `wavelength` (nm) reflected by the film over a dielectric
+    /// of index `base_index`, for an angle of incidence of acos(`cos_theta`)
+    pub fn reflectance_over_dielectric(
+        &self,
+        cos_theta: f64,
+        base_index: f64,
+        wavelength: f64,
+    ) -> f64 {
+        self.reflectance(cos_theta, wavelength, |cos_film| {
+            let sin_squared =
+                self.refraction_index.powi(2) * (1.0 - cos_film * cos_film) / base_index.powi(2);
+
+            // total internal reflection at the bottom of the film
+            (sin_squared < 1.0).then(|| {
+                let cos_base = (1.0 - sin_squared).sqrt();
+                amplitudes(self.refraction_index, cos_film, base_index, cos_base)
+            })
+        })
+    }
+
+    /// the fraction of the light of `wavelength` (nm) reflected by the film over a conductor
+    /// reflecting `base_reflectance` of the light without film.
+    /// A conductor reflects the light with a phase shift, taken as half a period
+    pub fn reflectance_over_conductor(
+        &self,
+        cos_theta: f64,
+        base_reflectance: f64,
+        wavelength: f64,
+    ) -> f64 {
+        let amplitude = base_reflectance.clamp(0.0, 1.0).sqrt();
+        // the amplitudes of the bare conductor. With the sign convention of `amplitudes`,
+        // the p coefficient is the opposite of the s one at normal incidence
+        let bare = [-amplitude, amplitude];
+
+        self.reflectance(cos_theta, wavelength, |cos_film| {
+            // the amplitudes under the film are chosen so that a film without thickness,
+            // whose top and bottom reflections add up as (top + bottom) / (1 + top * bottom),
+            // gives back the bare conductor
+            let top = amplitudes(1.0, cos_theta, self.refraction_index, cos_film);
+
+            Some(array::from_fn(|i| {
+                (bare[i] - top[i]) / (1.0 - top[i] * bare[i])
+            }))
+        })
+    }
+
+    /// Airy summation of the rays reflected back and forth inside the film, for each polarization.
+    /// `base_amplitudes` gives the amplitude reflection coefficients at the bottom of the film
+    /// knowing the cosine of the angle inside it (None on total reflection)
+    fn reflectance(
+        &self,
+        cos_theta: f64,
+        wavelength: f64,
+        base_amplitudes: impl Fn(f64) -> Option<[f64; 2]>,
+    ) -> f64 {
+        // at grazing incidence, every interface reflects everything
+        if cos_theta <= 0.0 {
+            return 1.0;
+        }
+
+        let cos_theta = cos_theta.min(1.0);
+        let sin_squared = (1.0 - cos_theta * cos_theta) / self.refraction_index.powi(2);
+        if sin_squared >= 1.0 {
+            return 1.0;
+        }
+
+        let cos_film = (1.0 - sin_squared).sqrt();
+        let Some(bottom) = base_amplitudes(cos_film) else {
+            // nothing leaves the film through the bottom: the lossless film sends everything back
+            return 1.0;
+        };
+        let top = amplitudes(1.0, cos_theta, self.refraction_index, cos_film);
+
+        // the phase difference between two successive rays, from the extra path inside the film
+        let phase = 4.0 * PI * self.refraction_index * self.thickness * cos_film / wavelength;
+
+        top.iter()
+            .zip(bottom)
+            .map(|(r_top, r_bottom)| {
+                let interference = 2.0 * r_top * r_bottom * phase.cos();
+
+                (r_top * r_top + r_bottom * r_bottom + interference)
+                    / (1.0 + (r_top * r_bottom).powi(2) + interference)
+            })
+            .sum::<f64>()
+            / 2.0
+    }
+}
+
+/// the amplitude reflection coefficients of an interface (s and p polarizations),
+/// knowing the cosines of the angles on both sides
+fn amplitudes(index_in: f64, cos_in: f64, index_out: f64, cos_out: f64) -> [f64; 2] {
+    [
+        (index_in * cos_in - index_out * cos_out) / (index_in * cos_in + index_out * cos_out),
+        (index_out * cos_in - index_in * cos_out) / (index_out * cos_in + index_in * cos_out),
+    ]
+}
+
+/// the fraction of unpolarized light reflected by the interface between two dielectrics,
+/// for light coming from the medium of index `index_in` (1 on total internal reflection)
+pub(crate) fn fresnel_dielectric(cos_theta: f64, index_in: f64, index_out: f64) -> f64 {
+    let cos_theta = cos_theta.clamp(0.0, 1.0);
+    let sin_squared = (index_in / index_out).powi(2) * (1.0 - cos_theta * cos_theta);
+    if sin_squared >= 1.0 {
+        return 1.0;
+    }
+
+    let cos_out = (1.0 - sin_squared).sqrt();
+    amplitudes(index_in, cos_theta, index_out, cos_out)
+        .iter()
+        .map(|amplitude| amplitude * amplitude)
+        .sum::<f64>()
+        / 2.0
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_vanish_without_thickness() {
+        let film = ThinFilm::new(0.0, 1.33);
+
+        // only the interface between the air and the base remains
+        for cos_theta in [1.0, 0.7, 0.3, 0.05] {
+            let reflectance = film.reflectance_over_dielectric(cos_theta, 1.5, 550.0);
+            assert!((reflectance - fresnel_dielectric(cos_theta, 1.0, 1.5)).abs() < 1e-12);
+        }
+        assert!((fresnel_dielectric(1.0, 1.0, 1.5) - 0.04).abs() < 1e-12);
+        assert_eq!(fresnel_dielectric(0.3, 1.5, 1.0), 1.0);
+
+        // and the conductor alone
+        for cos_theta in [1.0, 0.7, 0.3, 0.05] {
+            for base_reflectance in [0.0, 0.6, 1.0] {
+                let reflectance = ThinFilm::new(0.0, 2.5).reflectance_over_conductor(
+                    cos_theta,
+                    base_reflectance,
+                    550.0,
+                );
+                assert!(
+                    (reflectance - base_reflectance).abs() < 1e-12,
+                    "{reflectance} over {base_reflectance} at {cos_theta}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_cancel_reflections_with_a_quarter_wave_coating() {
+        // the antireflection coating of lenses: both reflections have the same amplitude
+        // and half a period of phase difference at the design wavelength
+        let index = 1.5_f64.sqrt();
+        let coating = ThinFilm::new(550.0 / (4.0 * index), index);
+
+        assert!(coating.reflectance_over_dielectric(1.0, 1.5, 550.0) < 1e-12);
+        assert!(coating.reflectance_over_dielectric(1.0, 1.5, 400.0) > 1e-3);
+    }
+
+    #[test]
+    fn it_should_make_iridescent_colors() {
+        let soap = ThinFilm::new(400.0, 1.33);
+
+        for cos_theta in [1.0, 0.7, 0.3, 0.05] {
+            let reflectances = (380..=780)
+                .step_by(10)
+                .map(|wavelength| {
+                    soap.reflectance_over_dielectric(cos_theta, 1.0, wavelength as f64)
+                })
+                .collect::<Vec<_>>();
+
+            // no energy is created, and the reflection depends on the wavelength
+            assert!(reflectances.iter().all(|r| (0.0..=1.0).contains(r)));
+            let (min, max) = reflectances
+                .iter()
+                .fold((1.0_f64, 0.0_f64), |(min, max), r| {
+                    (min.min(*r), max.max(*r))
+                });
+            assert!(max - min > 0.05, "{min} {max} at {cos_theta}");
+        }
+
+        // over a conductor too
+        let oxide = ThinFilm::new(300.0, 2.0);
+        let [red, green, blue] = RGB_WAVELENGTHS
+            .map(|wavelength| oxide.reflectance_over_conductor(1.0, 0.9, wavelength));
+        assert!([red, green, blue].iter().all(|r| (0.0..=1.0).contains(r)));
+        assert!(red.max(green).max(blue) - red.min(green).min(blue) > 0.05);
+    }
+}
@@ -0,0 +1,2 @@
+mod ply;
+pub use ply::{parse_ply, read_ply, PlyError};
@@ -0,0 +1,680 @@
+use std::{error::Error, fmt, fs, io, path::Path};
+
+use crate::{hittable::TriangleMesh, Color3, Point3, Vec3};
+
+/// Reasons a PLY file cannot be parsed
+#[derive(Debug)]
+pub enum PlyError {
+    Io(io::Error),
+    /// the file does not start with "ply"
+    NotPly,
+    /// a header line cannot be understood
+    InvalidHeader(String),
+    /// only ascii and binary little endian files are supported
+    UnsupportedFormat(String),
+    /// an ascii value is not a number
+    InvalidValue(String),
+    /// the file ends before all the elements were read
+    Truncated,
+    /// the vertices have no x, y and z properties
+    MissingPositions,
+    /// a face uses a vertex that does not exist
+    InvalidVertexIndex {
+        face: usize,
+        index: f64,
+    },
+}
+
+impl fmt::Display for PlyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PlyError::Io(error) => write!(f, "could not read the mesh: {error}"),
+            PlyError::NotPly => write!(f, "not a ply file"),
+            PlyError::InvalidHeader(line) => write!(f, "invalid header line {line:?}"),
+            PlyError::UnsupportedFormat(format) => write!(
+                f,
+                "unsupported format {format:?}, expected ascii or binary_little_endian"
+            ),
+            PlyError::InvalidValue(value) => write!(f, "invalid value {value:?}"),
+            PlyError::Truncated => write!(f, "truncated file"),
+            PlyError::MissingPositions => write!(f, "the vertices have no x, y and z properties"),
+            PlyError::InvalidVertexIndex { face, index } => {
+                write!(
+                    f,
+                    "face {face} uses the vertex {index}, which does not exist"
+                )
+            }
+        }
+    }
+}
+
+impl Error for PlyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            PlyError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for PlyError {
+    fn from(error: io::Error) -> Self {
+        PlyError::Io(error)
+    }
+}
+
+/// read and parse a PLY file
+pub fn read_ply(path: impl AsRef<Path>) -> Result<TriangleMesh, PlyError> {
+    parse_ply(&fs::read(path)?)
+}
+
+/// parse a PLY file, ascii or binary little endian.
+/// The vertices must have x, y and z properties. Normals (nx, ny, nz), texture coordinates
+/// (u and v, or s and t) and colors (red, green, blue) are read when present.
+/// Polygonal faces are split into triangles, and other elements are skipped
+pub fn parse_ply(bytes: &[u8]) -> Result<TriangleMesh, PlyError> {
+    let (header, body) = split_header(bytes)?;
+    let (encoding, elements) = parse_header(header)?;
+
+    let mut reader = match encoding {
+        Encoding::Ascii => Reader::Ascii(
+            body.split(|byte| byte.is_ascii_whitespace())
+                .filter(|token| !token.is_empty()),
+        ),
+        Encoding::BinaryLittleEndian => Reader::Binary(body),
+    };
+
+    let mut vertices: Option<Vertices> = None;
+    let mut triangles = Vec::new();
+    let mut values = Vec::new();
+
+    for element in &elements {
+        match element.name.as_str() {
+            "vertex" => vertices = Some(read_vertices(element, &mut reader)?),
+            "face" => {
+                // the indices are checked while reading: the vertices must come first
+                let nb_vertices = vertices
+                    .as_ref()
+                    .map(|vertices| vertices.positions.len())
+                    .ok_or_else(|| {
+                        PlyError::InvalidHeader("the faces come before the vertices".to_string())
+                    })?;
+                triangles = read_faces(element, &mut reader, nb_vertices)?;
+            }
+            _ => {
+                for _ in 0..element.count {
+                    for property in &element.properties {
+                        reader.read_property(property, &mut values)?;
+                    }
+                }
+            }
+        }
+    }
+
+    let vertices = vertices.ok_or(PlyError::MissingPositions)?;
+
+    let mut mesh = TriangleMesh::new(vertices.positions, triangles);
+    if let Some(normals) = vertices.normals {
+        mesh = mesh.with_normals(normals);
+    }
+    if let Some(uvs) = vertices.uvs {
+        mesh = mesh.with_uvs(uvs);
+    }
+    if let Some(colors) = vertices.colors {
+        mesh = mesh.with_colors(colors);
+    }
+
+    Ok(mesh)
+}
+
+// Header -----------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Ascii,
+    BinaryLittleEndian,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ScalarType {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl ScalarType {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(ScalarType::I8),
+            "uchar" | "uint8" => Some(ScalarType::U8),
+            "short" | "int16" => Some(ScalarType::I16),
+            "ushort" | "uint16" => Some(ScalarType::U16),
+            "int" | "int32" => Some(ScalarType::I32),
+            "uint" | "uint32" => Some(ScalarType::U32),
+            "float" | "float32" => Some(ScalarType::F32),
+            "double" | "float64" => Some(ScalarType::F64),
+            _ => None,
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            ScalarType::I8 | ScalarType::U8 => 1,
+            ScalarType::I16 | ScalarType::U16 => 2,
+            ScalarType::I32 | ScalarType::U32 | ScalarType::F32 => 4,
+            ScalarType::F64 => 8,
+        }
+    }
+
+    /// the value of a full intensity color channel
+    fn color_scale(&self) -> f64 {
+        match self {
+            ScalarType::U8 => 255.0,
+            ScalarType::U16 => 65535.0,
+            _ => 1.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum PropertyKind {
+    Scalar(ScalarType),
+    /// a number of items, then the items
+    List {
+        count: ScalarType,
+        item: ScalarType,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Property {
+    name: String,
+    kind: PropertyKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+/// split the ascii header (up to the end_header line) from the body of the file
+fn split_header(bytes: &[u8]) -> Result<(&str, &[u8]), PlyError> {
+    if !bytes.starts_with(b"ply") {
+        return Err(PlyError::NotPly);
+    }
+
+    const END: &[u8] = b"end_header";
+    let end = bytes
+        .windows(END.len())
+        .position(|window| window == END)
+        .ok_or(PlyError::Truncated)?;
+
+    // the body starts after the end of the end_header line
+    let body_start = bytes[end..]
+        .iter()
+        .position(|&byte| byte == b'\n')
+        .map(|position| end + position + 1)
+        .ok_or(PlyError::Truncated)?;
+
+    let header = std::str::from_utf8(&bytes[..end])
+        .map_err(|_| PlyError::InvalidHeader("non utf-8 header".to_string()))?;
+
+    Ok((header, &bytes[body_start..]))
+}
+
+fn parse_header(header: &str) -> Result<(Encoding, Vec<Element>), PlyError> {
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+
+    for line in header.lines().skip(1) {
+        let invalid = || PlyError::InvalidHeader(line.to_string());
+        let words = line.split_whitespace().collect::<Vec<_>>();
+
+        match words.as_slice() {
+            [] | ["comment", ..] | ["obj_info", ..] => {}
+            ["format", format, _version] => {
+                encoding = Some(match *format {
+                    "ascii" => Encoding::Ascii,
+                    "binary_little_endian" => Encoding::BinaryLittleEndian,
+                    _ => return Err(PlyError::UnsupportedFormat(format.to_string())),
+                })
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| invalid())?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count, item, name] => {
+                let kind = PropertyKind::List {
+                    count: ScalarType::parse(count).ok_or_else(invalid)?,
+                    item: ScalarType::parse(item).ok_or_else(invalid)?,
+                };
+                elements
+                    .last_mut()
+                    .ok_or_else(invalid)?
+                    .properties
+                    .push(Property {
+                        name: name.to_string(),
+                        kind,
+                    });
+            }
+            ["property", scalar, name] => {
+                let kind = PropertyKind::Scalar(ScalarType::parse(scalar).ok_or_else(invalid)?);
+                elements
+                    .last_mut()
+                    .ok_or_else(invalid)?
+                    .properties
+                    .push(Property {
+                        name: name.to_string(),
+                        kind,
+                    });
+            }
+            _ => return Err(invalid()),
+        }
+    }
+
+    let encoding =
+        encoding.ok_or_else(|| PlyError::InvalidHeader("missing format line".to_string()))?;
+
+    Ok((encoding, elements))
+}
+
+// Body -------------------------------------
+
+enum Reader<'a, T: Iterator<Item = &'a [u8]>> {
+    Ascii(T),
+    Binary(&'a [u8]),
+}
+
+impl<'a, T: Iterator<Item = &'a [u8]>> Reader<'a, T> {
+    fn read(&mut self, scalar: ScalarType) -> Result<f64, PlyError> {
+        match self {
+            Reader::Ascii(tokens) => {
+                let token = tokens.next().ok_or(PlyError::Truncated)?;
+                let token = std::str::from_utf8(token)
+                    .map_err(|_| PlyError::InvalidValue(String::from_utf8_lossy(token).into()))?;
+
+                token
+                    .parse()
+                    .map_err(|_| PlyError::InvalidValue(token.to_string()))
+            }
+            Reader::Binary(bytes) => {
+                let size = scalar.size();
+                if bytes.len() < size {
+                    return Err(PlyError::Truncated);
+                }
+                let (value, rest) = bytes.split_at(size);
+                *bytes = rest;
+
+                Ok(match scalar {
+                    ScalarType::I8 => value[0] as i8 as f64,
+                    ScalarType::U8 => value[0] as f64,
+                    ScalarType::I16 => i16::from_le_bytes([value[0], value[1]]) as f64,
+                    ScalarType::U16 => u16::from_le_bytes([value[0], value[1]]) as f64,
+                    ScalarType::I32 => i32::from_le_bytes(value.try_into().unwrap()) as f64,
+                    ScalarType::U32 => u32::from_le_bytes(value.try_into().unwrap()) as f64,
+                    ScalarType::F32 => f32::from_le_bytes(value.try_into().unwrap()) as f64,
+                    ScalarType::F64 => f64::from_le_bytes(value.try_into().unwrap()),
+                })
+            }
+        }
+    }
+
+    /// read the values of a property in `values`: one for a scalar, any number for a list.
+    /// The buffer is reused to avoid an allocation per value
+    fn read_property(
+        &mut self,
+        property: &Property,
+        values: &mut Vec<f64>,
+    ) -> Result<(), PlyError> {
+        values.clear();
+
+        match property.kind {
+            PropertyKind::Scalar(scalar) => values.push(self.read(scalar)?),
+            PropertyKind::List { count, item } => {
+                let count = self.read(count)?;
+                if count < 0.0 {
+                    return Err(PlyError::InvalidValue(count.to_string()));
+                }
+
+                for _ in 0..count as usize {
+                    values.push(self.read(item)?);
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// the vertex properties, as read from the file
+struct Vertices {
+    positions: Vec<Point3>,
+    normals: Option<Vec<Vec3>>,
+    uvs: Option<Vec<(f64, f64)>>,
+    colors: Option<Vec<Color3>>,
+}
+
+fn read_vertices<'a>(
+    element: &Element,
+    reader: &mut Reader<'a, impl Iterator<Item = &'a [u8]>>,
+) -> Result<Vertices, PlyError> {
+    // the index of the first property having one of the names
+    let find = |names: &[&str]| {
+        element
+            .properties
+            .iter()
+            .position(|property| names.contains(&property.name.as_str()))
+    };
+    let find_all = |names: [&[&str]; 3]| match names.map(find) {
+        [Some(a), Some(b), Some(c)] => Some([a, b, c]),
+        _ => None,
+    };
+
+    let position = find_all([&["x"], &["y"], &["z"]]).ok_or(PlyError::MissingPositions)?;
+    let normal = find_all([&["nx"], &["ny"], &["nz"]]);
+    let uv = match (
+        find(&["u", "s", "texture_u", "texture_s"]),
+        find(&["v", "t", "texture_v", "texture_t"]),
+    ) {
+        (Some(u), Some(v)) => Some([u, v]),
+        _ => None,
+    };
+    let color = find_all([&["red", "r"], &["green", "g"], &["blue", "b"]]);
+    let color_scale = color.map(|[red, _, _]| match element.properties[red].kind {
+        PropertyKind::Scalar(scalar) => scalar.color_scale(),
+        PropertyKind::List { .. } => 1.0,
+    });
+
+    // no capacity reserved from the header: a corrupted count must fail on read, not on allocation
+    let mut vertices = Vertices {
+        positions: Vec::new(),
+        normals: normal.map(|_| Vec::new()),
+        uvs: uv.map(|_| Vec::new()),
+        colors: color.map(|_| Vec::new()),
+    };
+
+    let mut values = vec![0.0; element.properties.len()];
+    let mut buffer = Vec::new();
+    for _ in 0..element.count {
+        for (value, property) in values.iter_mut().zip(&element.properties) {
+            // only the first item of a list is kept: lists are not expected on vertices
+            reader.read_property(property, &mut buffer)?;
+            *value = buffer.first().copied().unwrap_or_default();
+        }
+
+        let vector = |[x, y, z]: [usize; 3]| Vec3::new(values[x], values[y], values[z]);
+
+        vertices.positions.push(vector(position));
+        if let (Some(normals), Some(normal)) = (&mut vertices.normals, normal) {
+            normals.push(vector(normal));
+        }
+        if let (Some(uvs), Some([u, v])) = (&mut vertices.uvs, uv) {
+            uvs.push((values[u], values[v]));
+        }
+        if let (Some(colors), Some(color), Some(scale)) = (&mut vertices.colors, color, color_scale)
+        {
+            // colors are stored sRGB encoded, like in images
+            let stored = vector(color) / scale;
+            colors.push(Color3::from_srgb(&Color3::new(
+                stored.x(),
+                stored.y(),
+                stored.z(),
+            )));
+        }
+    }
+
+    Ok(vertices)
+}
+
+/// the triangles of the faces: polygons are split in fans of triangles
+fn read_faces<'a>(
+    element: &Element,
+    reader: &mut Reader<'a, impl Iterator<Item = &'a [u8]>>,
+    nb_vertices: usize,
+) -> Result<Vec<[usize; 3]>, PlyError> {
+    let indices = element
+        .properties
+        .iter()
+        .position(|property| property.name == "vertex_indices" || property.name == "vertex_index")
+        .ok_or_else(|| PlyError::InvalidHeader("faces without vertex_indices".to_string()))?;
+
+    let mut triangles = Vec::new();
+    let mut values = Vec::new();
+    for face in 0..element.count {
+        for (i, property) in element.properties.iter().enumerate() {
+            reader.read_property(property, &mut values)?;
+            if i != indices {
+                continue;
+            }
+
+            if let Some(&index) = values
+                .iter()
+                .find(|&&index| index < 0.0 || index >= nb_vertices as f64 || index.fract() != 0.0)
+            {
+                return Err(PlyError::InvalidVertexIndex { face, index });
+            }
+
+            for i in 1..values.len().saturating_sub(1) {
+                triangles.push([
+                    values[0] as usize,
+                    values[i] as usize,
+                    values[i + 1] as usize,
+                ]);
+            }
+        }
+    }
+
+    Ok(triangles)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        hittable::{Hittable, Mesh},
+        material::{Lambertian, Material, VertexColors},
+        Ray,
+    };
+
+    const SQUARE: &str = "ply
+format ascii 1.0
+comment a unit square, made of one polygon
+element vertex 4
+property float x
+property float y
+property float z
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+1 0 0
+1 1 0
+0 1 0
+4 0 1 2 3
+";
+
+    #[test]
+    fn it_should_parse_an_ascii_file() {
+        let mesh = parse_ply(SQUARE.as_bytes()).unwrap();
+
+        assert_eq!(mesh.positions().len(), 4);
+        assert_eq!(mesh.positions()[2], Point3::new(1.0, 1.0, 0.0));
+        // the polygon is split in a fan of triangles
+        assert_eq!(mesh.triangles(), &[[0, 1, 2], [0, 2, 3]]);
+        assert!(mesh.normals().is_none());
+        assert!(mesh.uvs().is_none());
+        assert!(mesh.colors().is_none());
+    }
+
+    #[test]
+    fn it_should_read_the_optional_vertex_properties() {
+        let file = "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+property float nx
+property float ny
+property float nz
+property float s
+property float t
+property uchar red
+property uchar green
+property uchar blue
+property uchar alpha
+element face 1
+property list uchar uint vertex_index
+end_header
+0 0 0  0 0 2  0 0  255 0 0 255
+1 0 0  0 0 2  1 0  0 255 0 255
+0 1 0  0 0 2  0 1  0 0 255 255
+3 0 1 2
+";
+        let mesh = parse_ply(file.as_bytes()).unwrap();
+
+        assert_eq!(mesh.normals().unwrap()[1], Vec3::new(0.0, 0.0, 1.0));
+        assert_eq!(mesh.uvs().unwrap()[2], (0.0, 1.0));
+        assert_eq!(mesh.colors().unwrap()[0], Color3::new(1.0, 0.0, 0.0));
+        assert_eq!(mesh.colors().unwrap()[1], Color3::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn it_should_parse_a_binary_little_endian_file() {
+        let mut file = b"ply
+format binary_little_endian 1.0
+element vertex 3
+property double x
+property float y
+property float z
+property ushort quality
+element face 1
+property list uchar int vertex_indices
+property uchar flags
+end_header
+"
+        .to_vec();
+        for (x, y, z) in [(0.0, 0.0, 0.0), (2.0, 0.0, 0.0), (0.0, 2.0, 0.5)] {
+            file.extend_from_slice(&f64::to_le_bytes(x));
+            file.extend_from_slice(&f32::to_le_bytes(y));
+            file.extend_from_slice(&f32::to_le_bytes(z));
+            file.extend_from_slice(&u16::to_le_bytes(7));
+        }
+        file.push(3);
+        for index in [0_i32, 1, 2] {
+            file.extend_from_slice(&index.to_le_bytes());
+        }
+        file.push(0);
+
+        let mesh = parse_ply(&file).unwrap();
+
+        assert_eq!(mesh.positions()[2], Point3::new(0.0, 2.0, 0.5));
+        assert_eq!(mesh.triangles(), &[[0, 1, 2]]);
+    }
+
+    #[test]
+    fn it_should_skip_unknown_elements() {
+        let file = "ply
+format ascii 1.0
+element vertex 3
+property float x
+property float y
+property float z
+element edge 1
+property int vertex1
+property int vertex2
+element face 1
+property list uchar int vertex_indices
+end_header
+0 0 0
+1 0 0
+0 1 0
+0 1
+3 0 1 2
+";
+        let mesh = parse_ply(file.as_bytes()).unwrap();
+
+        assert_eq!(mesh.triangles(), &[[0, 1, 2]]);
+    }
+
+    #[test]
+    fn it_should_reject_invalid_files() {
+        assert!(matches!(parse_ply(b"solid cube"), Err(PlyError::NotPly)));
+        assert!(matches!(
+            parse_ply(SQUARE.replace("ascii", "binary_big_endian").as_bytes()),
+            Err(PlyError::UnsupportedFormat(_))
+        ));
+        assert!(matches!(
+            parse_ply(&SQUARE.as_bytes()[..SQUARE.len() - 10]),
+            Err(PlyError::Truncated)
+        ));
+        assert!(matches!(
+            parse_ply(SQUARE.replace("4 0 1 2 3", "4 0 1 2 4").as_bytes()),
+            Err(PlyError::InvalidVertexIndex { face: 0, .. })
+        ));
+        assert!(matches!(
+            parse_ply(
+                SQUARE
+                    .replace("property float z", "property float w")
+                    .as_bytes()
+            ),
+            Err(PlyError::MissingPositions)
+        ));
+        assert!(matches!(
+            parse_ply(SQUARE.replace("1 1 0", "1 one 0").as_bytes()),
+            Err(PlyError::InvalidValue(_))
+        ));
+
+        // counts far beyond the size of the file
+        for (from, to) in [
+            ("element vertex 4", "element vertex 99999999999999"),
+            ("element face 1", "element face 99999999999999"),
+        ] {
+            assert!(matches!(
+                parse_ply(SQUARE.replace(from, to).as_bytes()),
+                Err(PlyError::Truncated)
+            ));
+        }
+    }
+
+    #[test]
+    fn it_should_texture_a_lambertian_with_the_vertex_colors() {
+        let file = SQUARE
+            .replace(
+                "property float z",
+                "property float z\nproperty uchar red\nproperty uchar green\nproperty uchar blue",
+            )
+            .replace(
+                "0 0 0\n1 0 0\n1 1 0\n0 1 0",
+                "0 0 0 255 255 255\n1 0 0 0 0 0\n1 1 0 0 0 0\n0 1 0 255 255 255",
+            );
+        let geometry = parse_ply(file.as_bytes()).unwrap();
+
+        let material = Lambertian::textured(VertexColors::new(&Color3::black()));
+        let mesh = Mesh::new(&geometry, &material);
+
+        // white on the left side, black on the right side
+        let ray = |x| Ray::new(&Point3::new(x, 0.5, 1.0), &Vec3::new(0.0, 0.0, -1.0));
+        let left = mesh.hit(&ray(0.0), 0.0, f64::INFINITY).unwrap();
+        let right = mesh.hit(&ray(1.0), 0.0, f64::INFINITY).unwrap();
+        let middle = mesh.hit(&ray(0.5), 0.0, f64::INFINITY).unwrap();
+
+        assert_eq!(material.albedo(&left), Color3::white());
+        assert_eq!(material.albedo(&right), Color3::black());
+        // interpolated in linear colors
+        assert!(
+            material
+                .albedo(&middle)
+                .distance(&Color3::new(0.5, 0.5, 0.5))
+                < 1e-9
+        );
+    }
+}
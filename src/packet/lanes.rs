@@ -0,0 +1,167 @@
+use std::{array, ops};
+
+/// `N` single precision numbers processed together.
+/// Every operation is applied lane by lane on a fixed size array: the compiler turns the loops
+/// into SIMD instructions on any target that has them. 4 lanes fill the registers of SSE and NEON,
+/// and are the size to use: packets of 8 lanes were measured slower, even on AVX targets
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Lanes<const N: usize>(pub [f32; N]);
+
+/// The result of a comparison of lanes: one boolean per lane
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mask<const N: usize>(pub [bool; N]);
+
+impl<const N: usize> Lanes<N> {
+    /// the same value in every lane
+    pub fn splat(value: f32) -> Self {
+        Self([value; N])
+    }
+
+    pub fn from_fn(f: impl FnMut(usize) -> f32) -> Self {
+        Self(array::from_fn(f))
+    }
+
+    fn map(self, f: impl Fn(f32) -> f32) -> Self {
+        Self(self.0.map(f))
+    }
+
+    fn zip(self, other: Self, f: impl Fn(f32, f32) -> f32) -> Self {
+        Self(array::from_fn(|lane| f(self.0[lane], other.0[lane])))
+    }
+
+    fn compare(self, other: Self, f: impl Fn(f32, f32) -> bool) -> Mask<N> {
+        Mask(array::from_fn(|lane| f(self.0[lane], other.0[lane])))
+    }
+
+    pub fn sqrt(self) -> Self {
+        self.map(f32::sqrt)
+    }
+
+    pub fn abs(self) -> Self {
+        self.map(f32::abs)
+    }
+
+    pub fn min(self, other: Self) -> Self {
+        self.zip(other, f32::min)
+    }
+
+    pub fn max(self, other: Self) -> Self {
+        self.zip(other, f32::max)
+    }
+
+    pub fn lt(self, other: Self) -> Mask<N> {
+        self.compare(other, |a, b| a < b)
+    }
+
+    pub fn le(self, other: Self) -> Mask<N> {
+        self.compare(other, |a, b| a <= b)
+    }
+
+    pub fn gt(self, other: Self) -> Mask<N> {
+        self.compare(other, |a, b| a > b)
+    }
+
+    pub fn ge(self, other: Self) -> Mask<N> {
+        self.compare(other, |a, b| a >= b)
+    }
+
+    /// the lanes of `if_true` where the mask is set, the lanes of `if_false` elsewhere
+    pub fn select(mask: Mask<N>, if_true: Self, if_false: Self) -> Self {
+        Self(array::from_fn(|lane| {
+            if mask.0[lane] {
+                if_true.0[lane]
+            } else {
+                if_false.0[lane]
+            }
+        }))
+    }
+}
+
+impl<const N: usize> Mask<N> {
+    pub fn any(&self) -> bool {
+        self.0.iter().any(|&lane| lane)
+    }
+}
+
+impl<const N: usize> ops::BitAnd for Mask<N> {
+    type Output = Self;
+    fn bitand(self, rhs: Self) -> Self {
+        Self(array::from_fn(|lane| self.0[lane] & rhs.0[lane]))
+    }
+}
+
+impl<const N: usize> ops::BitOr for Mask<N> {
+    type Output = Self;
+    fn bitor(self, rhs: Self) -> Self {
+        Self(array::from_fn(|lane| self.0[lane] | rhs.0[lane]))
+    }
+}
+
+impl<const N: usize> ops::Not for Mask<N> {
+    type Output = Self;
+    fn not(self) -> Self {
+        Self(self.0.map(|lane| !lane))
+    }
+}
+
+impl<const N: usize> ops::Add for Lanes<N> {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a + b)
+    }
+}
+
+impl<const N: usize> ops::Sub for Lanes<N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a - b)
+    }
+}
+
+impl<const N: usize> ops::Mul for Lanes<N> {
+    type Output = Self;
+    fn mul(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a * b)
+    }
+}
+
+impl<const N: usize> ops::Mul<f32> for Lanes<N> {
+    type Output = Self;
+    fn mul(self, rhs: f32) -> Self {
+        self.map(|a| a * rhs)
+    }
+}
+
+impl<const N: usize> ops::Div for Lanes<N> {
+    type Output = Self;
+    fn div(self, rhs: Self) -> Self {
+        self.zip(rhs, |a, b| a / b)
+    }
+}
+
+impl<const N: usize> ops::Neg for Lanes<N> {
+    type Output = Self;
+    fn neg(self) -> Self {
+        self.map(|a| -a)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_compute_lane_by_lane() {
+        let a = Lanes([1.0, 4.0, 9.0, 16.0]);
+        let b = Lanes::splat(2.0);
+
+        assert_eq!(a + b, Lanes([3.0, 6.0, 11.0, 18.0]));
+        assert_eq!(a * b - b, Lanes([0.0, 6.0, 16.0, 30.0]));
+        assert_eq!(a.sqrt(), Lanes([1.0, 2.0, 3.0, 4.0]));
+
+        let mask = a.gt(Lanes::splat(5.0));
+        assert_eq!(mask, Mask([false, false, true, true]));
+        assert_eq!(Lanes::select(mask, a, b), Lanes([2.0, 2.0, 9.0, 16.0]));
+        assert!(!(mask & !mask).any());
+    }
+}
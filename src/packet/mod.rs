@@ -0,0 +1,11 @@
+//! Single precision tracing of packets of rays, for the throughput of camera rays.
+//! Rays are stored as structures of arrays, and intersected `N` at a time (4 or 8 fit SIMD registers)
+
+mod lanes;
+pub use lanes::{Lanes, Mask};
+
+mod scene;
+pub use scene::{PacketHits, PacketScene, Primitive, RayPacket};
+
+mod vec3f;
+pub use vec3f::{Vec3Lanes, Vec3f};
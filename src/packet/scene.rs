@@ -0,0 +1,249 @@
+use std::array;
+
+use crate::{Point3, Ray};
+
+use super::{Lanes, Mask, Vec3Lanes, Vec3f};
+
+/// `N` rays traced together, ideally coherent ones (like the camera rays of neighboring pixels)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RayPacket<const N: usize> {
+    origin: Vec3Lanes<N>,
+    direction: Vec3Lanes<N>,
+}
+
+impl<const N: usize> RayPacket<N> {
+    pub fn new(rays: &[Ray; N]) -> Self {
+        Self {
+            origin: Vec3Lanes::from_vectors(&rays.each_ref().map(|ray| ray.origin().into())),
+            direction: Vec3Lanes::from_vectors(&rays.each_ref().map(|ray| ray.direction().into())),
+        }
+    }
+}
+
+/// A primitive of a `PacketScene`, by its index in the order it was added
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Sphere(usize),
+    Triangle(usize),
+}
+
+/// The closest hit of each ray of a packet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PacketHits<const N: usize> {
+    /// time of the hit (t_max for the rays hitting nothing)
+    pub t: [f32; N],
+    pub primitive: [Option<Primitive>; N],
+}
+
+/// Spheres and triangles in single precision, intersected by packets of rays.
+/// Each primitive is tested against all the rays of a packet at once, one ray per SIMD lane.
+/// Only the closest hits are computed: shading uses the regular `Hittable` path
+#[derive(Debug, Default)]
+pub struct PacketScene {
+    /// centers and radii
+    spheres: Vec<(Vec3f, f32)>,
+    /// first vertex and the two edges from it
+    triangles: Vec<[Vec3f; 3]>,
+}
+
+impl PacketScene {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_sphere(&mut self, center: &Point3, radius: f64) {
+        self.spheres.push(((*center).into(), radius as f32));
+    }
+
+    pub fn add_triangle(&mut self, vertex_0: &Point3, vertex_1: &Point3, vertex_2: &Point3) {
+        self.triangles.push([
+            (*vertex_0).into(),
+            (*vertex_1 - *vertex_0).into(),
+            (*vertex_2 - *vertex_0).into(),
+        ]);
+    }
+
+    /// the closest hit of every ray of the packet. A packet of 1 ray is the scalar path
+    pub fn intersect<const N: usize>(
+        &self,
+        packet: &RayPacket<N>,
+        t_min: f32,
+        t_max: f32,
+    ) -> PacketHits<N> {
+        let mut closest = Lanes::splat(t_max);
+        let mut primitive = [None; N];
+        let t_min = Lanes::splat(t_min);
+
+        // updates the lanes with a closer hit
+        let mut record = |valid: Mask<N>, t: Lanes<N>, closest: &mut Lanes<N>, hit: Primitive| {
+            *closest = Lanes::select(valid, t, *closest);
+            for (lane, is_valid) in valid.0.iter().enumerate() {
+                if *is_valid {
+                    primitive[lane] = Some(hit);
+                }
+            }
+        };
+
+        for (index, (center, radius)) in self.spheres.iter().enumerate() {
+            let oc = packet.origin - Vec3Lanes::splat(center);
+            let a = packet.direction.dot(&packet.direction);
+            let half_b = oc.dot(&packet.direction);
+            let c = oc.dot(&oc) - Lanes::splat(radius * radius);
+            let discriminant = half_b * half_b - a * c;
+
+            let hit = discriminant.ge(Lanes::splat(0.0));
+            if !hit.any() {
+                continue;
+            }
+
+            // the nearest root in the range, like `Sphere`
+            let sqrtd = discriminant.max(Lanes::splat(0.0)).sqrt();
+            let near = (-half_b - sqrtd) / a;
+            let far = (-half_b + sqrtd) / a;
+            let near_in_range = near.ge(t_min) & near.lt(closest);
+            let t = Lanes::select(near_in_range, near, far);
+            let valid = hit & t.ge(t_min) & t.lt(closest);
+
+            record(valid, t, &mut closest, Primitive::Sphere(index));
+        }
+
+        for (index, [vertex_0, edge_1, edge_2]) in self.triangles.iter().enumerate() {
+            // Möller–Trumbore algorithm, like `Triangle`
+            let edge_1 = Vec3Lanes::splat(edge_1);
+            let edge_2 = Vec3Lanes::splat(edge_2);
+            let h = packet.direction.cross(&edge_2);
+            let a = edge_1.dot(&h);
+            let not_parallel = a.abs().gt(Lanes::splat(1e-7));
+
+            let f = Lanes::splat(1.0) / a;
+            let s = packet.origin - Vec3Lanes::splat(vertex_0);
+            let u = f * s.dot(&h);
+            let q = s.cross(&edge_1);
+            let v = f * packet.direction.dot(&q);
+            let t = f * edge_2.dot(&q);
+
+            let (zero, one) = (Lanes::splat(0.0), Lanes::splat(1.0));
+            let valid = not_parallel
+                & u.ge(zero)
+                & u.le(one)
+                & v.ge(zero)
+                & (u + v).le(one)
+                & t.ge(t_min)
+                & t.lt(closest);
+
+            record(valid, t, &mut closest, Primitive::Triangle(index));
+        }
+
+        PacketHits {
+            t: closest.0,
+            primitive,
+        }
+    }
+
+    /// trace rays in packets of `N` (at least 1, checked at compile time).
+    /// The last packet is completed by repeating its last ray
+    pub fn intersect_all<const N: usize>(
+        &self,
+        rays: &[Ray],
+        t_min: f32,
+        t_max: f32,
+    ) -> Vec<(f32, Option<Primitive>)> {
+        const { assert!(N > 0, "packets must have at least one lane") };
+
+        rays.chunks(N)
+            .flat_map(|chunk| {
+                let rays: [Ray; N] = array::from_fn(|lane| {
+                    let ray = &chunk[lane.min(chunk.len() - 1)];
+                    Ray::new(&ray.origin(), &ray.direction())
+                });
+                let hits = self.intersect(&RayPacket::new(&rays), t_min, t_max);
+
+                (0..chunk.len()).map(move |lane| (hits.t[lane], hits.primitive[lane]))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use crate::{
+        hittable::{Hittable, HittableList, Sphere, Triangle},
+        material::Lambertian,
+        Color3, Vec3,
+    };
+
+    use super::*;
+
+    #[test]
+    fn it_should_hit_like_the_scalar_path() {
+        let material = Lambertian::new(&Color3::white());
+        let mut rng = StdRng::seed_from_u64(8);
+
+        let mut scene = PacketScene::new();
+        let mut spheres = Vec::new();
+        let mut triangles = Vec::new();
+        for _ in 0..20 {
+            let center = Vec3::new_clamped_random(-5.0, 5.0, &mut rng);
+            let radius = rng.gen_range(0.2..1.0);
+            scene.add_sphere(&center, radius);
+            spheres.push(Sphere::new(&center, radius, &material));
+
+            let vertices = [0; 3].map(|_| center + Vec3::new_clamped_random(-1.5, 1.5, &mut rng));
+            scene.add_triangle(&vertices[0], &vertices[1], &vertices[2]);
+            triangles.push(Triangle::new(
+                &vertices[0],
+                &vertices[1],
+                &vertices[2],
+                &material,
+            ));
+        }
+
+        // the list gives the object ids of the spheres first, then of the triangles
+        let mut world = HittableList::new();
+        spheres.iter().for_each(|sphere| world.add(sphere));
+        triangles.iter().for_each(|triangle| world.add(triangle));
+        let primitive_of = |object_id: u32| {
+            let index = object_id as usize;
+            if index < spheres.len() {
+                Primitive::Sphere(index)
+            } else {
+                Primitive::Triangle(index - spheres.len())
+            }
+        };
+
+        // a fan of coherent rays, not a multiple of the packet size
+        let origin = Point3::new(0.0, 0.0, 20.0);
+        let rays: Vec<Ray> = (0..1001)
+            .map(|i| {
+                let target = Point3::new(
+                    (i % 31) as f64 / 3.0 - 5.0,
+                    (i / 31) as f64 / 3.0 - 5.0,
+                    0.0,
+                );
+                Ray::new(&origin, &(target - origin))
+            })
+            .collect();
+
+        for hits in [
+            scene.intersect_all::<1>(&rays, 0.001, f32::INFINITY),
+            scene.intersect_all::<4>(&rays, 0.001, f32::INFINITY),
+            scene.intersect_all::<8>(&rays, 0.001, f32::INFINITY),
+        ] {
+            assert_eq!(hits.len(), rays.len());
+
+            for (ray, (t, primitive)) in rays.iter().zip(hits) {
+                let expected = world.hit(ray, 0.001, f64::INFINITY);
+
+                assert_eq!(
+                    primitive,
+                    expected.as_ref().map(|hit| primitive_of(hit.object_id))
+                );
+                if let Some(expected) = expected {
+                    assert!((t as f64 - expected.t).abs() < 1e-4 * expected.t);
+                }
+            }
+        }
+    }
+}
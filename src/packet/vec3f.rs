@@ -0,0 +1,101 @@
+use std::ops;
+
+use crate::Vec3;
+
+use super::Lanes;
+
+/// A single precision 3 dimension vector, half the size of a `Vec3`.
+/// Precise enough for the intersection of camera rays, it is the type of the packet path
+#[derive(Debug, PartialEq, Copy, Clone, Default)]
+pub struct Vec3f {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+}
+
+impl Vec3f {
+    pub fn new(x: f32, y: f32, z: f32) -> Self {
+        Self { x, y, z }
+    }
+
+    pub fn dot(&self, other: &Self) -> f32 {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+}
+
+impl From<Vec3> for Vec3f {
+    fn from(vector: Vec3) -> Self {
+        Self::new(vector.x() as f32, vector.y() as f32, vector.z() as f32)
+    }
+}
+
+impl From<Vec3f> for Vec3 {
+    fn from(vector: Vec3f) -> Self {
+        Vec3::new(vector.x as f64, vector.y as f64, vector.z as f64)
+    }
+}
+
+impl ops::Sub for Vec3f {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self::new(self.x - rhs.x, self.y - rhs.y, self.z - rhs.z)
+    }
+}
+
+/// `N` vectors stored as a structure of arrays: the x of every vector, then the y, then the z
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Vec3Lanes<const N: usize> {
+    pub x: Lanes<N>,
+    pub y: Lanes<N>,
+    pub z: Lanes<N>,
+}
+
+impl<const N: usize> Vec3Lanes<N> {
+    /// the same vector in every lane
+    pub fn splat(vector: &Vec3f) -> Self {
+        Self {
+            x: Lanes::splat(vector.x),
+            y: Lanes::splat(vector.y),
+            z: Lanes::splat(vector.z),
+        }
+    }
+
+    pub fn from_vectors(vectors: &[Vec3f; N]) -> Self {
+        Self {
+            x: Lanes::from_fn(|lane| vectors[lane].x),
+            y: Lanes::from_fn(|lane| vectors[lane].y),
+            z: Lanes::from_fn(|lane| vectors[lane].z),
+        }
+    }
+
+    pub fn dot(&self, other: &Self) -> Lanes<N> {
+        self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn cross(&self, other: &Self) -> Self {
+        Self {
+            x: self.y * other.z - self.z * other.y,
+            y: self.z * other.x - self.x * other.z,
+            z: self.x * other.y - self.y * other.x,
+        }
+    }
+}
+
+impl<const N: usize> ops::Sub for Vec3Lanes<N> {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Self {
+            x: self.x - rhs.x,
+            y: self.y - rhs.y,
+            z: self.z - rhs.z,
+        }
+    }
+}
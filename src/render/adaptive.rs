@@ -0,0 +1,60 @@
+use super::PixelStats;
+
+/// Controls how many samples are taken per pixel.
+/// Every pixel gets at least `min_samples`, then sampling stops as soon as the estimated
+/// relative error of the pixel falls below `max_relative_error`, or when `max_samples` are taken
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdaptiveSampling {
+    min_samples: u32,
+    max_samples: u32,
+    max_relative_error: f64,
+}
+
+impl AdaptiveSampling {
+    /// min_samples: samples always taken, before trusting the error estimate (at least 2)
+    /// max_samples: sample budget of a pixel
+    /// max_relative_error: e.g. 0.01 for a 1% error on the pixel luminance
+    pub fn new(min_samples: u32, max_samples: u32, max_relative_error: f64) -> Self {
+        let min_samples = min_samples.max(2);
+
+        Self {
+            min_samples,
+            max_samples: max_samples.max(min_samples),
+            max_relative_error,
+        }
+    }
+
+    /// the classic non adaptive sampling: every pixel gets the same number of samples
+    pub fn fixed(samples_per_pixel: u32) -> Self {
+        Self {
+            min_samples: samples_per_pixel,
+            max_samples: samples_per_pixel,
+            max_relative_error: 0.0,
+        }
+    }
+
+    pub fn min_samples(&self) -> u32 {
+        self.min_samples
+    }
+
+    pub fn max_samples(&self) -> u32 {
+        self.max_samples
+    }
+
+    pub fn max_relative_error(&self) -> f64 {
+        self.max_relative_error
+    }
+
+    /// true if the pixel does not need more samples
+    pub fn is_done(&self, stats: &PixelStats) -> bool {
+        if stats.count() >= self.max_samples {
+            return true;
+        }
+
+        if stats.count() < self.min_samples {
+            return false;
+        }
+
+        stats.relative_error() < self.max_relative_error
+    }
+}
@@ -0,0 +1,313 @@
+use std::{io, path::Path, thread};
+
+use exr::prelude::{
+    AnyChannel, AnyChannels, Encoding, FlatSamples, Image, Layer, LayerAttributes, SmallVec,
+    WritableImage,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use super::{background, PixelStats};
+use crate::{hittable::Hittable, Camera, Color3, Point3, Vec3};
+
+/// Arbitrary output variables of a pixel: what the camera rays hit first.
+/// They are used for compositing and to guide the denoiser.
+/// Values are averaged over the samples of the pixel, so edges are antialiased like the beauty image
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AovPixel {
+    /// distance from the camera to the first hit, averaged over the samples that hit something
+    /// (infinite if none did)
+    pub depth: f64,
+    /// world space normal of the first hit, facing the camera (zero if nothing was hit).
+    /// The average of the samples normals is not normalized again
+    pub normal: Vec3,
+    /// albedo of the material hit first (the sky color if nothing was hit)
+    pub albedo: Color3,
+    /// world space position of the first hit, averaged over the samples that hit something
+    pub position: Point3,
+    /// identifier of the object seen at the center of the pixel (see `HitRecord::object_id`)
+    pub object_id: Option<u32>,
+}
+
+/// compute the output variables of every pixel, tracing `samples_per_pixel` camera rays per pixel.
+/// Only the first hit matters, so this is much cheaper than rendering the scene.
+/// The result is a vector of rows, from the bottom line to the top one, like rendered scenes
+pub fn compute_aovs(
+    camera: &Camera,
+    world: &dyn Hittable,
+    image_width: u32,
+    image_height: u32,
+    samples_per_pixel: u32,
+) -> Vec<Vec<AovPixel>> {
+    let samples_per_pixel = samples_per_pixel.max(1);
+    let empty_pixel = AovPixel {
+        depth: f64::INFINITY,
+        normal: Vec3::new(0.0, 0.0, 0.0),
+        albedo: Color3::black(),
+        position: Point3::new(0.0, 0.0, 0.0),
+        object_id: None,
+    };
+    let mut aovs = vec![vec![empty_pixel; image_width as usize]; image_height as usize];
+
+    let nb_thread = 6;
+    let nb_line_per_thread = image_height.div_ceil(nb_thread).max(1);
+
+    thread::scope(|scope| {
+        for (num_thread, lines) in aovs.chunks_mut(nb_line_per_thread as usize).enumerate() {
+            let first_line = num_thread as u32 * nb_line_per_thread;
+
+            scope.spawn(move || {
+                for (i, row) in (first_line..).zip(lines.iter_mut()) {
+                    let mut rng = StdRng::seed_from_u64(i as u64);
+
+                    for (j, pixel) in (0..).zip(row.iter_mut()) {
+                        *pixel = aov_pixel(
+                            camera,
+                            world,
+                            (i, j),
+                            (image_width, image_height),
+                            samples_per_pixel,
+                            &mut rng,
+                        );
+                    }
+                }
+            });
+        }
+    });
+
+    aovs
+}
+
+/// trace the camera rays of pixel (line, column) and average what they hit first
+fn aov_pixel(
+    camera: &Camera,
+    world: &dyn Hittable,
+    (i, j): (u32, u32),
+    (image_width, image_height): (u32, u32),
+    samples_per_pixel: u32,
+    rng: &mut StdRng,
+) -> AovPixel {
+    let mut nb_hits = 0;
+    let mut depth = 0.0;
+    let mut normal = Vec3::new(0.0, 0.0, 0.0);
+    let mut albedo = Color3::black();
+    let mut position = Point3::new(0.0, 0.0, 0.0);
+    let mut object_id = None;
+
+    for sample in 0..samples_per_pixel {
+        // the first sample goes through the center of the pixel: it decides the object id
+        let (du, dv) = if sample == 0 {
+            (0.5, 0.5)
+        } else {
+            (rng.gen::<f64>(), rng.gen::<f64>())
+        };
+        let u = (j as f64 + du) / (image_width - 1) as f64;
+        let v = (i as f64 + dv) / (image_height - 1) as f64;
+
+        let ray = camera.get_ray(u, v, rng);
+
+        match world.hit(&ray, 0.001, f64::INFINITY) {
+            Some(hit_record) => {
+                nb_hits += 1;
+                depth += hit_record.t * ray.direction().mag();
+                normal += hit_record.normal;
+                albedo += hit_record.material.albedo(&hit_record);
+                position += hit_record.point;
+
+                if sample == 0 {
+                    object_id = Some(hit_record.object_id);
+                }
+            }
+            None => albedo += background(&ray),
+        }
+    }
+
+    let hits_scale = 1.0 / nb_hits.max(1) as f64;
+
+    AovPixel {
+        depth: if nb_hits == 0 {
+            f64::INFINITY
+        } else {
+            depth * hits_scale
+        },
+        normal: normal / samples_per_pixel as f64,
+        albedo: albedo / samples_per_pixel as f64,
+        position: position * hits_scale,
+        object_id,
+    }
+}
+
+/// save the beauty image and its output variables as the layers of a single EXR file (linear, 32 bits floats).
+/// channels: R, G, B (beauty), depth.Z, normal.X/Y/Z, albedo.R/G/B, position.X/Y/Z,
+/// and object.id (u32: the object id + 1, 0 where nothing was hit).
+/// There is no material id: materials are shared by reference and have no identifier,
+/// the object id tells the surfaces apart.
+/// Fails with `InvalidInput` if the scene and its output variables do not have the same size
+pub fn save_aovs_exr(
+    path: &Path,
+    scene: &[Vec<PixelStats>],
+    aovs: &[Vec<AovPixel>],
+) -> io::Result<()> {
+    let image_height = scene.len();
+    let image_width = scene.first().map_or(0, |row| row.len());
+
+    let same_size = aovs.len() == image_height
+        && scene.iter().zip(aovs).all(|(scene_row, aov_row)| {
+            scene_row.len() == image_width && aov_row.len() == image_width
+        });
+    if !same_size {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the scene and its output variables do not have the same size",
+        ));
+    }
+
+    let beauty = scene
+        .iter()
+        .map(|row| row.iter().map(|pixel| pixel.color()).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let object_ids = aovs
+        .iter()
+        .rev()
+        .flatten()
+        .map(|pixel| pixel.object_id.map_or(0, |id| id + 1))
+        .collect();
+
+    let channels = vec![
+        channel("R", &beauty, |color| color.r()),
+        channel("G", &beauty, |color| color.g()),
+        channel("B", &beauty, |color| color.b()),
+        channel("depth.Z", aovs, |pixel| pixel.depth),
+        channel("normal.X", aovs, |pixel| pixel.normal.x()),
+        channel("normal.Y", aovs, |pixel| pixel.normal.y()),
+        channel("normal.Z", aovs, |pixel| pixel.normal.z()),
+        channel("albedo.R", aovs, |pixel| pixel.albedo.r()),
+        channel("albedo.G", aovs, |pixel| pixel.albedo.g()),
+        channel("albedo.B", aovs, |pixel| pixel.albedo.b()),
+        channel("position.X", aovs, |pixel| pixel.position.x()),
+        channel("position.Y", aovs, |pixel| pixel.position.y()),
+        channel("position.Z", aovs, |pixel| pixel.position.z()),
+        AnyChannel::new("object.id", FlatSamples::U32(object_ids)),
+    ];
+
+    let layer = Layer::new(
+        (image_width, image_height),
+        LayerAttributes::default(),
+        Encoding::FAST_LOSSLESS,
+        AnyChannels::sort(SmallVec::from_vec(channels)),
+    );
+
+    Image::from_layer(layer)
+        .write()
+        .to_file(path)
+        .map_err(io::Error::other)
+}
+
+/// a 32 bits float channel. EXR lines go from top to bottom, our images from bottom to top
+fn channel<T>(name: &str, image: &[Vec<T>], value: impl Fn(&T) -> f64) -> AnyChannel<FlatSamples> {
+    let samples = image
+        .iter()
+        .rev()
+        .flatten()
+        .map(|pixel| value(pixel) as f32)
+        .collect();
+
+    AnyChannel::new(name, FlatSamples::F32(samples))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        hittable::{HittableList, Sphere},
+        material::Lambertian,
+    };
+
+    fn camera() -> Camera {
+        Camera::new(
+            &Point3::new(0.0, 0.0, 5.0),
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            5.0,
+        )
+    }
+
+    #[test]
+    fn it_should_report_the_first_hit() {
+        let material_ground = Lambertian::new(&Color3::black());
+        let material = Lambertian::new(&Color3::new(0.8, 0.4, 0.2));
+        // far away, not visible from the camera
+        let ground = Sphere::new(&Point3::new(0.0, -1000.0, 0.0), 1.0, &material_ground);
+        let sphere = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+        let mut world = HittableList::new();
+        world.add(&ground);
+        world.add(&sphere);
+
+        // pixels are mapped with (j + 0.5) / (width - 1): the center of pixel 4 is the center of the image
+        let aovs = compute_aovs(&camera(), &world, 10, 10, 1);
+
+        let center = aovs[4][4];
+        assert!((center.depth - 4.0).abs() < 1e-9);
+        assert!((center.normal - Vec3::new(0.0, 0.0, 1.0)).is_near_zero());
+        assert!((center.position - Point3::new(0.0, 0.0, 1.0)).is_near_zero());
+        assert_eq!(center.albedo, Color3::new(0.8, 0.4, 0.2));
+        assert_eq!(center.object_id, Some(1));
+
+        let corner = aovs[0][0];
+        assert_eq!(corner.depth, f64::INFINITY);
+        assert_eq!(corner.object_id, None);
+        assert!(corner.normal.is_near_zero());
+    }
+
+    #[test]
+    fn it_should_save_every_output_as_an_exr_layer() {
+        let world = HittableList::new();
+        let aovs = compute_aovs(&camera(), &world, 3, 2, 2);
+        let scene = vec![vec![PixelStats::new(); 3]; 2];
+
+        let path = std::env::temp_dir().join(format!("aovs-{}.exr", std::process::id()));
+        save_aovs_exr(&path, &scene, &aovs).unwrap();
+
+        let image = exr::prelude::read_all_flat_layers_from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let names = image.layer_data[0]
+            .channel_data
+            .list
+            .iter()
+            .map(|channel| channel.name.to_string())
+            .collect::<Vec<_>>();
+
+        assert_eq!(names.len(), 14);
+        for name in [
+            "R",
+            "depth.Z",
+            "normal.Y",
+            "albedo.B",
+            "position.X",
+            "object.id",
+        ] {
+            assert!(names.contains(&name.to_string()), "missing channel {name}");
+        }
+    }
+
+    #[test]
+    fn it_should_refuse_outputs_of_another_size() {
+        let world = HittableList::new();
+        let aovs = compute_aovs(&camera(), &world, 3, 2, 1);
+        let path = std::env::temp_dir().join(format!("aovs-size-{}.exr", std::process::id()));
+
+        for scene in [
+            vec![vec![PixelStats::new(); 3]; 3],
+            vec![vec![PixelStats::new(); 4]; 2],
+            vec![vec![PixelStats::new(); 3], vec![PixelStats::new(); 2]],
+        ] {
+            let error = save_aovs_exr(&path, &scene, &aovs).unwrap_err();
+            assert_eq!(error.kind(), io::ErrorKind::InvalidInput);
+        }
+        assert!(!path.exists());
+    }
+}
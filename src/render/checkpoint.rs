@@ -0,0 +1,389 @@
+use std::{
+    error::Error,
+    fmt,
+    fs::File,
+    hash::{Hash, Hasher},
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+use super::{Integrator, PixelStats, RenderState};
+use crate::{hittable::Hittable, Camera};
+
+/// first bytes of every checkpoint file, with the version of the format
+const MAGIC: &[u8; 8] = b"RTCKPT01";
+
+/// the size of everything before the pixels: magic, hash, seed, passes, width and height
+const HEADER_SIZE: u64 = 8 + 8 + 8 + 8 + 4 + 4;
+
+/// Reasons to refuse loading a checkpoint
+#[derive(Debug)]
+pub enum CheckpointError {
+    /// the file could not be read (or is truncated)
+    Io(io::Error),
+    /// the file is not a checkpoint, or was written by an incompatible version
+    InvalidFormat,
+    /// the checkpoint was rendered from another scene
+    SceneChanged { expected: u64, found: u64 },
+}
+
+impl fmt::Display for CheckpointError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckpointError::Io(error) => write!(f, "could not read the checkpoint: {error}"),
+            CheckpointError::InvalidFormat => write!(f, "the file is not a valid checkpoint"),
+            CheckpointError::SceneChanged { expected, found } => write!(
+                f,
+                "the checkpoint belongs to another scene (scene hash {found:016x}, expected {expected:016x})"
+            ),
+        }
+    }
+}
+
+impl Error for CheckpointError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CheckpointError::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for CheckpointError {
+    fn from(error: io::Error) -> Self {
+        CheckpointError::Io(error)
+    }
+}
+
+// A checkpoint is a snapshot of a render in progress, tied to the scene it was rendered from.
+// file layout (little endian):
+// magic "RTCKPT01", scene hash (u64), seed (u64), number of passes (u64), width (u32), height (u32),
+// then the raw statistics of every pixel, line after line
+
+/// write the render state to `path`. The file is written next to the target and then renamed,
+/// so a render killed while saving never leaves a corrupted checkpoint behind
+pub fn save_checkpoint(path: &Path, state: &RenderState, scene_hash: u64) -> io::Result<()> {
+    let temporary_path = path.with_extension("tmp");
+
+    {
+        let mut writer = BufWriter::new(File::create(&temporary_path)?);
+
+        writer.write_all(MAGIC)?;
+        writer.write_all(&scene_hash.to_le_bytes())?;
+        writer.write_all(&state.seed().to_le_bytes())?;
+        writer.write_all(&state.nb_passes().to_le_bytes())?;
+        writer.write_all(&state.image_width().to_le_bytes())?;
+        writer.write_all(&state.image_height().to_le_bytes())?;
+
+        for pixel in state.scene().iter().flatten() {
+            pixel.write_to(&mut writer)?;
+        }
+
+        writer.flush()?;
+    }
+
+    std::fs::rename(temporary_path, path)
+}
+
+/// load a render state, refusing checkpoints of another scene
+pub fn load_checkpoint(path: &Path, scene_hash: u64) -> Result<RenderState, CheckpointError> {
+    let file = File::open(path)?;
+    let file_size = file.metadata()?.len();
+    let mut reader = BufReader::new(file);
+
+    let mut magic = [0; 8];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(CheckpointError::InvalidFormat);
+    }
+
+    let found = read_u64(&mut reader)?;
+    if found != scene_hash {
+        return Err(CheckpointError::SceneChanged {
+            expected: scene_hash,
+            found,
+        });
+    }
+
+    let seed = read_u64(&mut reader)?;
+    let nb_passes = read_u64(&mut reader)?;
+    let image_width = read_u32(&mut reader)?;
+    let image_height = read_u32(&mut reader)?;
+
+    // the size must be checked before reading the pixels: a corrupted header could make
+    // the loop below run (and allocate rows) far beyond the end of the file
+    let pixels_size = (image_width as u64)
+        .checked_mul(image_height as u64)
+        .and_then(|nb_pixels| nb_pixels.checked_mul(PixelStats::SERIALIZED_SIZE))
+        .and_then(|size| size.checked_add(HEADER_SIZE))
+        .ok_or(CheckpointError::InvalidFormat)?;
+    if pixels_size > file_size {
+        return Err(CheckpointError::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "the checkpoint is truncated",
+        )));
+    }
+    // rows without pixels take no room in the file: their number can not be checked
+    if image_width == 0 && image_height != 0 {
+        return Err(CheckpointError::InvalidFormat);
+    }
+
+    // no capacity reserved from the header: a corrupted size must fail on read, not on allocation
+    let mut scene = Vec::new();
+    for _ in 0..image_height {
+        let row = (0..image_width)
+            .map(|_| PixelStats::read_from(&mut reader))
+            .collect::<io::Result<Vec<_>>>()?;
+        scene.push(row);
+    }
+
+    // trailing bytes mean the file was not written by `save_checkpoint`
+    if reader.read(&mut [0])? != 0 {
+        return Err(CheckpointError::InvalidFormat);
+    }
+
+    Ok(RenderState::from_raw(scene, seed, nb_passes))
+}
+
+/// A hash identifying a scene: anything that changes the rendered image changes the hash.
+/// It is computed from the debug representation of the camera and of the world, which includes
+/// every shape, material and their exact parameters (distance fields are known by their name).
+/// The sampling settings are left out on purpose: a render can be resumed with a larger sample budget
+pub fn scene_hash(
+    camera: &Camera,
+    world: &dyn Hittable,
+    image_width: u32,
+    image_height: u32,
+    max_depth: u32,
+    integrator: Integrator,
+) -> u64 {
+    let mut hasher = Fnv1aHasher::new();
+
+    hasher.hash_debug(camera);
+    hasher.hash_debug(world);
+    image_width.hash(&mut hasher);
+    image_height.hash(&mut hasher);
+    max_depth.hash(&mut hasher);
+    hasher.hash_debug(&integrator);
+
+    hasher.finish()
+}
+
+/// FNV-1a. Unlike the hasher of the standard library, its output is stable
+/// across rust versions and platforms, which is required for hashes stored in files
+struct Fnv1aHasher(u64);
+
+impl Fnv1aHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    /// hash the debug representation of a value, as hashing `format!("{value:?}")` would.
+    /// It is streamed to the hasher: the representation of a large world never sits in memory
+    fn hash_debug(&mut self, value: &(impl fmt::Debug + ?Sized)) {
+        use fmt::Write;
+
+        write!(self, "{value:?}").expect("hashing can not fail");
+        // the end of a string, as written by `str::hash`
+        self.write_u8(0xff);
+    }
+}
+
+/// the formatted text goes straight to the hasher
+impl fmt::Write for Fnv1aHasher {
+    fn write_str(&mut self, text: &str) -> fmt::Result {
+        Hasher::write(self, text.as_bytes());
+        Ok(())
+    }
+}
+
+impl Hasher for Fnv1aHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+pub(super) fn read_f64(reader: &mut impl Read) -> io::Result<f64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(f64::from_le_bytes(bytes))
+}
+
+pub(super) fn read_u64(reader: &mut impl Read) -> io::Result<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+pub(super) fn read_u32(reader: &mut impl Read) -> io::Result<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        hittable::{HittableList, Sphere},
+        material::Lambertian,
+        render::{render_pass, AdaptiveSampling},
+        Color3, Point3, Vec3,
+    };
+
+    fn camera() -> Camera {
+        Camera::new(
+            &Point3::new(0.0, 0.0, 5.0),
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            5.0,
+        )
+    }
+
+    fn checkpoint_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("{name}-{}.checkpoint", std::process::id()))
+    }
+
+    #[test]
+    fn it_should_resume_exactly_where_the_render_stopped() {
+        let material = Lambertian::new(&Color3::new(0.5, 0.5, 0.5));
+        let sphere = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+        let mut world = HittableList::new();
+        world.add(&sphere);
+
+        let sampling = AdaptiveSampling::fixed(4);
+        let hash = scene_hash(&camera(), &world, 6, 4, 10, Integrator::Rgb);
+        let path = checkpoint_path("resume");
+
+        // an uninterrupted render
+        let mut reference = RenderState::new(6, 4, 42);
+        while render_pass(
+            &camera(),
+            &world,
+            &mut reference,
+            &sampling,
+            10,
+            Integrator::Rgb,
+        ) > 0
+        {}
+
+        // the same render, killed after 2 passes
+        let mut state = RenderState::new(6, 4, 42);
+        render_pass(
+            &camera(),
+            &world,
+            &mut state,
+            &sampling,
+            10,
+            Integrator::Rgb,
+        );
+        render_pass(
+            &camera(),
+            &world,
+            &mut state,
+            &sampling,
+            10,
+            Integrator::Rgb,
+        );
+        save_checkpoint(&path, &state, hash).unwrap();
+
+        let mut resumed = load_checkpoint(&path, hash).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(resumed, state);
+
+        while render_pass(
+            &camera(),
+            &world,
+            &mut resumed,
+            &sampling,
+            10,
+            Integrator::Rgb,
+        ) > 0
+        {}
+        assert_eq!(resumed, reference);
+    }
+
+    #[test]
+    fn it_should_refuse_to_resume_another_scene() {
+        let material = Lambertian::new(&Color3::new(0.5, 0.5, 0.5));
+        let sphere = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+        let moved_sphere = Sphere::new(&Point3::new(0.0, 0.1, 0.0), 1.0, &material);
+
+        let mut world = HittableList::new();
+        world.add(&sphere);
+        let mut changed_world = HittableList::new();
+        changed_world.add(&moved_sphere);
+
+        let hash = scene_hash(&camera(), &world, 6, 4, 10, Integrator::Rgb);
+        let changed_hash = scene_hash(&camera(), &changed_world, 6, 4, 10, Integrator::Rgb);
+        assert_ne!(hash, changed_hash);
+
+        let path = checkpoint_path("changed");
+        save_checkpoint(&path, &RenderState::new(6, 4, 42), hash).unwrap();
+
+        let result = load_checkpoint(&path, changed_hash);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(CheckpointError::SceneChanged { .. })));
+    }
+
+    #[test]
+    fn it_should_reject_truncated_files() {
+        let path = checkpoint_path("truncated");
+        save_checkpoint(&path, &RenderState::new(6, 4, 42), 7).unwrap();
+
+        let bytes = std::fs::read(&path).unwrap();
+        std::fs::write(&path, &bytes[..bytes.len() - 1]).unwrap();
+
+        let result = load_checkpoint(&path, 7);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(result, Err(CheckpointError::Io(_))));
+    }
+
+    #[test]
+    fn it_should_check_the_size_before_reading_the_pixels() {
+        let path = checkpoint_path("oversized");
+        save_checkpoint(&path, &RenderState::new(6, 4, 42), 7).unwrap();
+
+        // a corrupted height: the file is far too small for it
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes[36..40].copy_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(&path, &bytes).unwrap();
+        let too_high = load_checkpoint(&path, 7);
+
+        // no pixel per row, as many rows as possible
+        bytes[32..36].copy_from_slice(&0_u32.to_le_bytes());
+        std::fs::write(&path, &bytes[..HEADER_SIZE as usize]).unwrap();
+        let empty_rows = load_checkpoint(&path, 7);
+        std::fs::remove_file(&path).unwrap();
+
+        assert!(matches!(too_high, Err(CheckpointError::Io(_))));
+        assert!(matches!(empty_rows, Err(CheckpointError::InvalidFormat)));
+    }
+
+    #[test]
+    fn it_should_hash_debug_representations_without_formatting_them() {
+        let material = Lambertian::new(&Color3::new(0.5, 0.5, 0.5));
+        let sphere = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+        let mut world = HittableList::new();
+        world.add(&sphere);
+
+        let mut streamed = Fnv1aHasher::new();
+        streamed.hash_debug(&world);
+        let mut formatted = Fnv1aHasher::new();
+        format!("{world:?}").hash(&mut formatted);
+
+        assert_eq!(streamed.finish(), formatted.finish());
+    }
+}
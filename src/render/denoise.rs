@@ -0,0 +1,300 @@
+use super::AovPixel;
+use crate::Color3;
+
+/// A feature guided denoiser for renders with few samples per pixel: a joint (cross) bilateral filter.
+/// Each pixel becomes a weighted mean of its neighbours. A neighbour only counts if it looks
+/// like the same surface: close albedo, close normal and close depth in the output variables,
+/// which are almost noise free. Edges between objects are kept, noise within a surface is smoothed.
+///
+/// The filter works on the illumination (color / albedo), then multiplies the albedo back,
+/// so textures are not blurred
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Denoiser {
+    /// half size of the filtering window, in pixels
+    radius: u32,
+    /// standard deviation of the spatial gaussian, in pixels
+    sigma_spatial: f64,
+    /// tolerated difference in albedo
+    sigma_albedo: f64,
+    /// tolerated difference in normal (distance between the vectors)
+    sigma_normal: f64,
+    /// tolerated difference in depth, relative to the depth of the filtered pixel
+    sigma_depth: f64,
+}
+
+impl Default for Denoiser {
+    fn default() -> Self {
+        Self::new(5, 3.0, 0.1, 0.3, 0.05)
+    }
+}
+
+impl Denoiser {
+    /// # panics
+    /// if a standard deviation is not positive
+    pub fn new(
+        radius: u32,
+        sigma_spatial: f64,
+        sigma_albedo: f64,
+        sigma_normal: f64,
+        sigma_depth: f64,
+    ) -> Self {
+        assert!(
+            [sigma_spatial, sigma_albedo, sigma_normal, sigma_depth]
+                .iter()
+                .all(|sigma| *sigma > 0.0),
+            "the standard deviations of the filter must be positive"
+        );
+
+        Self {
+            radius,
+            sigma_spatial,
+            sigma_albedo,
+            sigma_normal,
+            sigma_depth,
+        }
+    }
+
+    /// denoise an image (a vector of rows of averaged colors, as saved in ppm),
+    /// guided by the output variables of the same render
+    pub fn denoise(&self, image: &[Vec<Color3>], aovs: &[Vec<AovPixel>]) -> Vec<Vec<Color3>> {
+        let image_height = image.len();
+        let image_width = image.first().map_or(0, |row| row.len());
+        assert!(
+            aovs.len() == image_height && aovs.iter().all(|row| row.len() == image_width),
+            "the output variables do not match the image size"
+        );
+
+        // filtering the illumination only: the albedo is put back after filtering
+        let illumination = image
+            .iter()
+            .zip(aovs)
+            .map(|(row, aov_row)| {
+                row.iter()
+                    .zip(aov_row)
+                    .map(|(color, aov)| demodulate(color, &aov.albedo))
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        let radius = self.radius as isize;
+
+        (0..image_height)
+            .map(|i| {
+                (0..image_width)
+                    .map(|j| {
+                        let center = &aovs[i][j];
+                        let mut sum = Color3::black();
+                        let mut total_weight = 0.0;
+
+                        for di in -radius..=radius {
+                            for dj in -radius..=radius {
+                                let (Some(k), Some(l)) = (
+                                    i.checked_add_signed(di).filter(|&k| k < image_height),
+                                    j.checked_add_signed(dj).filter(|&l| l < image_width),
+                                ) else {
+                                    continue;
+                                };
+
+                                let distance_squared = (di * di + dj * dj) as f64;
+                                let weight = self.weight(distance_squared, center, &aovs[k][l]);
+
+                                sum += illumination[k][l] * weight;
+                                total_weight += weight;
+                            }
+                        }
+
+                        // the center pixel always has a weight of 1: no division by zero
+                        remodulate(&(sum / total_weight), &center.albedo)
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// weight of a neighbour, from its distance to the filtered pixel and the similarity of their features
+    fn weight(&self, distance_squared: f64, center: &AovPixel, neighbour: &AovPixel) -> f64 {
+        let spatial = distance_squared / (2.0 * self.sigma_spatial * self.sigma_spatial);
+
+        let albedo = center.albedo.distance(&neighbour.albedo).powi(2)
+            / (2.0 * self.sigma_albedo * self.sigma_albedo);
+
+        let normal = (center.normal - neighbour.normal).mag_squared()
+            / (2.0 * self.sigma_normal * self.sigma_normal);
+
+        let depth = if center.depth.is_finite() && neighbour.depth.is_finite() {
+            let relative = (center.depth - neighbour.depth) / center.depth.max(1e-6);
+            relative * relative / (2.0 * self.sigma_depth * self.sigma_depth)
+        } else if center.depth.is_finite() != neighbour.depth.is_finite() {
+            // one sees the sky, the other does not: not the same surface
+            f64::INFINITY
+        } else {
+            0.0
+        };
+
+        (-(spatial + albedo + normal + depth)).exp()
+    }
+}
+
+/// divide a color by an albedo, channel by channel (dark albedo channels are left as they are)
+fn demodulate(color: &Color3, albedo: &Color3) -> Color3 {
+    let channel = |value: f64, albedo: f64| {
+        if albedo > MIN_ALBEDO {
+            value / albedo
+        } else {
+            value
+        }
+    };
+
+    Color3::new(
+        channel(color.r(), albedo.r()),
+        channel(color.g(), albedo.g()),
+        channel(color.b(), albedo.b()),
+    )
+}
+
+/// inverse of `demodulate`
+fn remodulate(illumination: &Color3, albedo: &Color3) -> Color3 {
+    let channel = |value: f64, albedo: f64| {
+        if albedo > MIN_ALBEDO {
+            value * albedo
+        } else {
+            value
+        }
+    };
+
+    Color3::new(
+        channel(illumination.r(), albedo.r()),
+        channel(illumination.g(), albedo.g()),
+        channel(illumination.b(), albedo.b()),
+    )
+}
+
+/// below this albedo, a channel is considered black and is filtered without demodulation
+const MIN_ALBEDO: f64 = 1e-3;
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+    use crate::{Point3, Vec3};
+
+    const WIDTH: usize = 32;
+    const HEIGHT: usize = 24;
+
+    /// two walls meeting in the middle of the image: a red one facing the camera on the left,
+    /// a blue one facing up on the right. Both receive the same constant lighting
+    fn synthetic_scene() -> (Vec<Vec<Color3>>, Vec<Vec<AovPixel>>) {
+        let lighting = 0.8;
+        let mut image = Vec::new();
+        let mut aovs = Vec::new();
+
+        for _ in 0..HEIGHT {
+            let mut row = Vec::new();
+            let mut aov_row = Vec::new();
+
+            for j in 0..WIDTH {
+                let (albedo, normal) = if j < WIDTH / 2 {
+                    (Color3::new(0.9, 0.1, 0.1), Vec3::new(0.0, 0.0, 1.0))
+                } else {
+                    (Color3::new(0.1, 0.2, 0.9), Vec3::new(0.0, 1.0, 0.0))
+                };
+
+                row.push(albedo * lighting);
+                aov_row.push(AovPixel {
+                    depth: 5.0,
+                    normal,
+                    albedo,
+                    position: Point3::new(0.0, 0.0, 0.0),
+                    object_id: Some(0),
+                });
+            }
+
+            image.push(row);
+            aovs.push(aov_row);
+        }
+
+        (image, aovs)
+    }
+
+    fn add_noise(image: &[Vec<Color3>], rng: &mut StdRng) -> Vec<Vec<Color3>> {
+        image
+            .iter()
+            .map(|row| {
+                row.iter()
+                    // multiplicative noise, like a path tracer with few samples
+                    .map(|color| *color * rng.gen_range(0.2..1.8))
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn mean_squared_error(image: &[Vec<Color3>], reference: &[Vec<Color3>]) -> f64 {
+        let errors = image
+            .iter()
+            .flatten()
+            .zip(reference.iter().flatten())
+            .map(|(color, expected)| color.distance(expected).powi(2))
+            .collect::<Vec<_>>();
+
+        errors.iter().sum::<f64>() / errors.len() as f64
+    }
+
+    #[test]
+    fn it_should_remove_most_of_the_noise() {
+        let (reference, aovs) = synthetic_scene();
+        let noisy = add_noise(&reference, &mut StdRng::seed_from_u64(0));
+
+        let denoised = Denoiser::default().denoise(&noisy, &aovs);
+
+        let noisy_error = mean_squared_error(&noisy, &reference);
+        let denoised_error = mean_squared_error(&denoised, &reference);
+
+        assert!(
+            denoised_error < noisy_error / 10.0,
+            "error went from {noisy_error} to {denoised_error}"
+        );
+    }
+
+    #[test]
+    fn it_should_keep_edges_between_surfaces() {
+        let (reference, aovs) = synthetic_scene();
+        let noisy = add_noise(&reference, &mut StdRng::seed_from_u64(1));
+
+        let denoised = Denoiser::default().denoise(&noisy, &aovs);
+
+        // the columns on each side of the edge must not bleed into each other:
+        // averaged over the lines (to reduce the remaining noise), they keep their own color.
+        // Mixing both walls evenly would put them off by more than 0.3
+        for j in [WIDTH / 2 - 1, WIDTH / 2] {
+            let column_mean = |image: &[Vec<Color3>]| {
+                let mut sum = Color3::black();
+                for row in image {
+                    sum += row[j];
+                }
+                sum / HEIGHT as f64
+            };
+
+            let difference = column_mean(&denoised) - column_mean(&reference);
+            assert!(
+                difference.distance(&Color3::black()) < 0.1,
+                "column {j} is off by {difference:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn it_should_not_change_a_clean_image() {
+        let (reference, aovs) = synthetic_scene();
+
+        let denoised = Denoiser::default().denoise(&reference, &aovs);
+
+        assert!(mean_squared_error(&denoised, &reference) < 1e-20);
+    }
+
+    #[test]
+    #[should_panic]
+    fn it_should_reject_a_zero_standard_deviation() {
+        Denoiser::new(5, 3.0, 0.0, 0.3, 0.05);
+    }
+}
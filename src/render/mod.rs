@@ -0,0 +1,25 @@
+mod adaptive;
+pub use adaptive::AdaptiveSampling;
+
+mod aov;
+pub use aov::{compute_aovs, save_aovs_exr, AovPixel};
+
+mod checkpoint;
+pub use checkpoint::{load_checkpoint, save_checkpoint, scene_hash, CheckpointError};
+
+mod denoise;
+pub use denoise::Denoiser;
+
+mod pixel_stats;
+pub use pixel_stats::PixelStats;
+
+mod progressive;
+pub use progressive::{ProgressiveRendering, StopReason};
+
+mod renderer;
+pub use renderer::{
+    background, compute_scene, ray_color, render_pass, spectral_ray_color, Integrator,
+};
+
+mod render_state;
+pub use render_state::RenderState;
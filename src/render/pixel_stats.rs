@@ -0,0 +1,146 @@
+use std::io::{self, Read, Write};
+
+use super::checkpoint::{read_f64, read_u32};
+use crate::Color3;
+
+/// Running statistics of the samples taken for a single pixel.
+/// The mean and variance are tracked online (Welford's algorithm) on the luminance of each sample,
+/// so we never need to keep the samples themselves
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PixelStats {
+    /// sum of all the sampled colors (not divided by the number of samples)
+    sum: Color3,
+    /// number of samples taken so far
+    count: u32,
+    /// running mean of the samples luminance
+    mean: f64,
+    /// running sum of the squared distances to the mean
+    m2: f64,
+}
+
+impl Default for PixelStats {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PixelStats {
+    /// a pixel without any sample
+    pub fn new() -> Self {
+        Self {
+            sum: Color3::black(),
+            count: 0,
+            mean: 0.0,
+            m2: 0.0,
+        }
+    }
+
+    /// register a new sample for this pixel
+    pub fn add_sample(&mut self, color: &Color3) {
+        self.sum += *color;
+        self.count += 1;
+
+        let luminance = color.luminance();
+        let delta = luminance - self.mean;
+        self.mean += delta / self.count as f64;
+        self.m2 += delta * (luminance - self.mean);
+    }
+
+    /// sum of all the samples colors. Divide by `count` to get the pixel color
+    pub fn sum(&self) -> Color3 {
+        self.sum
+    }
+
+    /// the color of the pixel: the mean of its samples (black without samples)
+    pub fn color(&self) -> Color3 {
+        self.sum / self.count.max(1) as f64
+    }
+
+    /// number of samples taken
+    pub fn count(&self) -> u32 {
+        self.count
+    }
+
+    /// mean luminance of the samples
+    pub fn mean(&self) -> f64 {
+        self.mean
+    }
+
+    /// unbiased variance of the samples luminance (0 until 2 samples are taken)
+    pub fn variance(&self) -> f64 {
+        if self.count < 2 {
+            return 0.0;
+        }
+
+        self.m2 / (self.count - 1) as f64
+    }
+
+    /// estimated error of the pixel value: standard error of the mean, relative to the mean.
+    /// Relative error is used so dark and bright pixels are judged alike
+    /// (the mean is floored to avoid dividing by zero on black pixels)
+    pub fn relative_error(&self) -> f64 {
+        if self.count == 0 {
+            return f64::INFINITY;
+        }
+
+        let standard_error = (self.variance() / self.count as f64).sqrt();
+
+        standard_error / self.mean.max(0.001)
+    }
+
+    /// the number of bytes written by `write_to`
+    pub(crate) const SERIALIZED_SIZE: u64 = 3 * 8 + 4 + 8 + 8;
+
+    /// write the raw statistics, little endian (used by checkpoints)
+    pub(crate) fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        for value in [self.sum.r(), self.sum.g(), self.sum.b()] {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        writer.write_all(&self.count.to_le_bytes())?;
+        writer.write_all(&self.mean.to_le_bytes())?;
+        writer.write_all(&self.m2.to_le_bytes())
+    }
+
+    /// read raw statistics written by `write_to`
+    pub(crate) fn read_from(reader: &mut impl Read) -> io::Result<Self> {
+        Ok(Self {
+            sum: Color3::new(read_f64(reader)?, read_f64(reader)?, read_f64(reader)?),
+            count: read_u32(reader)?,
+            mean: read_f64(reader)?,
+            m2: read_f64(reader)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_compute_mean_and_variance() {
+        let mut stats = PixelStats::new();
+
+        // luminance of grey colors is the grey level itself
+        for value in [1.0, 2.0, 3.0, 4.0] {
+            stats.add_sample(&Color3::new(value, value, value));
+        }
+
+        assert_eq!(stats.count(), 4);
+        assert_eq!(stats.sum(), Color3::new(10.0, 10.0, 10.0));
+        assert!((stats.mean() - 2.5).abs() < 1e-12);
+        // unbiased variance of 1, 2, 3, 4
+        assert!((stats.variance() - 5.0 / 3.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn it_should_have_no_error_on_constant_samples() {
+        let mut stats = PixelStats::new();
+        assert_eq!(stats.relative_error(), f64::INFINITY);
+
+        for _ in 0..10 {
+            stats.add_sample(&Color3::new(0.5, 0.7, 1.0));
+        }
+
+        assert!(stats.relative_error() < 1e-12);
+    }
+}
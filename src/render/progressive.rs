@@ -0,0 +1,188 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+
+use super::{render_pass, AdaptiveSampling, Integrator, RenderState};
+use crate::{hittable::Hittable, Camera};
+
+/// Why a progressive render stopped
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StopReason {
+    /// every pixel reached its target sample count (or converged)
+    Done,
+    /// the wall-clock time budget is spent
+    TimeLimit,
+    /// the interrupt flag was raised (e.g. ctrl-c)
+    Interrupted,
+}
+
+/// Drives a progressive render: the scene is refined one sample per pixel per pass,
+/// so a usable image exists after every pass.
+/// The render stops when the sampling is done, when the time limit is reached, or when interrupted.
+/// Both checks happen between passes: the current pass is always completed
+pub struct ProgressiveRendering {
+    /// the current image is handed to the update callback every `passes_per_update` passes (0: never)
+    passes_per_update: u32,
+    /// wall-clock budget of the render
+    time_limit: Option<Duration>,
+    /// once set to true, the render stops after the current pass
+    interrupted: Arc<AtomicBool>,
+}
+
+impl ProgressiveRendering {
+    pub fn new(passes_per_update: u32, time_limit: Option<Duration>) -> Self {
+        Self {
+            passes_per_update,
+            time_limit,
+            interrupted: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// the flag to raise to stop the render, e.g. from a ctrl-c handler
+    pub fn interrupt_flag(&self) -> Arc<AtomicBool> {
+        self.interrupted.clone()
+    }
+
+    /// render passes into `state` until a stop condition is met.
+    /// `on_update` receives the current state every `passes_per_update` passes.
+    /// The state is not required to be empty: rendering continues from the samples already accumulated
+    /// (e.g. from a checkpoint)
+    #[allow(clippy::too_many_arguments)]
+    pub fn render(
+        &self,
+        camera: &Camera,
+        world: &dyn Hittable,
+        state: &mut RenderState,
+        sampling: &AdaptiveSampling,
+        max_depth: u32,
+        integrator: Integrator,
+        mut on_update: impl FnMut(&RenderState),
+    ) -> StopReason {
+        let starting_time = Instant::now();
+
+        loop {
+            if self.interrupted.load(Ordering::SeqCst) {
+                return StopReason::Interrupted;
+            }
+
+            if let Some(time_limit) = self.time_limit {
+                if starting_time.elapsed() >= time_limit {
+                    return StopReason::TimeLimit;
+                }
+            }
+
+            if render_pass(camera, world, state, sampling, max_depth, integrator) == 0 {
+                return StopReason::Done;
+            }
+
+            if self.passes_per_update > 0
+                && state
+                    .nb_passes()
+                    .is_multiple_of(self.passes_per_update as u64)
+            {
+                on_update(state);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{hittable::HittableList, Point3, Vec3};
+
+    fn camera() -> Camera {
+        Camera::new(
+            &Point3::new(0.0, 0.0, 5.0),
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            5.0,
+        )
+    }
+
+    #[test]
+    fn it_should_render_until_the_target_sample_count() {
+        let world = HittableList::new();
+        let mut state = RenderState::new(4, 4, 0);
+        let progressive = ProgressiveRendering::new(2, None);
+
+        let mut updates = Vec::new();
+        let reason = progressive.render(
+            &camera(),
+            &world,
+            &mut state,
+            &AdaptiveSampling::fixed(5),
+            10,
+            Integrator::Rgb,
+            |state| updates.push((state.nb_passes(), state.scene()[0][0].count())),
+        );
+
+        assert_eq!(reason, StopReason::Done);
+        assert_eq!(updates, vec![(2, 2), (4, 4)]);
+        assert!(state
+            .scene()
+            .iter()
+            .flatten()
+            .all(|pixel| pixel.count() == 5));
+    }
+
+    #[test]
+    fn it_should_stop_when_interrupted() {
+        let world = HittableList::new();
+        let mut state = RenderState::new(4, 4, 0);
+        let progressive = ProgressiveRendering::new(1, None);
+        let interrupted = progressive.interrupt_flag();
+
+        let reason = progressive.render(
+            &camera(),
+            &world,
+            &mut state,
+            &AdaptiveSampling::fixed(1000),
+            10,
+            Integrator::Rgb,
+            |state| {
+                if state.nb_passes() == 3 {
+                    interrupted.store(true, Ordering::SeqCst);
+                }
+            },
+        );
+
+        assert_eq!(reason, StopReason::Interrupted);
+        assert!(state
+            .scene()
+            .iter()
+            .flatten()
+            .all(|pixel| pixel.count() == 3));
+    }
+
+    #[test]
+    fn it_should_stop_at_the_time_limit() {
+        let world = HittableList::new();
+        let mut state = RenderState::new(4, 4, 0);
+        let progressive = ProgressiveRendering::new(0, Some(Duration::ZERO));
+
+        let reason = progressive.render(
+            &camera(),
+            &world,
+            &mut state,
+            &AdaptiveSampling::fixed(1000),
+            10,
+            Integrator::Rgb,
+            |_| {},
+        );
+
+        assert_eq!(reason, StopReason::TimeLimit);
+        assert!(state
+            .scene()
+            .iter()
+            .flatten()
+            .all(|pixel| pixel.count() == 0));
+    }
+}
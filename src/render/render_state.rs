@@ -0,0 +1,99 @@
+use rand::{rngs::StdRng, SeedableRng};
+
+use super::PixelStats;
+
+/// Everything a render accumulates: the samples of every pixel and the state of the random numbers.
+/// Random numbers are drawn from generators reseeded for every line of every pass,
+/// so the state is only the base seed and the number of passes already done.
+/// This makes a render reproducible, and resumable from a checkpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderState {
+    /// accumulated samples, a vector of rows from the bottom line to the top one: scene[line][column]
+    scene: Vec<Vec<PixelStats>>,
+    /// base seed of all the random numbers of the render
+    seed: u64,
+    /// number of passes already rendered
+    nb_passes: u64,
+}
+
+impl RenderState {
+    /// a render without any sample
+    pub fn new(image_width: u32, image_height: u32, seed: u64) -> Self {
+        Self {
+            scene: vec![vec![PixelStats::new(); image_width as usize]; image_height as usize],
+            seed,
+            nb_passes: 0,
+        }
+    }
+
+    pub(crate) fn from_raw(scene: Vec<Vec<PixelStats>>, seed: u64, nb_passes: u64) -> Self {
+        Self {
+            scene,
+            seed,
+            nb_passes,
+        }
+    }
+
+    pub fn image_width(&self) -> u32 {
+        self.scene.first().map_or(0, |row| row.len()) as u32
+    }
+
+    pub fn image_height(&self) -> u32 {
+        self.scene.len() as u32
+    }
+
+    pub fn scene(&self) -> &[Vec<PixelStats>] {
+        &self.scene
+    }
+
+    pub fn into_scene(self) -> Vec<Vec<PixelStats>> {
+        self.scene
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub fn nb_passes(&self) -> u64 {
+        self.nb_passes
+    }
+
+    /// give mutable access to the lines of the scene, and a way to build their random generators for the next pass
+    pub(crate) fn next_pass(&mut self) -> (&mut [Vec<PixelStats>], PassSeed) {
+        let pass_seed = PassSeed {
+            seed: self.seed,
+            pass: self.nb_passes,
+        };
+
+        (&mut self.scene, pass_seed)
+    }
+
+    /// register that a pass was completed
+    pub(crate) fn end_pass(&mut self) {
+        self.nb_passes += 1;
+    }
+}
+
+/// Seeds the random generators of the lines of a pass
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct PassSeed {
+    seed: u64,
+    pass: u64,
+}
+
+impl PassSeed {
+    /// the random generator of a given line
+    pub(crate) fn line_rng(&self, line: u32) -> StdRng {
+        let mixed = splitmix64(self.seed ^ splitmix64(self.pass ^ splitmix64(line as u64)));
+
+        StdRng::seed_from_u64(mixed)
+    }
+}
+
+/// a cheap integer hash, spreading close inputs (e.g. consecutive line numbers) far apart
+fn splitmix64(value: u64) -> u64 {
+    let mut z = value.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
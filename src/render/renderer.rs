@@ -0,0 +1,377 @@
+use std::thread;
+
+use rand::{Rng, RngCore};
+
+use super::{AdaptiveSampling, PixelStats, RenderState};
+use crate::{
+    hittable::Hittable,
+    spectrum::{RgbSpectrum, SampledSpectrum, SampledWavelengths},
+    Camera, Color3, Ray,
+};
+
+/// How the light carried by the rays is represented
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Integrator {
+    /// three rgb channels
+    #[default]
+    Rgb,
+    /// a few wavelengths sampled per path (see the `spectrum` module).
+    /// Noisier, but renders the effects depending on the wavelength, like dispersion
+    Spectral,
+}
+
+impl Integrator {
+    /// the color of one sample: the light carried by a camera ray
+    pub fn sample(
+        &self,
+        ray: &Ray,
+        world: &dyn Hittable,
+        max_depth: u32,
+        rng: &mut dyn RngCore,
+    ) -> Color3 {
+        match self {
+            Integrator::Rgb => ray_color(ray, world, max_depth, rng),
+            Integrator::Spectral => {
+                let mut wavelengths = SampledWavelengths::sample(rng.gen());
+                let radiance = spectral_ray_color(ray, world, max_depth, &mut wavelengths, rng);
+
+                wavelengths.to_color(&radiance)
+            }
+        }
+    }
+}
+
+/// compute the color carried by a ray, bouncing at most `depth` times in the world
+pub fn ray_color(ray: &Ray, world: &dyn Hittable, depth: u32, rng: &mut dyn RngCore) -> Color3 {
+    // the ray bounced too many times, we abort the ray and return no light (black)
+    if depth == 0 {
+        return Color3::new(0.0, 0.0, 0.0);
+    }
+
+    // using 0.001 instead of 0.0 to fix shadow acne (ray reflected not exactly at 0)
+    if let Some(hit_record) = world.hit(ray, 0.001, f64::INFINITY) {
+        let (scattered, attenuation, is_reflected) =
+            hit_record.material.scatter(ray, &hit_record, rng);
+
+        if is_reflected {
+            return ray_color(&scattered, world, depth - 1, rng) * attenuation;
+        }
+
+        return Color3::black();
+    }
+
+    background(ray)
+}
+
+/// compute the light carried by a ray at the wavelengths of its path, bouncing at most `depth` times.
+/// Materials may terminate the secondary wavelengths on the way
+pub fn spectral_ray_color(
+    ray: &Ray,
+    world: &dyn Hittable,
+    depth: u32,
+    wavelengths: &mut SampledWavelengths,
+    rng: &mut dyn RngCore,
+) -> SampledSpectrum {
+    if depth == 0 {
+        return SampledSpectrum::constant(0.0);
+    }
+
+    if let Some(hit_record) = world.hit(ray, 0.001, f64::INFINITY) {
+        let (scattered, attenuation, is_reflected) =
+            hit_record
+                .material
+                .scatter_spectral(ray, &hit_record, wavelengths, rng);
+
+        if is_reflected {
+            return spectral_ray_color(&scattered, world, depth - 1, wavelengths, rng)
+                * attenuation;
+        }
+
+        return SampledSpectrum::constant(0.0);
+    }
+
+    RgbSpectrum::new(&background(ray)).sample(wavelengths)
+}
+
+/// the color of the sky seen by a ray that escaped the world: a vertical gradient from white to blue
+pub fn background(ray: &Ray) -> Color3 {
+    let unit_direction = ray.direction().normalize();
+    let t = 0.5 * (unit_direction.y() + 1.0);
+
+    Color3::white().lerp(&Color3::new(0.5, 0.7, 1.0), t)
+}
+
+/// render one pass: take one more sample for every pixel the sampling does not consider done.
+/// The samples are accumulated in the render state.
+/// Returns the number of samples taken, 0 meaning that every pixel is done
+pub fn render_pass(
+    camera: &Camera,
+    world: &dyn Hittable,
+    state: &mut RenderState,
+    sampling: &AdaptiveSampling,
+    max_depth: u32,
+    integrator: Integrator,
+) -> usize {
+    let image_height = state.image_height();
+    let image_width = state.image_width();
+
+    if image_width == 0 {
+        return 0;
+    }
+
+    let (scene, pass_seed) = state.next_pass();
+
+    let nb_thread = 6;
+
+    // taking in account rounding up
+    let nb_line_per_thread = if image_height.is_multiple_of(nb_thread) {
+        image_height / nb_thread
+    } else {
+        1 + image_height / nb_thread
+    };
+
+    let nb_samples = thread::scope(|scope| {
+        let mut handles = Vec::new();
+
+        // each thread owns a disjoint block of lines
+        for (num_thread, lines) in scene.chunks_mut(nb_line_per_thread as usize).enumerate() {
+            let first_line = num_thread as u32 * nb_line_per_thread;
+
+            let handle = scope.spawn(move || {
+                let mut nb_samples = 0;
+
+                for (i, row) in (first_line..).zip(lines.iter_mut()) {
+                    // a generator per line: the result does not depend on how lines are shared between threads
+                    let mut rng = pass_seed.line_rng(i);
+
+                    for (j, pixel) in (0..).zip(row.iter_mut()) {
+                        if sampling.is_done(pixel) {
+                            continue;
+                        }
+
+                        let u = (j as f64 + rng.gen::<f64>()) / (image_width - 1) as f64;
+                        let v = (i as f64 + rng.gen::<f64>()) / (image_height - 1) as f64;
+
+                        let ray = camera.get_ray(u, v, &mut rng);
+
+                        pixel.add_sample(&integrator.sample(&ray, world, max_depth, &mut rng));
+                        nb_samples += 1;
+                    }
+                }
+
+                nb_samples
+            });
+            handles.push(handle);
+        }
+
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .expect("an error occured while joining threads")
+            })
+            .sum()
+    });
+
+    if nb_samples > 0 {
+        state.end_pass();
+    }
+
+    nb_samples
+}
+
+/// compute a scene.
+/// The result is a vector of rows, from the bottom line to the top one: scene[line][column].
+/// Each pixel keeps its own statistics, as the adaptive sampling can give pixels different sample counts.
+/// The same seed gives the same image
+#[allow(clippy::too_many_arguments)]
+pub fn compute_scene(
+    camera: &Camera,
+    world: &dyn Hittable,
+    image_width: u32,
+    image_height: u32,
+    sampling: &AdaptiveSampling,
+    max_depth: u32,
+    integrator: Integrator,
+    seed: u64,
+) -> Vec<Vec<PixelStats>> {
+    let mut state = RenderState::new(image_width, image_height, seed);
+
+    while render_pass(camera, world, &mut state, sampling, max_depth, integrator) > 0 {}
+
+    state.into_scene()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        hittable::{HittableList, Sphere},
+        material::Lambertian,
+        Point3, Vec3,
+    };
+
+    fn camera() -> Camera {
+        Camera::new(
+            &Point3::new(0.0, 0.0, 5.0),
+            &Point3::new(0.0, 0.0, 0.0),
+            &Vec3::new(0.0, 1.0, 0.0),
+            40.0,
+            1.0,
+            0.0,
+            5.0,
+        )
+    }
+
+    #[test]
+    fn it_should_stop_sampling_the_sky_early() {
+        let world = HittableList::new();
+        let sampling = AdaptiveSampling::new(4, 256, 0.01);
+
+        let scene = compute_scene(&camera(), &world, 8, 8, &sampling, 10, Integrator::Rgb, 42);
+
+        // the sky gradient barely changes within a pixel: the minimum number of samples is enough
+        for row in &scene {
+            for pixel in row {
+                assert_eq!(pixel.count(), 4);
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_spend_more_samples_on_noisy_pixels() {
+        let material = Lambertian::new(&Color3::new(0.5, 0.5, 0.5));
+        let sphere = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+        let mut world = HittableList::new();
+        world.add(&sphere);
+
+        let sampling = AdaptiveSampling::new(4, 64, 0.01);
+        let scene = compute_scene(&camera(), &world, 8, 8, &sampling, 10, Integrator::Rgb, 42);
+
+        // the center pixel sees the diffuse sphere, the corner sees the sky
+        assert_eq!(scene[0][0].count(), 4);
+        assert!(scene[4][4].count() > 4);
+        assert!(scene[4][4].count() <= 64);
+    }
+
+    #[test]
+    fn it_should_take_one_sample_per_pixel_per_pass() {
+        let world = HittableList::new();
+        let sampling = AdaptiveSampling::fixed(3);
+        let mut state = RenderState::new(5, 4, 0);
+
+        assert_eq!(
+            render_pass(
+                &camera(),
+                &world,
+                &mut state,
+                &sampling,
+                10,
+                Integrator::Rgb
+            ),
+            20
+        );
+        assert!(state
+            .scene()
+            .iter()
+            .flatten()
+            .all(|pixel| pixel.count() == 1));
+
+        assert_eq!(
+            render_pass(
+                &camera(),
+                &world,
+                &mut state,
+                &sampling,
+                10,
+                Integrator::Rgb
+            ),
+            20
+        );
+        assert_eq!(
+            render_pass(
+                &camera(),
+                &world,
+                &mut state,
+                &sampling,
+                10,
+                Integrator::Rgb
+            ),
+            20
+        );
+
+        // the target sample count is reached: nothing left to do
+        assert_eq!(
+            render_pass(
+                &camera(),
+                &world,
+                &mut state,
+                &sampling,
+                10,
+                Integrator::Rgb
+            ),
+            0
+        );
+        assert_eq!(state.nb_passes(), 3);
+        assert!(state
+            .scene()
+            .iter()
+            .flatten()
+            .all(|pixel| pixel.count() == 3));
+    }
+
+    #[test]
+    fn it_should_be_reproducible_with_a_seed() {
+        let material = Lambertian::new(&Color3::new(0.5, 0.5, 0.5));
+        let sphere = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+        let mut world = HittableList::new();
+        world.add(&sphere);
+
+        let sampling = AdaptiveSampling::fixed(2);
+        let render = |seed| {
+            let mut state = RenderState::new(8, 8, seed);
+            while render_pass(
+                &camera(),
+                &world,
+                &mut state,
+                &sampling,
+                10,
+                Integrator::Rgb,
+            ) > 0
+            {}
+            state
+        };
+
+        assert_eq!(render(7), render(7));
+        assert_ne!(render(7), render(8));
+    }
+
+    #[test]
+    fn it_should_render_the_same_colors_spectrally() {
+        let material = Lambertian::new(&Color3::new(0.5, 0.5, 0.5));
+        let sphere = Sphere::new(&Point3::new(0.0, 0.0, 0.0), 1.0, &material);
+        let mut world = HittableList::new();
+        world.add(&sphere);
+
+        let sampling = AdaptiveSampling::fixed(1000);
+        let render = |integrator| {
+            let mut state = RenderState::new(8, 8, 3);
+            while render_pass(&camera(), &world, &mut state, &sampling, 10, integrator) > 0 {}
+            state.into_scene()
+        };
+        let rgb = render(Integrator::Rgb);
+        let spectral = render(Integrator::Spectral);
+
+        // the sky and the sphere (not its edges), up to the noise of the sampled wavelengths
+        // (mostly on blue, where few wavelengths are sampled)
+        for (line, column) in [(0, 0), (7, 0), (6, 7), (3, 3), (4, 4), (3, 4)] {
+            let expected = rgb[line][column].color();
+            let color = spectral[line][column].color();
+            assert!(
+                color.distance(&expected) < 0.05,
+                "{color:?} instead of {expected:?} at ({line}, {column})"
+            );
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::OnceLock;
+
+use crate::{Color3, Vec3};
+
+/// shortest wavelength of the visible light, in nanometers
+pub const LAMBDA_MIN: f64 = 360.0;
+/// longest wavelength of the visible light, in nanometers
+pub const LAMBDA_MAX: f64 = 830.0;
+
+/// CIE 1931 color matching functions (x̄, ȳ, z̄) at a wavelength in nanometers,
+/// from the multi-lobe gaussian fit of Wyman, Sloan and Shirley (2013)
+pub fn color_matching(lambda: f64) -> Vec3 {
+    // a gaussian with a different width on each side of its mean
+    let lobe = |mean: f64, sigma_below: f64, sigma_above: f64| {
+        let sigma = if lambda < mean {
+            sigma_below
+        } else {
+            sigma_above
+        };
+        let t = (lambda - mean) / sigma;
+        (-0.5 * t * t).exp()
+    };
+
+    Vec3::new(
+        1.056 * lobe(599.8, 37.9, 31.0) + 0.362 * lobe(442.0, 16.0, 26.7)
+            - 0.065 * lobe(501.1, 20.4, 26.2),
+        0.821 * lobe(568.8, 46.9, 40.5) + 0.286 * lobe(530.9, 16.3, 31.1),
+        1.217 * lobe(437.0, 11.8, 36.0) + 0.681 * lobe(459.0, 26.0, 13.8),
+    )
+}
+
+/// The constants of the conversions from spectra to colors, integrated once
+struct Calibration {
+    /// integral of ȳ: a constant spectrum of 1 has a luminance of 1
+    y_integral: f64,
+    /// the equal energy spectrum is not the white of srgb (d65): colors are white balanced channel by channel
+    white_balance: Color3,
+}
+
+fn calibration() -> &'static Calibration {
+    static CALIBRATION: OnceLock<Calibration> = OnceLock::new();
+
+    CALIBRATION.get_or_init(|| {
+        let integrals = integrate(color_matching);
+        let y_integral = integrals.y();
+        let white = Color3::from_xyz(&(integrals / y_integral));
+
+        Calibration {
+            y_integral,
+            white_balance: white.map(|channel| 1.0 / channel),
+        }
+    })
+}
+
+/// sum of `f` over the visible wavelengths, by steps of 1 nm
+fn integrate(f: impl Fn(f64) -> Vec3) -> Vec3 {
+    let nb_steps = (LAMBDA_MAX - LAMBDA_MIN) as u32;
+
+    (0..=nb_steps).fold(Vec3::new(0.0, 0.0, 0.0), |sum, step| {
+        sum + f(LAMBDA_MIN + step as f64)
+    })
+}
+
+/// XYZ coordinates of a spectrum, scaled so that the constant spectrum of 1 has a luminance of 1
+pub(crate) fn spectrum_to_xyz(spectrum: impl Fn(f64) -> f64) -> Vec3 {
+    integrate(|lambda| color_matching(lambda) * spectrum(lambda)) / calibration().y_integral
+}
+
+/// integral of ȳ over the visible wavelengths, the scale of XYZ coordinates
+pub(crate) fn y_integral() -> f64 {
+    calibration().y_integral
+}
+
+/// the white balanced color of XYZ coordinates: the constant spectrum of 1 is white
+pub(crate) fn xyz_to_color(xyz: &Vec3) -> Color3 {
+    Color3::from_xyz(xyz) * calibration().white_balance
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn it_should_match_the_cie_observer() {
+        // the peak of the luminous efficiency, and the blue and red lobes
+        assert!((color_matching(555.0).y() - 1.0).abs() < 0.01);
+        assert!(color_matching(555.0).y() > color_matching(520.0).y());
+        assert!((color_matching(600.0).x() - 1.06).abs() < 0.01);
+        assert!((color_matching(445.0).z() - 1.78).abs() < 0.03);
+
+        // the tabulated integral of ȳ is 106.857
+        assert!((y_integral() - 106.857).abs() < 0.2);
+    }
+
+    #[test]
+    fn it_should_convert_the_constant_spectrum_to_white() {
+        let white = xyz_to_color(&spectrum_to_xyz(|_| 1.0));
+        assert!(white.distance(&Color3::white()) < 1e-12);
+
+        let grey = xyz_to_color(&spectrum_to_xyz(|_| 0.25));
+        assert!(grey.distance(&Color3::new(0.25, 0.25, 0.25)) < 1e-12);
+
+        // a monochromatic light out of the visible range has no color
+        let infrared = xyz_to_color(&spectrum_to_xyz(|lambda| (lambda > 800.0) as u8 as f64));
+        assert!(infrared.luminance() < 1e-3);
+    }
+}
@@ -0,0 +1,16 @@
+//! Spectral rendering: paths carry light for a few sampled wavelengths instead of three rgb channels,
+//! so wavelength dependent effects (like the dispersion of glass) can be rendered.
+//! Each path samples its own wavelengths (hero wavelength sampling), rgb colors of the scene are
+//! upsampled to smooth spectra, and the radiance is converted back to rgb through CIE XYZ
+
+mod cie;
+pub use cie::{color_matching, LAMBDA_MAX, LAMBDA_MIN};
+
+mod sampled;
+pub use sampled::SampledSpectrum;
+
+mod upsampling;
+pub use upsampling::RgbSpectrum;
+
+mod wavelengths;
+pub use wavelengths::{SampledWavelengths, NB_WAVELENGTHS};
@@ -0,0 +1,42 @@
+use std::ops;
+
+use super::NB_WAVELENGTHS;
+
+/// The values of a spectrum at the wavelengths sampled for a path (see `SampledWavelengths`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampledSpectrum([f64; NB_WAVELENGTHS]);
+
+impl SampledSpectrum {
+    pub fn new(values: [f64; NB_WAVELENGTHS]) -> Self {
+        Self(values)
+    }
+
+    /// the same value at every wavelength
+    pub fn constant(value: f64) -> Self {
+        Self([value; NB_WAVELENGTHS])
+    }
+
+    pub fn values(&self) -> &[f64; NB_WAVELENGTHS] {
+        &self.0
+    }
+
+    /// the spectrum with `f` applied at each wavelength
+    pub fn map(&self, f: impl Fn(f64) -> f64) -> Self {
+        Self(self.0.map(f))
+    }
+}
+
+/// values multiplied wavelength by wavelength: the light filtered by a surface
+impl ops::Mul for SampledSpectrum {
+    type Output = SampledSpectrum;
+    fn mul(self, rhs: Self) -> Self {
+        Self(std::array::from_fn(|i| self.0[i] * rhs.0[i]))
+    }
+}
+
+impl ops::Mul<f64> for SampledSpectrum {
+    type Output = SampledSpectrum;
+    fn mul(self, rhs: f64) -> Self {
+        Self(self.0.map(|value| value * rhs))
+    }
+}
@@ -0,0 +1,134 @@
+use std::sync::OnceLock;
+
+use super::{cie, SampledSpectrum, SampledWavelengths};
+use crate::Color3;
+
+/// wavelengths (in nanometers) where the blue band meets the green one, and the green band the red one
+const BLUE_GREEN: f64 = 490.0;
+const GREEN_RED: f64 = 588.0;
+/// width of the transitions between bands, in nanometers
+const TRANSITION: f64 = 8.0;
+
+/// A smooth spectrum of an rgb color, to render the colors of a scene spectrally.
+/// It is a mix of three smooth bands (red, green and blue) summing to 1 at every wavelength,
+/// weighted so that the spectrum converts back to the same color: white is the constant spectrum of 1,
+/// and the colors of the srgb gamut give spectra close to [0, 1].
+/// Spectra can not be negative: very saturated colors are clamped
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RgbSpectrum {
+    /// weights of the red, green and blue bands
+    weights: [f64; 3],
+}
+
+impl RgbSpectrum {
+    pub fn new(color: &Color3) -> Self {
+        let inverse = band_colors_inverse();
+        let rgb = [color.r(), color.g(), color.b()];
+
+        Self {
+            weights: inverse.map(|row| row.iter().zip(&rgb).map(|(a, b)| a * b).sum()),
+        }
+    }
+
+    /// value of the spectrum at a wavelength in nanometers
+    pub fn at(&self, lambda: f64) -> f64 {
+        bands(lambda)
+            .iter()
+            .zip(&self.weights)
+            .map(|(band, weight)| band * weight)
+            .sum::<f64>()
+            .max(0.0)
+    }
+
+    /// values of the spectrum at the wavelengths of a path
+    pub fn sample(&self, wavelengths: &SampledWavelengths) -> SampledSpectrum {
+        SampledSpectrum::new(wavelengths.lambdas().map(|lambda| self.at(lambda)))
+    }
+}
+
+/// values of the red, green and blue bands at a wavelength in nanometers
+fn bands(lambda: f64) -> [f64; 3] {
+    let sigmoid = |x: f64| 1.0 / (1.0 + (-x).exp());
+
+    let red = sigmoid((lambda - GREEN_RED) / TRANSITION);
+    let blue = 1.0 - sigmoid((lambda - BLUE_GREEN) / TRANSITION);
+
+    [red, 1.0 - red - blue, blue]
+}
+
+/// the matrix giving the weights of the bands of a color:
+/// the inverse of the matrix whose columns are the colors of the bands
+fn band_colors_inverse() -> &'static [[f64; 3]; 3] {
+    static INVERSE: OnceLock<[[f64; 3]; 3]> = OnceLock::new();
+
+    INVERSE.get_or_init(|| {
+        let columns = [0, 1, 2].map(|band| {
+            let color = cie::xyz_to_color(&cie::spectrum_to_xyz(|lambda| bands(lambda)[band]));
+            [color.r(), color.g(), color.b()]
+        });
+        let m = [0, 1, 2].map(|row| columns.map(|column| column[row]));
+
+        // cofactors over the determinant (the bands are close to the primaries: far from singular)
+        let cofactor = |row: usize, column: usize| {
+            let (r0, r1) = ((row + 1) % 3, (row + 2) % 3);
+            let (c0, c1) = ((column + 1) % 3, (column + 2) % 3);
+            m[r0][c0] * m[r1][c1] - m[r0][c1] * m[r1][c0]
+        };
+        let determinant: f64 = (0..3)
+            .map(|column| m[0][column] * cofactor(0, column))
+            .sum();
+
+        [0, 1, 2].map(|row| [0, 1, 2].map(|column| cofactor(column, row) / determinant))
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// the color of a spectrum, integrated over every wavelength
+    fn color(spectrum: &RgbSpectrum) -> Color3 {
+        cie::xyz_to_color(&cie::spectrum_to_xyz(|lambda| spectrum.at(lambda)))
+    }
+
+    #[test]
+    fn it_should_upsample_white_to_a_constant_spectrum() {
+        let white = RgbSpectrum::new(&Color3::white());
+
+        for lambda in [380.0, 450.0, 550.0, 650.0, 800.0] {
+            assert!((white.at(lambda) - 1.0).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn it_should_give_back_the_same_color() {
+        for rgb in [
+            Color3::new(0.5, 0.5, 0.5),
+            Color3::new(0.8, 0.1, 0.1),
+            Color3::new(0.2, 0.6, 0.3),
+            Color3::new(0.1, 0.2, 0.9),
+            Color3::new(0.9, 0.7, 0.1),
+        ] {
+            let spectrum = RgbSpectrum::new(&rgb);
+            assert!(color(&spectrum).distance(&rgb) < 1e-9, "{rgb:?}");
+
+            // a reflectance stays a reflectance
+            for lambda in (360..=830).step_by(10) {
+                let value = spectrum.at(lambda as f64);
+                assert!(
+                    (0.0..=1.0).contains(&value),
+                    "{value} at {lambda} for {rgb:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn it_should_put_colors_at_their_wavelengths() {
+        let red = RgbSpectrum::new(&Color3::new(1.0, 0.0, 0.0));
+        let blue = RgbSpectrum::new(&Color3::new(0.0, 0.0, 1.0));
+
+        assert!(red.at(650.0) > 0.9 && red.at(450.0) < 0.1);
+        assert!(blue.at(450.0) > 0.9 && blue.at(650.0) < 0.1);
+    }
+}
@@ -0,0 +1,146 @@
+use super::{cie, SampledSpectrum, LAMBDA_MAX, LAMBDA_MIN};
+use crate::{Color3, Vec3};
+
+/// number of wavelengths carried by a path
+pub const NB_WAVELENGTHS: usize = 4;
+
+/// The wavelengths (in nanometers) carried by a path, with their probability densities.
+/// Hero wavelength sampling: a single random number gives the first (hero) wavelength,
+/// the others are spread evenly from it, so the whole visible range is covered by every path.
+/// Wavelengths are sampled close to the sensitivity of the eye rather than uniformly
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SampledWavelengths {
+    lambdas: [f64; NB_WAVELENGTHS],
+    pdfs: [f64; NB_WAVELENGTHS],
+}
+
+impl SampledWavelengths {
+    /// `u` is a uniform random number in [0, 1)
+    pub fn sample(u: f64) -> Self {
+        let lambdas = std::array::from_fn(|i| {
+            // the secondary wavelengths are evenly rotated from the hero in sample space
+            let u_i = (u + i as f64 / NB_WAVELENGTHS as f64).fract();
+            sample_visible(u_i)
+        });
+
+        Self {
+            lambdas,
+            pdfs: lambdas.map(visible_pdf),
+        }
+    }
+
+    pub fn lambdas(&self) -> &[f64; NB_WAVELENGTHS] {
+        &self.lambdas
+    }
+
+    /// the first wavelength, the only one left once the secondary wavelengths are terminated
+    pub fn hero(&self) -> f64 {
+        self.lambdas[0]
+    }
+
+    /// keep only the hero wavelength. Used when the path depends on the wavelength
+    /// (e.g. the refraction by a dispersive medium): the other wavelengths can not follow it
+    pub fn terminate_secondary(&mut self) {
+        if self.is_secondary_terminated() {
+            return;
+        }
+
+        // the hero now stands for every wavelength of the path
+        self.pdfs[0] /= NB_WAVELENGTHS as f64;
+        self.pdfs[1..].fill(0.0);
+    }
+
+    pub fn is_secondary_terminated(&self) -> bool {
+        self.pdfs[1..].iter().all(|pdf| *pdf == 0.0)
+    }
+
+    /// the rgb color of a radiance carried at these wavelengths:
+    /// a Monte Carlo estimate of its XYZ coordinates, converted to rgb
+    pub fn to_color(&self, radiance: &SampledSpectrum) -> Color3 {
+        let xyz = self
+            .lambdas
+            .iter()
+            .zip(&self.pdfs)
+            .zip(radiance.values())
+            .filter(|((_, pdf), _)| **pdf > 0.0)
+            .fold(Vec3::new(0.0, 0.0, 0.0), |sum, ((lambda, pdf), value)| {
+                sum + cie::color_matching(*lambda) * (value / pdf)
+            });
+
+        cie::xyz_to_color(&(xyz / (NB_WAVELENGTHS as f64 * cie::y_integral())))
+    }
+}
+
+/// a wavelength of the visible range with a density close to the luminous efficiency ȳ
+/// (the inverse of the cumulative distribution of `visible_pdf`)
+fn sample_visible(u: f64) -> f64 {
+    538.0 - 138.888_889 * (0.856_910_62 - 1.827_501_97 * u).atanh()
+}
+
+/// probability density of the wavelengths given by `sample_visible`
+fn visible_pdf(lambda: f64) -> f64 {
+    if !(LAMBDA_MIN..=LAMBDA_MAX).contains(&lambda) {
+        return 0.0;
+    }
+
+    0.003_939_804_2 / (0.0072 * (lambda - 538.0)).cosh().powi(2)
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    use super::*;
+
+    #[test]
+    fn it_should_sample_the_visible_range() {
+        assert!((sample_visible(0.0) - LAMBDA_MIN).abs() < 1.0);
+        assert!((sample_visible(1.0 - 1e-12) - LAMBDA_MAX).abs() < 1.0);
+
+        // the density sums to 1 over the visible range
+        let total: f64 = (0..4700)
+            .map(|i| visible_pdf(LAMBDA_MIN + (i as f64 + 0.5) * 0.1) * 0.1)
+            .sum();
+        assert!((total - 1.0).abs() < 1e-3);
+
+        // the density is the derivative of the sampling
+        let u = 0.3;
+        let derivative = (sample_visible(u + 1e-6) - sample_visible(u - 1e-6)) / 2e-6;
+        assert!((visible_pdf(sample_visible(u)) * derivative - 1.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn it_should_spread_the_wavelengths_from_the_hero() {
+        let wavelengths = SampledWavelengths::sample(0.9);
+        let lambdas = wavelengths.lambdas();
+
+        assert_eq!(wavelengths.hero(), sample_visible(0.9));
+        for (i, u) in [0.15, 0.4, 0.65].into_iter().enumerate() {
+            assert!((lambdas[i + 1] - sample_visible(u)).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn it_should_estimate_the_color_of_a_spectrum() {
+        let mut rng = StdRng::seed_from_u64(48);
+        let nb_samples = 20_000;
+
+        // a constant spectrum is white, with or without the secondary wavelengths
+        for terminated in [false, true] {
+            let mut sum = Color3::black();
+            for _ in 0..nb_samples {
+                let mut wavelengths = SampledWavelengths::sample(rng.gen());
+                if terminated {
+                    wavelengths.terminate_secondary();
+                }
+                sum += wavelengths.to_color(&SampledSpectrum::constant(0.5));
+            }
+
+            let mean = sum / nb_samples as f64;
+            assert!(
+                mean.distance(&Color3::new(0.5, 0.5, 0.5)) < 0.02,
+                "{mean:?} with terminated secondary wavelengths: {terminated}"
+            );
+        }
+    }
+}
@@ -11,35 +11,6 @@ pub struct Vec3 {
 }
 
 pub type Point3 = Vec3;
-pub type Color3 = Vec3;
-
-impl Color3 {
-    pub fn as_ppm(&self, samples_per_pixel: u32) -> String {
-        let scale = 1.0 / samples_per_pixel as f64;
-
-        // scale the color per sample and add a gamma correct of 2 (sqrrt = power 1/2)
-        let (r, g, b) = (
-            (self.x() * scale).sqrt(),
-            (self.y() * scale).sqrt(),
-            (self.z() * scale).sqrt(),
-        );
-
-        let ir = (256.0 * r.clamp(0.0, 0.999)) as u32;
-        let ig = (256.0 * g.clamp(0.0, 0.999)) as u32;
-        let ib = (256.0 * b.clamp(0.0, 0.999)) as u32;
-
-        format!("{ir} {ig} {ib}")
-    }
-
-    /// a black Color3 with all chanels at 0
-    pub fn black() -> Self {
-        Self::new(0.0, 0.0, 0.0)
-    }
-
-    pub fn white() -> Self {
-        Self::new(1.0, 1.0, 1.0)
-    }
-}
 
 impl Vec3 {
     pub fn new(x: f64, y: f64, z: f64) -> Self {
@@ -47,7 +18,7 @@ impl Vec3 {
     }
 
     // returns a new random vector with coordinates in the specified range
-    pub fn new_clamped_random(min: f64, max: f64, rng: &mut impl Rng) -> Self {
+    pub fn new_clamped_random(min: f64, max: f64, rng: &mut (impl Rng + ?Sized)) -> Self {
         Self {
             x: rng.gen_range(min..=max),
             y: rng.gen_range(min..=max),
@@ -58,7 +29,7 @@ impl Vec3 {
     /// create a vector in the unit sphere. Creating a random vector in the unit cube until it's in the sphere
     /// Probability of success per iteration. 4/3 pi / 8 ~= 0.52... not so great but will converge eventually
     /// ugly
-    pub fn new_randow_in_unit_sphere(rng: &mut impl Rng) -> Self {
+    pub fn new_randow_in_unit_sphere(rng: &mut (impl Rng + ?Sized)) -> Self {
         loop {
             let vector = Self::new_clamped_random(-1.0, 1.0, rng);
             if vector.mag_squared() < 1.0 {
@@ -68,7 +39,7 @@ impl Vec3 {
     }
 
     /// create a vector in the unit sphere.
-    pub fn new_random_in_unit_disk(rng: &mut impl Rng) -> Self {
+    pub fn new_random_in_unit_disk(rng: &mut (impl Rng + ?Sized)) -> Self {
         loop {
             let vector = Self {
                 x: rng.gen_range(-1.0..=1.0),
@@ -82,7 +53,7 @@ impl Vec3 {
         }
     }
 
-    pub fn new_randow_unit_vector(rng: &mut impl Rng) -> Self {
+    pub fn new_randow_unit_vector(rng: &mut (impl Rng + ?Sized)) -> Self {
         Self::new_randow_in_unit_sphere(rng).normalize()
     }
 
@@ -132,14 +103,22 @@ impl Vec3 {
         )
     }
 
-    /// create a vector with coordinates multiplied term by term
-    /// e.g. (1, 2, 3).hadamar((2, 3, 4)) = (2, 6, 12)
-    pub fn hadamar(&self, other: &Self) -> Self {
-        Vec3 {
-            x: self.x * other.x,
-            y: self.y * other.y,
-            z: self.z * other.z,
-        }
+    /// the smallest coordinates of both vectors
+    pub fn min(&self, other: &Self) -> Self {
+        Vec3::new(
+            self.x.min(other.x),
+            self.y.min(other.y),
+            self.z.min(other.z),
+        )
+    }
+
+    /// the largest coordinates of both vectors
+    pub fn max(&self, other: &Self) -> Self {
+        Vec3::new(
+            self.x.max(other.x),
+            self.y.max(other.y),
+            self.z.max(other.z),
+        )
     }
 
     // Physic -----------------------------------
@@ -150,9 +129,9 @@ impl Vec3 {
 
     /// Refract the light knowing a normal and the ratio of material indices
     pub fn refract(&self, n: &Vec3, refraction_ratio: f64) -> Vec3 {
-        let cos_theta = (*self * -1.0).dot(n).min(1.0);
+        let cos_theta = (-*self).dot(n).min(1.0);
         let r_out_perpendicular = (*self + *n * cos_theta) * refraction_ratio;
-        let r_out_parallel = *n * -1.0 * (1.0 - r_out_perpendicular.mag_squared()).abs().sqrt();
+        let r_out_parallel = -*n * (1.0 - r_out_perpendicular.mag_squared()).abs().sqrt();
 
         r_out_perpendicular + r_out_parallel
     }
@@ -193,6 +172,36 @@ impl ops::Mul<f64> for Vec3 {
     }
 }
 
+/// coordinates multiplied term by term: (1, 2, 3) * (2, 3, 4) = (2, 6, 12)
+impl ops::Mul for Vec3 {
+    type Output = Vec3;
+    fn mul(self, rhs: Self) -> Self {
+        Self {
+            x: self.x() * rhs.x(),
+            y: self.y() * rhs.y(),
+            z: self.z() * rhs.z(),
+        }
+    }
+}
+
+impl ops::Mul<Vec3> for f64 {
+    type Output = Vec3;
+    fn mul(self, rhs: Vec3) -> Vec3 {
+        rhs * self
+    }
+}
+
+impl ops::Neg for Vec3 {
+    type Output = Vec3;
+    fn neg(self) -> Self {
+        Self {
+            x: -self.x(),
+            y: -self.y(),
+            z: -self.z(),
+        }
+    }
+}
+
 impl ops::Div<f64> for Vec3 {
     type Output = Vec3;
     fn div(self, rhs: f64) -> Self {
@@ -207,6 +216,30 @@ impl ops::Div<f64> for &Vec3 {
     }
 }
 
+/// coordinates by axis: 0 is x, 1 is y and 2 is z
+impl ops::Index<usize> for Vec3 {
+    type Output = f64;
+    fn index(&self, axis: usize) -> &f64 {
+        match axis {
+            0 => &self.x,
+            1 => &self.y,
+            2 => &self.z,
+            _ => panic!("no axis {axis} in 3 dimensions"),
+        }
+    }
+}
+
+impl ops::IndexMut<usize> for Vec3 {
+    fn index_mut(&mut self, axis: usize) -> &mut f64 {
+        match axis {
+            0 => &mut self.x,
+            1 => &mut self.y,
+            2 => &mut self.z,
+            _ => panic!("no axis {axis} in 3 dimensions"),
+        }
+    }
+}
+
 // Overloading assign operators for convenience
 
 impl ops::AddAssign for Vec3 {
@@ -260,5 +293,30 @@ mod tests {
         assert_eq!(Vec3::new(1.0, 2.0, 3.0) * 2.0, Vec3::new(2.0, 4.0, 6.0));
         assert_eq!(Vec3::new(1.0, 2.0, 3.0) * -1.0, Vec3::new(-1.0, -2.0, -3.0));
         assert_eq!(Vec3::new(2.0, 4.0, 6.0) / 2.0, Vec3::new(1.0, 2.0, 3.0));
+
+        assert_eq!(-Vec3::new(1.0, -2.0, 3.0), Vec3::new(-1.0, 2.0, -3.0));
+        assert_eq!(2.0 * Vec3::new(1.0, 2.0, 3.0), Vec3::new(2.0, 4.0, 6.0));
+        assert_eq!(
+            Vec3::new(1.0, 2.0, 3.0) * Vec3::new(2.0, 3.0, 4.0),
+            Vec3::new(2.0, 6.0, 12.0)
+        );
+    }
+
+    #[test]
+    fn it_should_access_coordinates_by_axis() {
+        let mut vector = Vec3::new(1.0, 2.0, 3.0);
+        assert_eq!([vector[0], vector[1], vector[2]], [1.0, 2.0, 3.0]);
+
+        vector[1] = 5.0;
+        assert_eq!(vector, Vec3::new(1.0, 5.0, 3.0));
+    }
+
+    #[test]
+    fn it_should_compute_component_bounds() {
+        let a = Vec3::new(1.0, 5.0, -3.0);
+        let b = Vec3::new(2.0, -1.0, -4.0);
+
+        assert_eq!(a.min(&b), Vec3::new(1.0, -1.0, -4.0));
+        assert_eq!(a.max(&b), Vec3::new(2.0, 5.0, -3.0));
     }
 }
@@ -0,0 +1,408 @@
+//! Regression tests of the rendered images.
+//! Small canonical scenes are rendered with a fixed seed, and compared to the reference images
+//! of tests/references with the FLIP perceptual error.
+//! On failure, the render and a false color difference image are written in the target directory.
+//!
+//! After an intended change of the rendering, the references are generated again with
+//! `UPDATE_REFERENCES=1 cargo test --test reference_images`
+
+use std::{env, fs, path::PathBuf};
+
+use gpu_attempt::{
+    hittable::{Hittable, HittableList, Quad, Sphere, Tetrahedron, Triangle},
+    image_diff::{flip, mse, psnr, DEFAULT_PIXELS_PER_DEGREE},
+    image_io::{load_image, save_image, FloatImage},
+    material::{Dielectric, Lambertian, Material, Metal},
+    render::{render_pass, AdaptiveSampling, Integrator, RenderState},
+    Camera, Color3, Point3, Vec3,
+};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+/// the seed of every render: the same seed gives the same image
+const SEED: u64 = 42;
+const MAX_DEPTH: u32 = 20;
+
+struct Render {
+    name: &'static str,
+    width: u32,
+    height: u32,
+    samples_per_pixel: u32,
+    /// the highest mean FLIP error accepted
+    tolerance: f64,
+}
+
+impl Render {
+    fn run(&self, camera: &Camera, world: &dyn Hittable) -> FloatImage {
+        let sampling = AdaptiveSampling::fixed(self.samples_per_pixel);
+        let mut state = RenderState::new(self.width, self.height, SEED);
+
+        while render_pass(
+            camera,
+            world,
+            &mut state,
+            &sampling,
+            MAX_DEPTH,
+            Integrator::Rgb,
+        ) > 0
+        {}
+
+        FloatImage::from_scene(state.scene())
+    }
+
+    fn check(&self, camera: &Camera, world: &dyn Hittable) {
+        let image = self.run(camera, world);
+        let reference_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/references")
+            .join(format!("{}.pfm", self.name));
+
+        if env::var_os("UPDATE_REFERENCES").is_some() {
+            fs::create_dir_all(reference_path.parent().unwrap()).unwrap();
+            save_image(&reference_path, &image).unwrap();
+            return;
+        }
+
+        let reference = load_image(&reference_path).unwrap_or_else(|error| {
+            panic!(
+                "cannot read the reference {}: {error}\n\
+                 generate it with UPDATE_REFERENCES=1 cargo test --test reference_images",
+                reference_path.display()
+            )
+        });
+
+        assert!(
+            reference.same_size(&image),
+            "{}: the reference is {}x{}, the render is {}x{}",
+            self.name,
+            reference.width(),
+            reference.height(),
+            image.width(),
+            image.height()
+        );
+
+        let errors = flip(&reference, &image, DEFAULT_PIXELS_PER_DEGREE);
+
+        if errors.mean() > self.tolerance {
+            let output = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("reference_images");
+            let render_path = output.join(format!("{}.png", self.name));
+            let diff_path = output.join(format!("{}_diff.png", self.name));
+
+            fs::create_dir_all(&output).unwrap();
+            save_image(&render_path, &image).unwrap();
+            save_image(&diff_path, &errors.to_false_color()).unwrap();
+
+            panic!(
+                "{}: the render differs from its reference (mean FLIP error {:.4} above {}, max {:.4}, PSNR {:.2} dB)\n\
+                 render: {}\ndifference: {}",
+                self.name,
+                errors.mean(),
+                self.tolerance,
+                errors.max(),
+                psnr(&reference, &image),
+                render_path.display(),
+                diff_path.display()
+            );
+        }
+
+        // a perceptual metric can miss a global change of the exposure
+        assert!(
+            mse(&reference, &image) < 0.01,
+            "{}: the image is too different",
+            self.name
+        );
+    }
+}
+
+/// the six faces of a box, turned around its vertical axis by `angle` degrees
+fn box_faces<'a>(
+    min: &Point3,
+    max: &Point3,
+    angle: f64,
+    material: &'a dyn Material,
+) -> Vec<Quad<'a>> {
+    let center = (*min + *max) / 2.0;
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let corner = |x: f64, y: f64, z: f64| {
+        let (dx, dz) = (x - center.x(), z - center.z());
+        Point3::new(
+            center.x() + cos * dx + sin * dz,
+            y,
+            center.z() - sin * dx + cos * dz,
+        )
+    };
+
+    let (x0, y0, z0) = (min.x(), min.y(), min.z());
+    let (x1, y1, z1) = (max.x(), max.y(), max.z());
+
+    [
+        [(x0, y0, z0), (x1, y0, z0), (x1, y1, z0), (x0, y1, z0)],
+        [(x0, y0, z1), (x0, y1, z1), (x1, y1, z1), (x1, y0, z1)],
+        [(x0, y0, z0), (x0, y1, z0), (x0, y1, z1), (x0, y0, z1)],
+        [(x1, y0, z0), (x1, y0, z1), (x1, y1, z1), (x1, y1, z0)],
+        [(x0, y0, z0), (x0, y0, z1), (x1, y0, z1), (x1, y0, z0)],
+        [(x0, y1, z0), (x1, y1, z0), (x1, y1, z1), (x0, y1, z1)],
+    ]
+    .iter()
+    .map(|[a, b, c, d]| {
+        Quad::new(
+            &corner(a.0, a.1, a.2),
+            &corner(b.0, b.1, b.2),
+            &corner(c.0, c.1, c.2),
+            &corner(d.0, d.1, d.2),
+            material,
+        )
+        .unwrap()
+    })
+    .collect()
+}
+
+#[test]
+fn cornell_box() {
+    let red = Lambertian::new(&Color3::new(0.65, 0.05, 0.05));
+    let green = Lambertian::new(&Color3::new(0.12, 0.45, 0.15));
+    let white = Lambertian::new(&Color3::new(0.73, 0.73, 0.73));
+
+    // there is no light material: the sky lights the box through a hole in the ceiling and the open front
+    let point = Point3::new;
+    let walls = [
+        // left, right, floor and back
+        Quad::new(
+            &point(0.0, 0.0, 0.0),
+            &point(0.0, 0.0, 1.0),
+            &point(0.0, 1.0, 1.0),
+            &point(0.0, 1.0, 0.0),
+            &red,
+        )
+        .unwrap(),
+        Quad::new(
+            &point(1.0, 0.0, 0.0),
+            &point(1.0, 1.0, 0.0),
+            &point(1.0, 1.0, 1.0),
+            &point(1.0, 0.0, 1.0),
+            &green,
+        )
+        .unwrap(),
+        Quad::new(
+            &point(0.0, 0.0, 0.0),
+            &point(1.0, 0.0, 0.0),
+            &point(1.0, 0.0, 1.0),
+            &point(0.0, 0.0, 1.0),
+            &white,
+        )
+        .unwrap(),
+        Quad::new(
+            &point(0.0, 0.0, 1.0),
+            &point(1.0, 0.0, 1.0),
+            &point(1.0, 1.0, 1.0),
+            &point(0.0, 1.0, 1.0),
+            &white,
+        )
+        .unwrap(),
+        // the ceiling, around a 0.3 x 0.3 hole
+        Quad::new(
+            &point(0.0, 1.0, 0.0),
+            &point(0.35, 1.0, 0.0),
+            &point(0.35, 1.0, 1.0),
+            &point(0.0, 1.0, 1.0),
+            &white,
+        )
+        .unwrap(),
+        Quad::new(
+            &point(0.65, 1.0, 0.0),
+            &point(1.0, 1.0, 0.0),
+            &point(1.0, 1.0, 1.0),
+            &point(0.65, 1.0, 1.0),
+            &white,
+        )
+        .unwrap(),
+        Quad::new(
+            &point(0.35, 1.0, 0.0),
+            &point(0.65, 1.0, 0.0),
+            &point(0.65, 1.0, 0.35),
+            &point(0.35, 1.0, 0.35),
+            &white,
+        )
+        .unwrap(),
+        Quad::new(
+            &point(0.35, 1.0, 0.65),
+            &point(0.65, 1.0, 0.65),
+            &point(0.65, 1.0, 1.0),
+            &point(0.35, 1.0, 1.0),
+            &white,
+        )
+        .unwrap(),
+    ];
+    let tall_box = box_faces(&point(0.2, 0.0, 0.5), &point(0.45, 0.6, 0.75), 15.0, &white);
+    let short_box = box_faces(
+        &point(0.55, 0.0, 0.2),
+        &point(0.8, 0.3, 0.45),
+        -18.0,
+        &white,
+    );
+
+    let mut world = HittableList::new();
+    for quad in walls.iter().chain(&tall_box).chain(&short_box) {
+        world.add(quad);
+    }
+
+    let camera = Camera::new(
+        &point(0.5, 0.5, -1.4),
+        &point(0.5, 0.5, 0.0),
+        &Vec3::new(0.0, 1.0, 0.0),
+        40.0,
+        1.0,
+        0.0,
+        1.4,
+    );
+
+    Render {
+        name: "cornell_box",
+        width: 48,
+        height: 48,
+        samples_per_pixel: 32,
+        tolerance: 0.01,
+    }
+    .check(&camera, &world);
+}
+
+#[test]
+fn glass_and_metal_spheres() {
+    let ground = Lambertian::new(&Color3::new(0.5, 0.5, 0.5));
+    let glass = Dielectric::new(1.5);
+    let polished = Metal::new(&Color3::new(0.8, 0.6, 0.2), 0.0);
+    let fuzzy = Metal::new(&Color3::new(0.8, 0.8, 0.8), 0.3);
+
+    let ground = Sphere::new(&Point3::new(0.0, -100.5, -1.0), 100.0, &ground);
+    let center = Sphere::new(&Point3::new(0.0, 0.0, -1.0), 0.5, &glass);
+    let left = Sphere::new(&Point3::new(-1.0, 0.0, -1.0), 0.5, &polished);
+    let right = Sphere::new(&Point3::new(1.0, 0.0, -1.0), 0.5, &fuzzy);
+
+    let mut world = HittableList::new();
+    world.add(&ground);
+    world.add(&center);
+    world.add(&left);
+    world.add(&right);
+
+    let camera = Camera::new(
+        &Point3::new(0.0, 0.5, 2.0),
+        &Point3::new(0.0, 0.0, -1.0),
+        &Vec3::new(0.0, 1.0, 0.0),
+        40.0,
+        2.0,
+        0.0,
+        3.0,
+    );
+
+    Render {
+        name: "glass_and_metal_spheres",
+        width: 64,
+        height: 32,
+        samples_per_pixel: 32,
+        tolerance: 0.01,
+    }
+    .check(&camera, &world);
+}
+
+/// the scene of the main binary, with a smaller grid of little spheres
+#[test]
+fn spheres_scene() {
+    let mut rng = StdRng::seed_from_u64(2022);
+
+    let material_ground = Lambertian::new(&Color3::new(1.0, 0.0, 0.0));
+    let displacement = Vec3::new(4.0, 0.0, 0.0);
+    let quad_ground = Quad::new(
+        &(Point3::new(1.0, 0.0, -1.0) + displacement),
+        &(Point3::new(1.0, 0.0, 1.0) + displacement),
+        &(Point3::new(-1.0, 0.0, 1.0) + displacement),
+        &(Point3::new(-1.0, 0.0, -1.0) + displacement),
+        &material_ground,
+    )
+    .unwrap();
+
+    let material_dielectric = Dielectric::new(1.5);
+    let sphere_dielectric = Sphere::new(&Point3::new(0.0, 1.0, 0.0), 1.0, &material_dielectric);
+    let material_lambertian = Lambertian::new(&Color3::new(0.4, 0.2, 0.1));
+    let sphere_lambertian = Sphere::new(&Point3::new(-4.0, 1.0, 0.0), 1.0, &material_lambertian);
+    let material_metal = Metal::new(&Color3::new(0.7, 0.6, 0.5), 0.0);
+    let sphere_metal = Sphere::new(&Point3::new(4.0, 1.0, 0.0), 1.0, &material_metal);
+
+    let nb_grid_nodes = 5;
+    let mut spheres_element: Vec<(Point3, Box<dyn Material>)> = Vec::new();
+
+    for a in -nb_grid_nodes..nb_grid_nodes {
+        for b in -nb_grid_nodes..nb_grid_nodes {
+            let random_choose: f64 = rng.gen();
+            let center = Point3::new(
+                a as f64 + 0.9 * rng.gen::<f64>(),
+                0.2,
+                b as f64 + 0.9 * rng.gen::<f64>(),
+            );
+
+            if (center - Point3::new(4.0, 0.2, 0.0)).mag() > 0.9 {
+                let sphere_material: Box<dyn Material> = if random_choose < 0.8 {
+                    let albedo = Color3::new_clamped_random(0.0, 1.0, &mut rng)
+                        * Color3::new_clamped_random(0.0, 1.0, &mut rng);
+                    Box::new(Lambertian::new(&albedo))
+                } else if random_choose < 0.95 {
+                    let albedo = Color3::new_clamped_random(0.5, 1.0, &mut rng);
+                    let fuzziness = rng.gen_range(0.0..=0.5);
+                    Box::new(Metal::new(&albedo, fuzziness))
+                } else {
+                    Box::new(Dielectric::new(1.5))
+                };
+
+                spheres_element.push((center, sphere_material));
+            }
+        }
+    }
+
+    let spheres = spheres_element
+        .iter()
+        .map(|(center, material)| Sphere::new(center, 0.2, material.as_ref()))
+        .collect::<Vec<_>>();
+
+    let triangle = Triangle::new(
+        &Point3::new(0.0, 2.0, 0.0),
+        &Point3::new(-1.0, 0.0, 0.0),
+        &Point3::new(1.0, 0.0, 1.0),
+        &material_metal,
+    );
+    let side = Vec3::new(2.0, 0.0, 2.0);
+    let tetrahedron = Tetrahedron::new(
+        &(Point3::new(1.0, 1.5, 0.0) + side),
+        &(Point3::new(-1.0, 2.0, 1.0) + side),
+        &(Point3::new(-1.0, 2.0, -1.0) + side),
+        &(Point3::new(0.0, 0.0, 0.0) + side),
+        &material_metal,
+    );
+
+    let mut world = HittableList::new();
+    world.add(&quad_ground);
+    world.add(&sphere_dielectric);
+    world.add(&sphere_lambertian);
+    world.add(&sphere_metal);
+    for sphere in &spheres {
+        world.add(sphere);
+    }
+    world.add(&triangle);
+    world.add(&tetrahedron);
+
+    // no depth of field: a sharp image is a better reference
+    let camera = Camera::new(
+        &Point3::new(13.0, 2.0, 3.0),
+        &Point3::new(0.0, 0.0, 0.0),
+        &Vec3::new(0.0, 1.0, 0.0),
+        20.0,
+        3.0 / 2.0,
+        0.0,
+        10.0,
+    );
+
+    Render {
+        name: "spheres_scene",
+        width: 72,
+        height: 48,
+        samples_per_pixel: 32,
+        tolerance: 0.01,
+    }
+    .check(&camera, &world);
+}